@@ -86,7 +86,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         };
         
         let truncated = if annotation.description.len() == 40 && 
-                          annotation.description.chars().last() != Some(' ') &&
+                          !annotation.description.ends_with(' ') &&
                           !annotation.description.ends_with('.') {
             " [截断]"
         } else {
@@ -97,8 +97,7 @@ fn main() -> Result<()> {
     
     // 读取所有数据
     let mut total_samples_read = 0;
-    for signal_idx in 0..signals.len() {
-        let signal = &signals[signal_idx];
+    for (signal_idx, signal) in signals.iter().enumerate() {
         let samples_to_read = signal.samples_in_file as usize;
         
         reader.rewind(signal_idx)?;
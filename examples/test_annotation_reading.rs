@@ -90,12 +90,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     
     // 验证注释是否正确
-    let expected_annotations = vec![
-        (1.5, Some(0.5), "Test annotation 1"),
+    let expected_annotations = [(1.5, Some(0.5), "Test annotation 1"),
         (3.0, None, "Test annotation 2"),
         (5.25, Some(1.0), "Test annotation 3 with longer duration"),
-        (7.8, None, "Final test annotation"),
-    ];
+        (7.8, None, "Final test annotation")];
     
     println!("\nValidation:");
     let mut all_correct = true;
@@ -47,8 +47,7 @@ fn main() -> Result<()> {
     // 读取一些样本数据进行验证
     println!("\n🔬 数据样本验证:");
     let num_channels_to_check = 3.min(signal_count);
-    for chan_idx in 0..num_channels_to_check {
-        let (signal_name, _, _, _, _) = &signals_info[chan_idx];
+    for (chan_idx, (signal_name, _, _, _, _)) in signals_info.iter().enumerate().take(num_channels_to_check) {
         let samples = reader.read_physical_samples(chan_idx, 10)?;
         println!("  {} (前10个样本): {:.2?}...", signal_name, &samples[..samples.len().min(3)]);
         
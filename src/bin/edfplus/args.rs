@@ -0,0 +1,84 @@
+/// Command line of one subcommand: positional arguments and `--name [value]` options
+pub struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+/// 不带值的选项
+const FLAGS: &[&str] = &["json", "digital", "keep-date", "help"];
+
+impl Args {
+    /// Splits arguments into positionals and options (`--name value` or `--name=value`)
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(option) = arg.strip_prefix("--") else {
+                positional.push(arg);
+                continue;
+            };
+            if let Some((name, value)) = option.split_once('=') {
+                options.push((name.to_string(), Some(value.to_string())));
+            } else if FLAGS.contains(&option) {
+                options.push((option.to_string(), None));
+            } else {
+                let value = args.next().ok_or_else(|| format!("Option --{} needs a value", option))?;
+                options.push((option.to_string(), Some(value)));
+            }
+        }
+        Ok(Args { positional, options })
+    }
+
+    /// Rejects options outside `allowed` and a wrong number of positionals
+    pub fn check(&self, allowed: &[&str], positional: &[&str]) -> Result<(), String> {
+        if let Some((name, _)) = self.options.iter().find(|(name, _)| !allowed.contains(&name.as_str())) {
+            return Err(format!("Unknown option --{}", name));
+        }
+        if self.positional.len() != positional.len() {
+            return Err(format!("Expected arguments: {}", positional.join(" ")));
+        }
+        Ok(())
+    }
+
+    pub fn positional(&self, index: usize) -> &str {
+        &self.positional[index]
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == name)
+    }
+
+    /// Last value given for an option
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values(name).pop()
+    }
+
+    /// All values given for a repeatable option, in order
+    pub fn values(&self, name: &str) -> Vec<&str> {
+        self.options.iter()
+            .filter(|(option, _)| option == name)
+            .filter_map(|(_, value)| value.as_deref())
+            .collect()
+    }
+
+    /// Value of an option in seconds
+    pub fn seconds(&self, name: &str) -> Result<Option<f64>, String> {
+        self.value(name)
+            .map(|value| match value.parse::<f64>() {
+                Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => Ok(seconds),
+                _ => Err(format!("--{} expects a non-negative number of seconds, got '{}'", name, value)),
+            })
+            .transpose()
+    }
+
+    /// Value of an option in Hz
+    pub fn hertz(&self, name: &str) -> Result<Option<f64>, String> {
+        self.value(name)
+            .map(|value| match value.parse::<f64>() {
+                Ok(hertz) if hertz.is_finite() && hertz > 0.0 => Ok(hertz),
+                _ => Err(format!("--{} expects a positive frequency in Hz, got '{}'", name, value)),
+            })
+            .transpose()
+    }
+}
@@ -0,0 +1,484 @@
+use std::io::{self, BufWriter, Write};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use edfplus::{
+    CropOptions, EdfError, EdfHeader, EdfReader, EdfWriter, ExportDtype, ExportOptions, FilterChain, StatsOptions,
+    EDFLIB_TIME_DIMENSION,
+};
+
+use crate::args::Args;
+use crate::json::{object, Json};
+use crate::CliError;
+
+/// Samples per signal that `dump` reads at a time
+const DUMP_CHUNK: usize = 65536;
+
+/// `Ok(false)` reports a negative result, e.g. a file that failed validation
+type CommandResult = Result<bool, CliError>;
+
+/// Prints the header, signal table and an annotation summary
+pub fn info(args: &Args) -> CommandResult {
+    args.check(&["json"], &["<file>"]).map_err(CliError::Usage)?;
+    let path = args.positional(0);
+    let reader = EdfReader::open(path)?;
+    let header = reader.header();
+
+    // 按出现次数统计注释描述
+    let mut descriptions: Vec<(&str, usize)> = Vec::new();
+    for annotation in reader.annotations() {
+        match descriptions.iter_mut().find(|(text, _)| *text == annotation.description) {
+            Some((_, count)) => *count += 1,
+            None => descriptions.push((&annotation.description, 1)),
+        }
+    }
+    descriptions.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    let mut out = io::stdout().lock();
+    if args.flag("json") {
+        let signals = header.signals.iter().enumerate()
+            .map(|(i, signal)| object(vec![
+                ("index", i.into()),
+                ("label", signal.label.as_str().into()),
+                ("sampling_frequency", header.sampling_frequency(i).ok().into()),
+                ("samples_per_record", signal.samples_per_record.into()),
+                ("samples", signal.samples_in_file.into()),
+                ("physical_min", signal.physical_min.into()),
+                ("physical_max", signal.physical_max.into()),
+                ("digital_min", signal.digital_min.into()),
+                ("digital_max", signal.digital_max.into()),
+                ("physical_dimension", signal.physical_dimension.as_str().into()),
+                ("prefilter", signal.prefilter.as_str().into()),
+                ("transducer", signal.transducer.as_str().into()),
+            ]))
+            .collect();
+        let descriptions = descriptions.iter()
+            .map(|(text, count)| object(vec![("description", (*text).into()), ("count", (*count).into())]))
+            .collect();
+        let json = object(vec![
+            ("file", path.into()),
+            ("start", format_datetime(&header.start_datetime(), 'T').into()),
+            ("duration", seconds(header.file_duration).into()),
+            ("data_records", header.datarecords_in_file.into()),
+            ("data_record_duration", seconds(header.datarecord_duration).into()),
+            ("discontinuous", header.discontinuous.into()),
+            ("patient", object(vec![
+                ("code", header.patient_code.as_str().into()),
+                ("sex", header.sex.as_str().into()),
+                ("birthdate", header.birthdate.as_str().into()),
+                ("name", header.patient_name.as_str().into()),
+                ("additional", header.patient_additional.as_str().into()),
+            ])),
+            ("recording", object(vec![
+                ("admin_code", header.admin_code.as_str().into()),
+                ("technician", header.technician.as_str().into()),
+                ("equipment", header.equipment.as_str().into()),
+                ("additional", header.recording_additional.as_str().into()),
+            ])),
+            ("signals", Json::Array(signals)),
+            ("annotations", object(vec![
+                ("count", reader.annotations().len().into()),
+                ("descriptions", Json::Array(descriptions)),
+            ])),
+        ]);
+        writeln!(out, "{}", json)?;
+        return Ok(true);
+    }
+
+    writeln!(out, "File:        {} ({})", path, if header.discontinuous { "EDF+D" } else { "EDF+C" })?;
+    writeln!(out, "Start:       {}", format_datetime(&header.start_datetime(), ' '))?;
+    writeln!(out, "Duration:    {} s ({} data records of {} s)",
+        seconds(header.file_duration), header.datarecords_in_file, seconds(header.datarecord_duration))?;
+    writeln!(out, "Patient:     code {}, sex {}, birthdate {}, name {}",
+        header.patient_code, header.sex, header.birthdate, header.patient_name)?;
+    writeln!(out, "Recording:   admin {}, technician {}, equipment {}",
+        header.admin_code, header.technician, header.equipment)?;
+
+    writeln!(out, "\nSignals ({}):", header.signals.len())?;
+    writeln!(out, "  {:>3}  {:<16}  {:>10}  {:<23}  {:<16}  Unit", "#", "Label", "Rate (Hz)", "Physical range", "Digital range")?;
+    for (i, signal) in header.signals.iter().enumerate() {
+        let rate = header.sampling_frequency(i).map(|f| f.to_string()).unwrap_or_else(|_| "-".to_string());
+        writeln!(out, "  {:>3}  {:<16}  {:>10}  {:<23}  {:<16}  {}",
+            i, signal.label, rate,
+            format!("{} .. {}", signal.physical_min, signal.physical_max),
+            format!("{} .. {}", signal.digital_min, signal.digital_max),
+            signal.physical_dimension)?;
+    }
+
+    writeln!(out, "\nAnnotations ({}):", reader.annotations().len())?;
+    for (text, count) in descriptions {
+        writeln!(out, "  {:>6}  {}", count, text)?;
+    }
+    Ok(true)
+}
+
+/// Lists every annotation with onset and duration in seconds
+pub fn annotations(args: &Args) -> CommandResult {
+    args.check(&["json"], &["<file>"]).map_err(CliError::Usage)?;
+    let path = args.positional(0);
+    let reader = EdfReader::open(path)?;
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    if args.flag("json") {
+        let annotations = reader.annotations().iter()
+            .map(|a| object(vec![
+                ("onset", seconds(a.onset).into()),
+                ("duration", (a.duration >= 0).then(|| seconds(a.duration)).into()),
+                ("description", a.description.as_str().into()),
+            ]))
+            .collect();
+        writeln!(out, "{}", object(vec![("file", path.into()), ("annotations", Json::Array(annotations))]))?;
+    } else {
+        writeln!(out, "onset\tduration\tdescription")?;
+        for a in reader.annotations() {
+            let duration = if a.duration >= 0 { seconds(a.duration).to_string() } else { String::new() };
+            writeln!(out, "{}\t{}\t{}", seconds(a.onset), duration, a.description)?;
+        }
+    }
+    out.flush()?;
+    Ok(true)
+}
+
+/// Prints the samples of signals with equal sampling rates in a time window
+pub fn dump(args: &Args) -> CommandResult {
+    args.check(&["json", "signal", "start", "end", "duration", "digital"], &["<file>"])
+        .map_err(CliError::Usage)?;
+    let reader = EdfReader::open(args.positional(0))?;
+    let header = reader.header();
+    let signals = select_signals(header, &args.values("signal"))?;
+    let frequency = header.sampling_frequency(signals[0])?;
+    for &signal in &signals[1..] {
+        if header.sampling_frequency(signal)? != frequency {
+            return Err(CliError::Usage(format!(
+                "Signals '{}' and '{}' have different sampling rates; select signals with equal rates using --signal",
+                header.signals[signals[0]].label, header.signals[signal].label
+            )));
+        }
+    }
+
+    let total = header.signals[signals[0]].samples_in_file;
+    let start = args.seconds("start").map_err(CliError::Usage)?.unwrap_or(0.0);
+    let first = reader.seconds_to_sample(signals[0], start)?.min(total);
+    let last = match window_end(args, start)? {
+        Some(end) => reader.seconds_to_sample(signals[0], end)?.clamp(first, total),
+        None => total,
+    };
+    let read = |signal: usize, start: i64, count: usize| if args.flag("digital") {
+        reader.read_digital_at(signal, start, count).map(|d| d.into_iter().map(f64::from).collect())
+    } else {
+        reader.read_physical_at(signal, start, count)
+    };
+
+    // 按块读取和输出，整夜记录也不必全部载入内存
+    let mut out = BufWriter::new(io::stdout().lock());
+    if args.flag("json") {
+        let start_time = if last > first { reader.sample_times(signals[0], first, 1)?.first().copied() } else { None };
+        write!(out, "{{\"start\":{},\"sampling_frequency\":{},\"digital\":{},\"signals\":[",
+            Json::from(start_time), Json::from(frequency), Json::from(args.flag("digital")))?;
+        for (i, &signal) in signals.iter().enumerate() {
+            if i > 0 {
+                write!(out, ",")?;
+            }
+            write!(out, "{{\"label\":{},\"unit\":{},\"samples\":[",
+                Json::from(header.signals[signal].label.as_str()),
+                Json::from(header.signals[signal].physical_dimension.as_str()))?;
+            let mut position = first;
+            while position < last {
+                let count = DUMP_CHUNK.min((last - position) as usize);
+                for (j, value) in read(signal, position, count)?.into_iter().enumerate() {
+                    if position > first || j > 0 {
+                        write!(out, ",")?;
+                    }
+                    write!(out, "{}", Json::from(value))?;
+                }
+                position += count as i64;
+            }
+            write!(out, "]}}")?;
+        }
+        writeln!(out, "]}}")?;
+    } else {
+        // EDF 标签只含可打印 ASCII，不会包含制表符
+        write!(out, "time")?;
+        for &signal in &signals {
+            write!(out, "\t{}", header.signals[signal].label)?;
+        }
+        writeln!(out)?;
+        let mut position = first;
+        while position < last {
+            let count = DUMP_CHUNK.min((last - position) as usize);
+            let times = reader.sample_times(signals[0], position, count)?;
+            let columns = signals.iter()
+                .map(|&signal| read(signal, position, count))
+                .collect::<Result<Vec<Vec<f64>>, EdfError>>()?;
+            for (row, time) in times.iter().enumerate() {
+                write!(out, "{}", time)?;
+                for column in &columns {
+                    write!(out, "\t{}", column[row])?;
+                }
+                writeln!(out)?;
+            }
+            position += count as i64;
+        }
+    }
+    out.flush()?;
+    Ok(true)
+}
+
+/// Checks that the file opens, all data records can be read and the samples
+/// are within the declared ranges
+pub fn validate(args: &Args) -> CommandResult {
+    args.check(&["json"], &["<file>"]).map_err(CliError::Usage)?;
+    let path = args.positional(0);
+    let mut errors: Vec<(String, Json)> = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+
+    match EdfReader::open_strict(path) {
+        Err(error) => errors.push(error_report(&error, None)),
+        Ok(mut reader) => {
+            if reader.header().datarecords_in_file == 0 {
+                warnings.push("File contains no data records".to_string());
+            }
+            for (index, record) in reader.records().enumerate() {
+                if let Err(error) = record {
+                    errors.push(error_report(&error, Some(index)));
+                    break;
+                }
+            }
+            if errors.is_empty() {
+                match reader.signal_statistics(&StatsOptions::default()) {
+                    Ok(statistics) => {
+                        for stats in statistics {
+                            let outside = stats.below_range + stats.above_range;
+                            if outside > 0 {
+                                warnings.push(format!(
+                                    "Signal '{}': {} samples outside the declared digital range", stats.label, outside
+                                ));
+                            }
+                        }
+                    }
+                    Err(error) => errors.push(error_report(&error, None)),
+                }
+            }
+        }
+    }
+
+    let valid = errors.is_empty();
+    let mut out = io::stdout().lock();
+    if args.flag("json") {
+        writeln!(out, "{}", object(vec![
+            ("file", path.into()),
+            ("valid", valid.into()),
+            ("errors", Json::Array(errors.into_iter().map(|(_, json)| json).collect())),
+            ("warnings", warnings.into()),
+        ]))?;
+    } else {
+        writeln!(out, "{}: {}", path, if valid { "OK" } else { "INVALID" })?;
+        for (message, _) in &errors {
+            writeln!(out, "  error: {}", message)?;
+        }
+        for message in &warnings {
+            writeln!(out, "  warning: {}", message)?;
+        }
+    }
+    Ok(valid)
+}
+
+/// Copies the data records covering a time window into a new file
+pub fn cut(args: &Args) -> CommandResult {
+    args.check(&["json", "start", "end", "duration"], &["<input>", "<output>"])
+        .map_err(CliError::Usage)?;
+    let reader = EdfReader::open(args.positional(0))?;
+    let start = args.seconds("start").map_err(CliError::Usage)?.unwrap_or(0.0);
+    let end = window_end(args, start)?.unwrap_or(seconds(reader.header().file_duration));
+    let summary = reader.crop(args.positional(1), &CropOptions::seconds(start, end))?;
+
+    let records = summary.records.end - summary.records.start;
+    let end = summary.offset_seconds + seconds(records * reader.header().datarecord_duration);
+    let annotations = summary.annotations_copied + summary.annotations_trimmed;
+    let mut out = io::stdout().lock();
+    if args.flag("json") {
+        writeln!(out, "{}", object(vec![
+            ("output", args.positional(1).into()),
+            ("first_record", summary.records.start.into()),
+            ("records", records.into()),
+            ("start", summary.offset_seconds.into()),
+            ("end", end.into()),
+            ("annotations", annotations.into()),
+            ("annotations_trimmed", summary.annotations_trimmed.into()),
+        ]))?;
+    } else {
+        writeln!(out, "Wrote data records {}..{} ({} s to {} s, {} annotations) to {}",
+            summary.records.start, summary.records.end, summary.offset_seconds, end,
+            annotations, args.positional(1))?;
+    }
+    Ok(true)
+}
+
+/// Exports the samples as CSV, TSV, `.npy` or `.npz`
+pub fn convert(args: &Args) -> CommandResult {
+    args.check(&["json", "format", "signal", "digital", "highpass", "lowpass", "notch"], &["<input>", "<output>"])
+        .map_err(CliError::Usage)?;
+    let output = args.positional(1);
+    let format = args.value("format")
+        .or_else(|| output.rsplit_once('.').map(|(_, extension)| extension))
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if !matches!(format.as_str(), "csv" | "tsv" | "txt" | "npy" | "npz") {
+        return Err(CliError::Usage(format!(
+            "Unknown output format '{}' (use --format csv, tsv, npy or npz)", format
+        )));
+    }
+
+    let reader = EdfReader::open(args.positional(0))?;
+    let signals = select_signals(reader.header(), &args.values("signal"))?;
+    let mut options = ExportOptions::new().signals(&signals);
+    if args.flag("digital") {
+        options = options.dtype(ExportDtype::Int16);
+    }
+    // 离线导出，使用零相位滤波
+    let mut chain = FilterChain::new();
+    if let Some(cutoff) = args.hertz("highpass").map_err(CliError::Usage)? {
+        chain = chain.highpass(cutoff, 2);
+    }
+    if let Some(cutoff) = args.hertz("lowpass").map_err(CliError::Usage)? {
+        chain = chain.lowpass(cutoff, 4);
+    }
+    if let Some(frequency) = args.hertz("notch").map_err(CliError::Usage)? {
+        chain = chain.notch(frequency, 30.0);
+    }
+    if !chain.stages().is_empty() {
+        if args.flag("digital") {
+            return Err(CliError::Usage("--digital values cannot be filtered".to_string()));
+        }
+        let chain = chain.zero_phase();
+        for &signal in &signals {
+            options = options.filter(signal, &chain);
+        }
+    }
+    let summary = match format.as_str() {
+        "csv" => reader.export_csv(output, &options)?,
+        "tsv" | "txt" => reader.export_csv(output, &options.delimiter('\t'))?,
+        "npy" => reader.export_npy(output, &options)?,
+        _ => reader.export_npz(output, &options)?,
+    };
+
+    let samples = summary.samples.iter().copied().max().unwrap_or(0);
+    let annotations_path = summary.annotations_path.as_ref().map(|path| path.display().to_string());
+    let mut out = io::stdout().lock();
+    if args.flag("json") {
+        writeln!(out, "{}", object(vec![
+            ("output", output.into()),
+            ("format", format.into()),
+            ("samples", samples.into()),
+            ("signals", summary.signals.into()),
+            ("annotations", summary.annotations.into()),
+            ("annotations_output", annotations_path.into()),
+        ]))?;
+    } else {
+        writeln!(out, "Wrote {} samples of {} signals to {}", samples, summary.signals.len(), output)?;
+        if let Some(path) = annotations_path {
+            writeln!(out, "Wrote {} annotations to {}", summary.annotations, path)?;
+        }
+    }
+    Ok(true)
+}
+
+/// Copies the file with the patient identification replaced
+pub fn anonymize(args: &Args) -> CommandResult {
+    args.check(&["json", "code", "keep-date"], &["<input>", "<output>"])
+        .map_err(CliError::Usage)?;
+    let reader = EdfReader::open(args.positional(0))?;
+    let header = reader.header();
+
+    // EDF+ 约定：未知或匿名的日期使用 01.01.1985
+    let start = if args.flag("keep-date") {
+        header.start_datetime()
+    } else {
+        NaiveDate::from_ymd_opt(1985, 1, 1)
+            .unwrap_or_default()
+            .and_time(header.start_datetime().time())
+    };
+    // 字段之间以空格分隔，代码中的空格替换为下划线
+    let code = args.value("code").unwrap_or("X").replace(' ', "_");
+    let mut writer = EdfWriter::create(args.positional(1))?;
+    writer.set_patient_info(&code, "X", "X", "X")?;
+    let options = CropOptions::records(0..header.datarecords_in_file).start_datetime(start);
+    let summary = reader.crop_into(writer, &options)?;
+    let annotations = summary.annotations_copied + summary.annotations_trimmed;
+
+    let mut out = io::stdout().lock();
+    if args.flag("json") {
+        writeln!(out, "{}", object(vec![
+            ("output", args.positional(1).into()),
+            ("patient_code", code.as_str().into()),
+            ("start", format_datetime(&start, 'T').into()),
+            ("annotations", annotations.into()),
+        ]))?;
+    } else {
+        writeln!(out, "Wrote anonymized copy to {}", args.positional(1))?;
+    }
+    Ok(true)
+}
+
+/// Resolves `--signal` values (index or label) to signal indices; all signals if none given
+fn select_signals(header: &EdfHeader, selection: &[&str]) -> Result<Vec<usize>, CliError> {
+    if header.signals.is_empty() {
+        return Err(CliError::Failed("File contains no signals".to_string()));
+    }
+    if selection.is_empty() {
+        return Ok((0..header.signals.len()).collect());
+    }
+    selection.iter()
+        .map(|&name| {
+            header.signals.iter().position(|s| s.label == name)
+                .or_else(|| name.parse().ok().filter(|&i: &usize| i < header.signals.len()))
+                .ok_or_else(|| CliError::Usage(format!("No signal '{}'", name)))
+        })
+        .collect()
+}
+
+/// Window end from `--end` or `--duration`
+fn window_end(args: &Args, start: f64) -> Result<Option<f64>, CliError> {
+    match (args.seconds("end").map_err(CliError::Usage)?, args.seconds("duration").map_err(CliError::Usage)?) {
+        (Some(_), Some(_)) => Err(CliError::Usage("Use either --end or --duration".to_string())),
+        (Some(end), None) if end < start => Err(CliError::Usage("--end is before --start".to_string())),
+        (Some(end), None) => Ok(Some(end)),
+        (None, Some(duration)) => Ok(Some(start + duration)),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Message and JSON object of an error, with its location when known
+fn error_report(error: &EdfError, record: Option<usize>) -> (String, Json) {
+    let message = match record {
+        Some(record) => format!("data record {}: {}", record, error),
+        None => error.to_string(),
+    };
+    let mut fields = vec![("message", message.as_str().into())];
+    match error {
+        EdfError::InvalidHeader { field, signal, bytes, raw, .. } => {
+            fields.push(("field", (*field).into()));
+            fields.push(("signal", (*signal).into()));
+            fields.push(("bytes", vec![bytes.start, bytes.end].into()));
+            fields.push(("raw", raw.as_str().into()));
+        }
+        EdfError::InvalidAnnotation { record, offset, .. } => {
+            fields.push(("record", (*record).into()));
+            fields.push(("offset", (*offset).into()));
+        }
+        _ => {
+            if let Some(record) = record {
+                fields.push(("record", record.into()));
+            }
+        }
+    }
+    (message, object(fields))
+}
+
+/// 100纳秒单位转换为秒
+fn seconds(time: i64) -> f64 {
+    time as f64 / EDFLIB_TIME_DIMENSION as f64
+}
+
+fn format_datetime(datetime: &NaiveDateTime, separator: char) -> String {
+    datetime.format(&format!("%Y-%m-%d{}%H:%M:%S%.f", separator)).to_string()
+}
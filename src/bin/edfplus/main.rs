@@ -0,0 +1,113 @@
+//! `edfplus` command-line tool for inspecting and manipulating EDF+ files
+//!
+//! Run `edfplus help` for the list of subcommands. Every subcommand accepts
+//! `--json` for machine-readable output on stdout.
+
+mod args;
+mod commands;
+mod json;
+
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use args::Args;
+
+const USAGE: &str = "\
+Usage: edfplus <command> [options]
+
+Commands:
+  info <file>                  Header, signals and annotation summary
+  annotations <file>           List all annotations
+  dump <file>                  Print samples as text
+      --signal <index|label>   Signal to print (repeatable, default: all)
+      --start <s>              Window start in seconds (default: 0)
+      --end <s> | --duration <s>
+                               Window end or length (default: end of file)
+      --digital                Print digital instead of physical values
+  validate <file>              Check the file for format problems
+  cut <input> <output>         Copy a time window into a new file
+      --start <s>              Window start (rounded down to a data record)
+      --end <s> | --duration <s>
+                               Window end (rounded up to a data record)
+  convert <input> <output>     Export samples as CSV, TSV, .npy or .npz; annotations
+                               go to <output>.annotations.csv (.npz: into the archive)
+      --format <csv|tsv|npy|npz>
+                               Output format (default: from the file extension)
+      --signal <index|label>   Signal to export (repeatable, default: all)
+      --digital                Export digital instead of physical values
+      --highpass <Hz> | --lowpass <Hz> | --notch <Hz>
+                               Filter the exported signals without phase shift
+  anonymize <input> <output>   Copy with patient identification removed
+      --code <code>            New patient code (default: X)
+      --keep-date              Keep the recording date (default: 01.01.1985)
+
+Options for all commands:
+  --json                       Machine-readable JSON output
+";
+
+/// Error of a subcommand
+pub enum CliError {
+    /// Wrong arguments; the usage is printed
+    Usage(String),
+    /// The command failed
+    Failed(String),
+    /// Stdout was closed early, e.g. by `edfplus dump ... | head`
+    Closed,
+}
+
+impl From<edfplus::EdfError> for CliError {
+    fn from(error: edfplus::EdfError) -> Self {
+        CliError::Failed(error.to_string())
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> Self {
+        if error.kind() == io::ErrorKind::BrokenPipe {
+            return CliError::Closed;
+        }
+        CliError::Failed(error.to_string())
+    }
+}
+
+fn main() -> ExitCode {
+    let mut arguments = std::env::args().skip(1);
+    let command = arguments.next().unwrap_or_else(|| "help".to_string());
+
+    // 在执行子命令之前处理帮助，避免 --help 触发写文件等操作
+    if matches!(command.as_str(), "help" | "-h" | "--help") || std::env::args().any(|a| a == "--help") {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let result = Args::parse(arguments)
+        .map_err(CliError::Usage)
+        .and_then(|args| {
+            match command.as_str() {
+                "info" => commands::info(&args),
+                "annotations" => commands::annotations(&args),
+                "dump" => commands::dump(&args),
+                "validate" => commands::validate(&args),
+                "cut" => commands::cut(&args),
+                "convert" => commands::convert(&args),
+                "anonymize" => commands::anonymize(&args),
+                _ => Err(CliError::Usage(format!("Unknown command '{}'", command))),
+            }
+        });
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        // 命令执行成功但结果为否定（例如文件校验失败）
+        Ok(false) => ExitCode::FAILURE,
+        Err(CliError::Usage(message)) => {
+            eprintln!("edfplus: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+        // 管道被关闭不算错误
+        Err(CliError::Closed) => ExitCode::SUCCESS,
+        Err(CliError::Failed(message)) => {
+            let _ = writeln!(io::stderr(), "edfplus: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use std::ops::Range;
+use std::path::Path;
+
+use chrono::{NaiveDateTime, TimeDelta};
+
+use crate::error::{EdfError, Result};
+use crate::reader::EdfReader;
+use crate::select::SignalSelection;
+use crate::types::SignalParam;
+use crate::utils::CONTIGUOUS_TOLERANCE;
+use crate::writer::EdfWriter;
+use crate::EDFLIB_TIME_DIMENSION;
+
+/// What to do with annotations that overlap a crop boundary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnnotationCrop {
+    /// Keep the part inside the window: the onset moves to the window start
+    /// and the duration is cut at the window end
+    #[default]
+    Trim,
+    /// Drop every annotation that does not lie entirely inside the window
+    Drop,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CropWindow {
+    Seconds(f64, f64),
+    Records(Range<i64>),
+}
+
+/// Window for [`EdfReader::crop`]
+///
+/// A window in seconds is widened to whole data records: the start is
+/// rounded down and the end rounded up to a record boundary, so the digital
+/// data can be copied unchanged. An end past the end of the file is clamped.
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::{AnnotationCrop, CropOptions};
+///
+/// // 10 minutes starting 2 hours into the recording
+/// let segment = CropOptions::seconds(7200.0, 7800.0);
+/// // The first 30 data records, without cut-off annotations
+/// let head = CropOptions::records(0..30).annotations(AnnotationCrop::Drop);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CropOptions {
+    window: CropWindow,
+    annotations: AnnotationCrop,
+    start_datetime: Option<NaiveDateTime>,
+    signals: Option<SignalSelection>,
+}
+
+impl CropOptions {
+    /// Window from `start_seconds` to `end_seconds` after the first sample
+    pub fn seconds(start_seconds: f64, end_seconds: f64) -> Self {
+        CropOptions {
+            window: CropWindow::Seconds(start_seconds, end_seconds),
+            annotations: AnnotationCrop::default(),
+            start_datetime: None,
+            signals: None,
+        }
+    }
+
+    /// Window of whole data records
+    pub fn records(records: Range<i64>) -> Self {
+        CropOptions {
+            window: CropWindow::Records(records),
+            annotations: AnnotationCrop::default(),
+            start_datetime: None,
+            signals: None,
+        }
+    }
+
+    /// Sets how annotations crossing the window boundaries are handled
+    pub fn annotations(mut self, annotations: AnnotationCrop) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
+    /// Writes this start date and time instead of the source start plus the
+    /// window offset, e.g. to remove the recording date from a shared file
+    pub fn start_datetime(mut self, start: NaiveDateTime) -> Self {
+        self.start_datetime = Some(start);
+        self
+    }
+
+    /// Writes only these signals, in this order (default: all signals)
+    pub fn signals(mut self, selection: SignalSelection) -> Self {
+        self.signals = Some(selection);
+        self
+    }
+}
+
+/// Result of [`EdfReader::crop`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CropSummary {
+    /// Data records of the source file that were copied
+    pub records: Range<i64>,
+    /// Offset of the new file in the source, in seconds after the first sample
+    pub offset_seconds: f64,
+    /// Start date and time written to the new file (including the subsecond)
+    pub start: NaiveDateTime,
+    /// Annotations copied unchanged
+    pub annotations_copied: usize,
+    /// Annotations shortened to fit the window
+    pub annotations_trimmed: usize,
+    /// Annotations outside the window, or with an empty description
+    pub annotations_dropped: usize,
+}
+
+impl EdfReader {
+    /// Writes a time range of the file into a new EDF+ file
+    ///
+    /// Copies the stored digital samples of all signals in the data records of
+    /// the window (see [`CropOptions`]) byte for byte, without requantization
+    /// or clamping to the digital range, so the physical values are identical. Annotations are shifted relative to the new
+    /// start; those outside the window are dropped, those crossing it are
+    /// trimmed or dropped per [`CropOptions::annotations`]. The start date,
+    /// time and subsecond of the new file are moved to the first copied
+    /// record, and the patient identification is copied.
+    ///
+    /// Recording identification fields other than the start date are not
+    /// copied. Use [`crop_into`](Self::crop_into) to set header fields of the
+    /// new file yourself.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - The window is empty, reversed or outside
+    ///   the file, or the signal selection is invalid (see [`extract_signals`](Self::extract_signals))
+    /// * `EdfError::InvalidSignalIndex` - A selected signal does not exist
+    /// * `EdfError::Io` - I/O error reading the source or writing the new file
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{CropOptions, EdfReader};
+    ///
+    /// # edfplus::doctest_utils::create_simple_test_file("night.edf")?;
+    /// let reader = EdfReader::open("night.edf")?;
+    /// let summary = reader.crop("night_segment.edf", &CropOptions::seconds(0.25, 0.75))?;
+    /// println!("copied records {:?}, new start {}", summary.records, summary.start);
+    ///
+    /// let segment = EdfReader::open("night_segment.edf")?;
+    /// assert_eq!(segment.header().datarecords_in_file, summary.records.end - summary.records.start);
+    ///
+    /// # std::fs::remove_file("night.edf").ok();
+    /// # std::fs::remove_file("night_segment.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn crop<P: AsRef<Path>>(&self, path: P, options: &CropOptions) -> Result<CropSummary> {
+        // 先校验窗口和信号选择，避免留下空的输出文件
+        self.crop_records(options)?;
+        self.crop_signals(options)?;
+        let header = self.header();
+        let mut writer = EdfWriter::create(path)?;
+        writer.set_patient_info(&header.patient_code, &header.sex, &header.birthdate, &header.patient_name)?;
+        self.crop_into(writer, options)
+    }
+
+    /// Writes a time range of the file into a prepared writer
+    ///
+    /// Like [`crop`](Self::crop), but header fields already set on `writer`
+    /// (e.g. the patient identification) are kept. The record duration,
+    /// signals, start time and annotations are set from the source, and the
+    /// writer is finalized.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - The window is invalid, or the writer
+    ///   already has signals or has written data
+    /// * `EdfError::Io` - I/O error reading the source or writing the new file
+    pub fn crop_into(&self, mut writer: EdfWriter, options: &CropOptions) -> Result<CropSummary> {
+        let records = self.crop_records(options)?;
+        let (indices, signals): (Vec<usize>, Vec<SignalParam>) = self.crop_signals(options)?.into_iter().unzip();
+        let header = self.header();
+        let record_duration = header.datarecord_duration;
+        let onsets = &self.record_onsets()[records.start as usize..records.end as usize];
+        let window_start = onsets[0];
+        let window_end = onsets[onsets.len() - 1] + record_duration;
+        let start = options.start_datetime
+            .unwrap_or_else(|| header.start_datetime() + TimeDelta::nanoseconds(window_start * 100));
+
+        writer.set_start_datetime(start)?;
+        writer.set_datarecord_duration(record_duration as f64 / EDFLIB_TIME_DIMENSION as f64)?;
+        for signal in signals {
+            writer.add_signal(signal)?;
+        }
+
+        // 注释裁剪到窗口内，时间相对于新文件的开始
+        let mut summary = CropSummary {
+            records: records.clone(),
+            offset_seconds: window_start as f64 / EDFLIB_TIME_DIMENSION as f64,
+            start,
+            annotations_copied: 0,
+            annotations_trimmed: 0,
+            annotations_dropped: 0,
+        };
+        let mut annotations = Vec::new();
+        for annotation in self.annotations() {
+            let onset = annotation.onset;
+            let end = onset + annotation.duration.max(0);
+            let inside = onset >= window_start && onset < window_end && end <= window_end;
+            let overlaps = annotation.duration > 0 && onset < window_end && end > window_start;
+            if annotation.description.is_empty()
+                || !(inside || overlaps && options.annotations == AnnotationCrop::Trim)
+            {
+                summary.annotations_dropped += 1;
+                continue;
+            }
+            if inside {
+                summary.annotations_copied += 1;
+                annotations.push((onset - window_start, annotation.duration, annotation.description.as_str()));
+            } else {
+                summary.annotations_trimmed += 1;
+                let onset = onset.max(window_start);
+                annotations.push((onset - window_start, end.min(window_end) - onset, annotation.description.as_str()));
+            }
+        }
+
+        // EDF+D 源文件中窗口内的间隙保留在新文件中
+        let relative_onsets: Vec<i64> = onsets.iter().map(|onset| onset - window_start).collect();
+        let mut data = Vec::new();
+        write_records(writer, record_duration, &relative_onsets, &annotations, |writer, i| {
+            data.clear();
+            self.record_bytes(records.start + i as i64, &indices, &mut data)?;
+            writer.write_raw_record(&data)
+        })?;
+        Ok(summary)
+    }
+
+    /// 将一个数据记录中所选信号的原始样本追加到 data，用于不经换算、不做范围限制地复制数据
+    pub(crate) fn record_bytes(&self, record: i64, signals: &[usize], data: &mut Vec<u8>) -> Result<()> {
+        for &signal in signals {
+            self.read_record_bytes(record, signal, data)?;
+        }
+        Ok(())
+    }
+
+    /// 要写入的信号：源信号索引及输出参数
+    fn crop_signals(&self, options: &CropOptions) -> Result<Vec<(usize, SignalParam)>> {
+        match &options.signals {
+            Some(selection) => self.resolve_selection(selection),
+            None => Ok(self.header().signals.iter().cloned().enumerate().collect()),
+        }
+    }
+
+    /// Data records covered by the crop window
+    fn crop_records(&self, options: &CropOptions) -> Result<Range<i64>> {
+        let header = self.header();
+        let record_duration = header.datarecord_duration;
+        if record_duration <= 0 {
+            return Err(EdfError::InvalidFormat("Cannot crop a file with a data record duration of 0".to_string()));
+        }
+
+        let records = match options.window {
+            CropWindow::Records(ref records) => records.clone(),
+            CropWindow::Seconds(start, end) => {
+                if !(start.is_finite() && end.is_finite()) || start < 0.0 || end <= start {
+                    return Err(EdfError::InvalidFormat(format!(
+                        "Invalid crop window from {} s to {} s", start, end
+                    )));
+                }
+                // 与窗口有交集的记录（EDF+D 中按各记录的实际开始时间）
+                let onsets = self.record_onsets();
+                let start = (start * EDFLIB_TIME_DIMENSION as f64).round() as i64;
+                let end = (end * EDFLIB_TIME_DIMENSION as f64).round() as i64;
+                let first = onsets.partition_point(|&onset| onset + record_duration <= start);
+                let last = onsets.partition_point(|&onset| onset < end);
+                first as i64..last as i64
+            }
+        };
+        if records.start < 0 || records.start >= records.end || records.end > header.datarecords_in_file {
+            return Err(EdfError::InvalidFormat(format!(
+                "Crop window covers data records {}..{}, but the file has {} data records",
+                records.start, records.end, header.datarecords_in_file
+            )));
+        }
+        Ok(records)
+    }
+}
+
+/// 添加注释并写入数据记录，然后完成文件
+///
+/// `onsets` 为各记录相对于新文件开始的时间，与上一个记录不连续时（EDF+D 的间隙）
+/// 设置记录的开始时间；注释为（onset，duration，描述），时间单位均为100纳秒。
+/// `write_record` 写入第 i 个记录的样本。
+pub(crate) fn write_records(
+    mut writer: EdfWriter,
+    record_duration: i64,
+    onsets: &[i64],
+    annotations: &[(i64, i64, &str)],
+    mut write_record: impl FnMut(&mut EdfWriter, usize) -> Result<()>,
+) -> Result<()> {
+    let record_ends: Vec<i64> = onsets.iter().map(|onset| onset + record_duration).collect();
+    writer.set_number_of_annotation_signals(annotation_channels(&record_ends, annotations.iter().map(|&(onset, _, _)| onset)))?;
+    for &(onset, duration, description) in annotations {
+        let duration = (duration >= 0).then(|| duration as f64 / EDFLIB_TIME_DIMENSION as f64);
+        writer.add_annotation(onset as f64 / EDFLIB_TIME_DIMENSION as f64, duration, description)?;
+    }
+
+    let mut expected = 0i64;
+    for (record, &onset) in onsets.iter().enumerate() {
+        if onset > expected + CONTIGUOUS_TOLERANCE {
+            writer.set_next_record_onset(onset as f64 / EDFLIB_TIME_DIMENSION as f64)?;
+            expected = onset;
+        }
+        write_record(&mut writer, record)?;
+        expected += record_duration;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+/// 每个注释通道每个记录只能容纳少量注释，按最密集的记录分配通道数
+///
+/// 注释存放在结束时间晚于其开始时间的第一个记录中（与写入器处理间隙的规则一致）。
+pub(crate) fn annotation_channels(record_ends: &[i64], onsets: impl Iterator<Item = i64>) -> usize {
+    let mut per_record = vec![0usize; record_ends.len()];
+    for onset in onsets {
+        let record = record_ends.partition_point(|&end| end <= onset);
+        if let Some(count) = per_record.get_mut(record) {
+            *count += 1;
+        }
+    }
+    per_record.into_iter().max().unwrap_or(0).clamp(1, 64)
+}
@@ -0,0 +1,249 @@
+use crate::error::{EdfError, Result};
+use crate::reader::EdfReader;
+use crate::types::Annotation;
+use crate::utils::{seconds_to_time, time_to_sample};
+use crate::EDFLIB_TIME_DIMENSION;
+
+/// How the epoch iterator handles an epoch that runs past the end of the file
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PartialEpoch {
+    /// Only yield complete epochs
+    #[default]
+    Drop,
+    /// Yield the partial epoch with fewer samples per signal
+    Keep,
+    /// Yield the partial epoch padded to full length with this physical value
+    Pad(f64),
+}
+
+/// Options for [`EdfReader::epochs`]
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::{EpochOptions, PartialEpoch};
+///
+/// // 30 s scoring epochs
+/// let scoring = EpochOptions::new(30.0);
+///
+/// // 4 s windows with 50% overlap on the first two signals
+/// let windows = EpochOptions::new(4.0)
+///     .overlap(2.0)
+///     .signals(&[0, 1])
+///     .partial(PartialEpoch::Pad(0.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpochOptions {
+    length: f64,
+    step: Option<f64>,
+    signals: Option<Vec<usize>>,
+    partial: PartialEpoch,
+}
+
+impl EpochOptions {
+    /// Epochs of `length_seconds`, back to back, over all signals
+    pub fn new(length_seconds: f64) -> Self {
+        EpochOptions { length: length_seconds, step: None, signals: None, partial: PartialEpoch::Drop }
+    }
+
+    /// Sets the distance between epoch starts (defaults to the epoch length)
+    pub fn step(mut self, step_seconds: f64) -> Self {
+        self.step = Some(step_seconds);
+        self
+    }
+
+    /// Sets the overlap between consecutive epochs (step = length - overlap)
+    pub fn overlap(mut self, overlap_seconds: f64) -> Self {
+        self.step = Some(self.length - overlap_seconds);
+        self
+    }
+
+    /// Restricts the epochs to these signals, in this order
+    pub fn signals(mut self, signals: &[usize]) -> Self {
+        self.signals = Some(signals.to_vec());
+        self
+    }
+
+    /// Sets how a trailing partial epoch is handled (default: dropped)
+    pub fn partial(mut self, partial: PartialEpoch) -> Self {
+        self.partial = partial;
+        self
+    }
+}
+
+/// One fixed-length window of signal data, see [`EdfReader::epochs`]
+#[derive(Debug, Clone)]
+pub struct Epoch {
+    /// Zero-based epoch number
+    pub index: usize,
+    /// Epoch start in 100-nanosecond units since the recording start
+    pub start: i64,
+    /// Epoch length in 100-nanosecond units
+    pub duration: i64,
+    /// Physical samples of each selected signal, in selection order
+    pub signals: Vec<Vec<f64>>,
+    /// Annotations overlapping the epoch
+    pub annotations: Vec<Annotation>,
+    /// Whether the epoch runs past the end of the file
+    pub partial: bool,
+}
+
+impl Epoch {
+    /// Epoch start in seconds
+    pub fn start_seconds(&self) -> f64 {
+        self.start as f64 / EDFLIB_TIME_DIMENSION as f64
+    }
+}
+
+/// Iterator over fixed-length epochs, see [`EdfReader::epochs`]
+pub struct Epochs<'a> {
+    reader: &'a mut EdfReader,
+    signals: Vec<usize>,
+    length: i64,
+    step: i64,
+    partial: PartialEpoch,
+    next: usize,
+    count: usize,
+}
+
+impl EdfReader {
+    /// Iterates over fixed-length epochs of the selected signals
+    ///
+    /// Epoch `k` starts at `k * step` seconds. Each signal contributes
+    /// `length * sampling rate` samples (rounded), so signals with different
+    /// rates stay aligned. Annotations overlapping an epoch are attached to it.
+    ///
+    /// In a discontinuous (EDF+D) file epochs follow the recording time line
+    /// given by [`record_onsets`](Self::record_onsets), and epochs that overlap
+    /// a gap between data records are skipped.
+    ///
+    /// The iterator moves the per-signal read positions of the selected signals.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - Length or step is not positive
+    /// * `EdfError::InvalidSignalIndex` - A selected signal does not exist
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{EdfReader, EpochOptions};
+    ///
+    /// # edfplus::doctest_utils::create_simple_test_file("epochs.edf")?;
+    /// let mut reader = EdfReader::open("epochs.edf")?;
+    ///
+    /// for epoch in reader.epochs(&EpochOptions::new(2.0))? {
+    ///     let epoch = epoch?;
+    ///     let eeg = &epoch.signals[0];
+    ///     let rms = (eeg.iter().map(|v| v * v).sum::<f64>() / eeg.len() as f64).sqrt();
+    ///     println!("epoch {} at {:.0}s: RMS {:.2}, {} annotations",
+    ///         epoch.index, epoch.start_seconds(), rms, epoch.annotations.len());
+    /// }
+    ///
+    /// # std::fs::remove_file("epochs.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn epochs(&mut self, options: &EpochOptions) -> Result<Epochs<'_>> {
+        let length = positive_time(options.length, "Epoch length")?;
+        let step = positive_time(options.step.unwrap_or(options.length), "Epoch step")?;
+
+        let signal_count = self.header().signals.len();
+        let signals = options.signals.clone().unwrap_or_else(|| (0..signal_count).collect());
+        if let Some(&invalid) = signals.iter().find(|&&s| s >= signal_count) {
+            return Err(EdfError::InvalidSignalIndex(invalid));
+        }
+
+        // 完整的 epoch 数量，以及可选的一个不完整 epoch
+        let total = self.recording_end();
+        let full = if total >= length { ((total - length) / step + 1) as usize } else { 0 };
+        let has_partial = options.partial != PartialEpoch::Drop && (full as i64) * step < total;
+        let count = full + has_partial as usize;
+
+        Ok(Epochs { reader: self, signals, length, step, partial: options.partial, next: 0, count })
+    }
+}
+
+impl Epochs<'_> {
+    fn read_epoch(&mut self, index: usize) -> Result<Epoch> {
+        let start = index as i64 * self.step;
+        let end = start + self.length;
+        let mut partial = false;
+        let mut signals = Vec::with_capacity(self.signals.len());
+
+        for &signal in &self.signals {
+            let header = self.reader.header();
+            let samples_per_record = header.signals[signal].samples_per_record as i64;
+            let record_duration = header.datarecord_duration;
+            let first = self.reader.time_to_sample(signal, start);
+            let len = time_to_sample(self.length, samples_per_record, record_duration, true) as usize;
+
+            let mut samples = vec![0.0; len];
+            self.reader.seek(signal, first)?;
+            let read = self.reader.read_physical_into(signal, &mut samples)?;
+            if read < len {
+                partial = true;
+                match self.partial {
+                    PartialEpoch::Pad(value) => samples[read..].fill(value),
+                    _ => samples.truncate(read),
+                }
+            }
+            signals.push(samples);
+        }
+
+        let annotations = self.reader.annotations()
+            .iter()
+            .filter(|a| overlaps(a, start, end))
+            .cloned()
+            .collect();
+
+        Ok(Epoch { index, start, duration: self.length, signals, annotations, partial })
+    }
+
+    /// epoch 是否位于数据记录中（EDF+D 文件中不跨越记录之间的间隙）
+    fn is_recorded(&self, index: usize) -> bool {
+        if !self.reader.header().discontinuous {
+            return true;
+        }
+        let start = index as i64 * self.step;
+        let end = (start + self.length).min(self.reader.recording_end());
+        self.reader.is_recorded(start, end)
+    }
+}
+
+impl Iterator for Epochs<'_> {
+    type Item = Result<Epoch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next < self.count && !self.is_recorded(self.next) {
+            self.next += 1;
+        }
+        if self.next >= self.count {
+            return None;
+        }
+        let result = self.read_epoch(self.next);
+        // 出错后停止迭代
+        self.next = if result.is_ok() { self.next + 1 } else { self.count };
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.count - self.next))
+    }
+}
+
+/// 秒转换为100纳秒单位，要求为正数
+pub(crate) fn positive_time(seconds: f64, what: &str) -> Result<i64> {
+    match seconds_to_time(seconds) {
+        Ok(time) if time > 0 => Ok(time),
+        _ => Err(EdfError::InvalidFormat(format!("{} must be positive, got {} s", what, seconds))),
+    }
+}
+
+/// 注释是否与时间窗 [start, end) 重叠（瞬时注释按时间点判断）
+pub(crate) fn overlaps(annotation: &Annotation, start: i64, end: i64) -> bool {
+    if annotation.duration > 0 {
+        annotation.onset < end && annotation.onset + annotation.duration > start
+    } else {
+        annotation.onset >= start && annotation.onset < end
+    }
+}
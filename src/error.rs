@@ -1,43 +1,97 @@
 use std::io;
+use std::ops::Range;
 use thiserror::Error;
 
+/// Errors returned by this crate
+///
+/// Errors about a malformed file carry their location: the header field,
+/// byte range and signal for header problems, the data record and byte
+/// offset for annotation problems. The enum is `#[non_exhaustive]`, so
+/// matches need a wildcard arm.
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::{EdfError, EdfReader};
+///
+/// match EdfReader::open_strict("partner_site.edf") {
+///     Ok(reader) => println!("{} signals", reader.header().signals.len()),
+///     Err(EdfError::InvalidHeader { field, signal, bytes, raw, .. }) => {
+///         eprintln!("bad '{}' (signal {:?}) at bytes {:?}: {:?}", field, signal, bytes, raw);
+///     }
+///     Err(EdfError::InvalidAnnotation { record, offset, reason }) => {
+///         eprintln!("bad TAL in record {} at byte {}: {}", record, offset, reason);
+///     }
+///     Err(e) => eprintln!("{}", e),
+/// }
+/// ```
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum EdfError {
     #[error("File not found: {0}")]
     FileNotFound(String),
-    
+
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
-    
+
     #[error("Invalid file format: {0}")]
     InvalidFormat(String),
-    
-    #[error("File contains format errors")]
-    FormatError,
-    
+
+    /// A header field has an invalid value
+    #[error("Invalid header field '{field}'{} at bytes {}..{} ({raw:?}): {reason}",
+        signal_suffix(.signal), .bytes.start, .bytes.end)]
+    InvalidHeader {
+        /// Name of the field, e.g. `"physical minimum"`
+        field: &'static str,
+        /// Index of the signal the field belongs to (counting annotation
+        /// signals), `None` for fields of the main header
+        signal: Option<usize>,
+        /// Byte range of the field in the file
+        bytes: Range<usize>,
+        /// Raw text of the field
+        raw: String,
+        /// What is wrong with the value
+        reason: String,
+    },
+
+    /// An annotation (TAL) in a data record is invalid
+    #[error("Invalid annotation in data record {record} at byte {offset}: {reason}")]
+    InvalidAnnotation {
+        /// Index of the data record
+        record: i64,
+        /// Byte offset of the TAL in the file
+        offset: usize,
+        /// What is wrong with the annotation
+        reason: String,
+    },
+
     #[error("Signal index {0} out of range")]
     InvalidSignalIndex(usize),
-    
+
     #[error("Unsupported file type: {0}")]
     UnsupportedFileType(String),
-    
+
     #[error("File is discontinuous")]
     DiscontinuousFile,
-    
+
     #[error("Memory allocation error")]
     MemoryError,
-    
-    #[error("Invalid header size")]
-    InvalidHeader,
-    
+
     #[error("Invalid number of signals: {0}")]
     InvalidSignalCount(i32),
-    
+
     #[error("Physical min equals physical max")]
     PhysicalMinEqualsMax,
-    
+
     #[error("Digital min equals digital max")]
     DigitalMinEqualsMax,
+
+    #[error("Invalid sample: {0}")]
+    InvalidSample(String),
+}
+
+fn signal_suffix(signal: &Option<usize>) -> String {
+    signal.map(|s| format!(" of signal {}", s)).unwrap_or_default()
 }
 
 pub type Result<T> = std::result::Result<T, EdfError>;
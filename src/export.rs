@@ -0,0 +1,762 @@
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::epoch::overlaps;
+use crate::error::{EdfError, Result};
+use crate::filter::FilterChain;
+use crate::reader::EdfReader;
+use crate::resample::{ResampleOptions, Resampler};
+use crate::types::Annotation;
+use crate::EDFLIB_TIME_DIMENSION;
+
+/// 每次读取并写出的样本数，导出时内存占用与文件大小无关
+const CHUNK: usize = 65536;
+
+/// Value type of exported samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportDtype {
+    /// Physical values as 64-bit floats (NumPy `<f8`)
+    #[default]
+    Float64,
+    /// Physical values as 32-bit floats (NumPy `<f4`)
+    Float32,
+    /// Raw digital values as stored in the file, not clamped to the digital
+    /// range (NumPy `<i2`)
+    Int16,
+}
+
+/// Options for [`EdfReader::export_csv`], [`EdfReader::export_npy`] and
+/// [`EdfReader::export_npz`]
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::{ExportDtype, ExportOptions, FilterChain};
+///
+/// // Everything, as float64
+/// let all = ExportOptions::new();
+///
+/// // Two signals between 10 and 40 minutes, as float32 at 100 Hz
+/// let window = ExportOptions::new()
+///     .signals(&[0, 2])
+///     .window(600.0, 2400.0)
+///     .resample(100.0)
+///     .dtype(ExportDtype::Float32);
+///
+/// // Signal 0 band-passed without phase shift
+/// let filtered = ExportOptions::new()
+///     .filter(0, &FilterChain::new().highpass(0.5, 2).lowpass(35.0, 4).zero_phase());
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ExportOptions {
+    signals: Option<Vec<usize>>,
+    window: Option<(f64, f64)>,
+    rate: Option<f64>,
+    dtype: ExportDtype,
+    annotations: bool,
+    delimiter: Option<char>,
+    filters: Vec<(usize, FilterChain)>,
+}
+
+impl ExportOptions {
+    /// All signals, the whole file, float64 values, with annotations
+    pub fn new() -> Self {
+        ExportOptions { annotations: true, ..Default::default() }
+    }
+
+    /// Exports only these signals, in this order
+    pub fn signals(mut self, signals: &[usize]) -> Self {
+        self.signals = Some(signals.to_vec());
+        self
+    }
+
+    /// Exports the samples from `start_seconds` up to `end_seconds` after
+    /// the first sample; an end past the end of the file is clamped
+    pub fn window(mut self, start_seconds: f64, end_seconds: f64) -> Self {
+        self.window = Some((start_seconds, end_seconds));
+        self
+    }
+
+    /// Resamples every signal to `rate_hz` (see [`EdfReader::resample`])
+    pub fn resample(mut self, rate_hz: f64) -> Self {
+        self.rate = Some(rate_hz);
+        self
+    }
+
+    /// Sets the value type (default: float64)
+    pub fn dtype(mut self, dtype: ExportDtype) -> Self {
+        self.dtype = dtype;
+        self
+    }
+
+    /// Sets whether annotations are written alongside the samples (default: true)
+    pub fn annotations(mut self, annotations: bool) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
+    /// Sets the field delimiter of [`EdfReader::export_csv`] (default: `,`;
+    /// `'\t'` writes TSV). The annotations file is always comma-separated.
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = Some(delimiter);
+        self
+    }
+
+    /// Filters the physical values of `signal` (index in the file) with
+    /// `chain` before they are exported, replacing an earlier filter of the
+    /// same signal
+    ///
+    /// Unlike [`EdfReader::set_filter`], zero-phase chains are allowed: the
+    /// whole signal is filtered as by [`EdfReader::read_filtered`] and the
+    /// window is cut from the result, so a filtered signal is held in memory
+    /// during the export. Resampling is applied to the filtered values.
+    /// Raw int16 exports cannot be filtered.
+    pub fn filter(mut self, signal: usize, chain: &FilterChain) -> Self {
+        self.filters.retain(|(filtered, _)| *filtered != signal);
+        self.filters.push((signal, chain.clone()));
+        self
+    }
+}
+
+/// Result of an export
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportSummary {
+    /// Column names (CSV), or array names (`.npz`), of the exported signals
+    pub signals: Vec<String>,
+    /// Number of samples exported per signal
+    pub samples: Vec<i64>,
+    /// Number of annotations written
+    pub annotations: usize,
+    /// File the annotations were written to, if not into the export itself
+    pub annotations_path: Option<PathBuf>,
+}
+
+/// 导出计划：所选信号、每个信号的样本范围，以及可选的重采样
+struct ExportPlan<'a> {
+    reader: &'a EdfReader,
+    resampler: Option<Resampler<'a>>,
+    signals: Vec<usize>,
+    samples: Vec<Range<i64>>,
+    dtype: ExportDtype,
+    annotations: Vec<&'a Annotation>,
+    /// 第 i 个所选信号滤波后的全部物理值
+    filtered: Vec<Option<Vec<f64>>>,
+}
+
+impl EdfReader {
+    /// Writes signals as CSV, one row per sample
+    ///
+    /// The first column is the time in seconds since the first sample of the
+    /// file; the header line holds `time` and each signal's label with its
+    /// unit, e.g. `EEG C3 (uV)`. All exported signals must have the same
+    /// sampling rate, unless [`ExportOptions::resample`] is used. The file is
+    /// written in chunks, so the recording does not have to fit in memory.
+    ///
+    /// Annotations in the window are written to a second CSV file next to
+    /// `path` (`recording.csv` → `recording.annotations.csv`) with the
+    /// columns `onset`, `duration` (empty if unknown) and `description`, on
+    /// the same time base as the samples.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - No signals are selected, the window is
+    ///   invalid, the signals have different sampling rates, raw int16
+    ///   values are combined with resampling or filtering, or a filter
+    ///   cannot be designed for its signal's sampling rate
+    /// * `EdfError::InvalidSignalIndex` - A selected or filtered signal does not exist
+    /// * `EdfError::DiscontinuousFile` - Resampling was requested for an EDF+D file
+    /// * `EdfError::Io` - I/O error reading the file or writing the export
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{EdfReader, ExportOptions};
+    ///
+    /// # edfplus::doctest_utils::create_simple_test_file("study.edf")?;
+    /// let reader = EdfReader::open("study.edf")?;
+    /// let summary = reader.export_csv("study.csv", &ExportOptions::new().window(0.0, 0.5))?;
+    /// assert_eq!(summary.signals, vec!["EEG Fp1 (uV)"]);
+    ///
+    /// let csv = std::fs::read_to_string("study.csv")?;
+    /// assert!(csv.starts_with("time,EEG Fp1 (uV)\n0,"));
+    ///
+    /// # std::fs::remove_file("study.edf").ok();
+    /// # std::fs::remove_file("study.csv").ok();
+    /// # std::fs::remove_file("study.annotations.csv").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn export_csv<P: AsRef<Path>>(&self, path: P, options: &ExportOptions) -> Result<ExportSummary> {
+        let plan = ExportPlan::new(self, options)?;
+        plan.check_common_time_base()?;
+
+        let header = self.header();
+        let columns: Vec<String> = plan.signals.iter()
+            .map(|&signal| {
+                let signal = &header.signals[signal];
+                if signal.physical_dimension.is_empty() || plan.dtype == ExportDtype::Int16 {
+                    signal.label.clone()
+                } else {
+                    format!("{} ({})", signal.label, signal.physical_dimension)
+                }
+            })
+            .collect();
+
+        let delimiter = options.delimiter.unwrap_or(',');
+        let mut out = BufWriter::new(File::create(path.as_ref())?);
+        write!(out, "time")?;
+        for column in &columns {
+            write!(out, "{}{}", delimiter, quote(column, delimiter))?;
+        }
+        writeln!(out)?;
+
+        let range = plan.samples[0].clone();
+        let mut start = range.start;
+        while start < range.end {
+            let count = CHUNK.min((range.end - start) as usize);
+            let times = plan.times(0, start, count)?;
+            let values = (0..plan.signals.len())
+                .map(|i| plan.read_text(i, start, count))
+                .collect::<Result<Vec<_>>>()?;
+            for (row, time) in times.iter().enumerate() {
+                write!(out, "{}", time)?;
+                for column in &values {
+                    write!(out, "{}{}", delimiter, column[row])?;
+                }
+                writeln!(out)?;
+            }
+            start += count as i64;
+        }
+        out.flush()?;
+
+        let annotations_path = plan.write_annotations_csv(path.as_ref(), options)?;
+        Ok(plan.summary(columns, annotations_path))
+    }
+
+    /// Writes signals as one NumPy `.npy` array of shape `(signals, samples)`
+    ///
+    /// Rows follow the signal selection. All exported signals must have the
+    /// same sampling rate, unless [`ExportOptions::resample`] is used; use
+    /// [`export_npz`](Self::export_npz) to keep signals with different rates
+    /// at their own rate. The array is written signal by signal in chunks.
+    /// Annotations are written to a CSV file next to `path`, as for
+    /// [`export_csv`](Self::export_csv).
+    ///
+    /// # Errors
+    ///
+    /// Same as [`export_csv`](Self::export_csv).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{EdfReader, ExportDtype, ExportOptions};
+    ///
+    /// # edfplus::doctest_utils::create_simple_test_file("model_input.edf")?;
+    /// let reader = EdfReader::open("model_input.edf")?;
+    /// let options = ExportOptions::new().dtype(ExportDtype::Float32).annotations(false);
+    /// let summary = reader.export_npy("model_input.npy", &options)?;
+    ///
+    /// // numpy.load("model_input.npy").shape == (1, 256)
+    /// assert_eq!(summary.samples, vec![256]);
+    ///
+    /// # std::fs::remove_file("model_input.edf").ok();
+    /// # std::fs::remove_file("model_input.npy").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn export_npy<P: AsRef<Path>>(&self, path: P, options: &ExportOptions) -> Result<ExportSummary> {
+        let plan = ExportPlan::new(self, options)?;
+        plan.check_common_time_base()?;
+
+        let mut out = BufWriter::new(File::create(path.as_ref())?);
+        let samples = (plan.samples[0].end - plan.samples[0].start) as usize;
+        out.write_all(&npy_header(plan.dtype.descr(), &[plan.signals.len(), samples]))?;
+        for i in 0..plan.signals.len() {
+            plan.write_values(&mut out, i)?;
+        }
+        out.flush()?;
+
+        let annotations_path = plan.write_annotations_csv(path.as_ref(), options)?;
+        let labels = plan.signals.iter().map(|&s| self.header().signals[s].label.clone()).collect();
+        Ok(plan.summary(labels, annotations_path))
+    }
+
+    /// Writes signals as a NumPy `.npz` archive, one array per signal
+    ///
+    /// Each signal is a 1-D array named after its label (`/` and `\` are
+    /// replaced by `_`, and repeated labels get a `_<n>` suffix), at its own
+    /// sampling rate unless [`ExportOptions::resample`] is used. Annotations
+    /// are stored in the same archive as `annotations_onset` and
+    /// `annotations_duration` (seconds, NaN if unknown) and
+    /// `annotations_description` (unicode strings). The archive is
+    /// uncompressed and written in chunks, using ZIP64 so that it may exceed
+    /// 4 GB.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`export_csv`](Self::export_csv), except that signals may have
+    /// different sampling rates.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{EdfReader, ExportOptions};
+    ///
+    /// # edfplus::doctest_utils::create_multi_channel_test_file("polysomnography.edf")?;
+    /// let reader = EdfReader::open("polysomnography.edf")?;
+    /// let summary = reader.export_npz("polysomnography.npz", &ExportOptions::new())?;
+    ///
+    /// // numpy.load("polysomnography.npz")["ECG Lead II"]
+    /// assert_eq!(summary.signals, vec!["EEG C3", "ECG Lead II"]);
+    ///
+    /// # std::fs::remove_file("polysomnography.edf").ok();
+    /// # std::fs::remove_file("polysomnography.npz").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn export_npz<P: AsRef<Path>>(&self, path: P, options: &ExportOptions) -> Result<ExportSummary> {
+        let plan = ExportPlan::new(self, options)?;
+
+        // 数组名取自标签，重复时加序号
+        let mut names: Vec<String> = Vec::with_capacity(plan.signals.len());
+        for (i, &signal) in plan.signals.iter().enumerate() {
+            let label = self.header().signals[signal].label.replace(['/', '\\'], "_");
+            let mut name = if label.is_empty() { format!("signal_{}", i) } else { label };
+            if names.contains(&name) {
+                name = format!("{}_{}", name, i);
+            }
+            names.push(name);
+        }
+
+        let mut archive = ZipWriter::new(BufWriter::new(File::create(path.as_ref())?));
+        for (i, name) in names.iter().enumerate() {
+            let samples = (plan.samples[i].end - plan.samples[i].start) as usize;
+            archive.add(&format!("{}.npy", name), |out| {
+                out.write_all(&npy_header(plan.dtype.descr(), &[samples]))?;
+                plan.write_values(out, i)
+            })?;
+        }
+
+        if options.annotations {
+            let annotations = &plan.annotations;
+            archive.add("annotations_onset.npy", |out| {
+                out.write_all(&npy_header("<f8", &[annotations.len()]))?;
+                for annotation in annotations {
+                    out.write_all(&seconds(annotation.onset).to_le_bytes())?;
+                }
+                Ok(())
+            })?;
+            archive.add("annotations_duration.npy", |out| {
+                out.write_all(&npy_header("<f8", &[annotations.len()]))?;
+                for annotation in annotations {
+                    let duration = if annotation.duration >= 0 { seconds(annotation.duration) } else { f64::NAN };
+                    out.write_all(&duration.to_le_bytes())?;
+                }
+                Ok(())
+            })?;
+            // 定长 UTF-32 字符串数组（NumPy 的 <U 类型）
+            let width = annotations.iter().map(|a| a.description.chars().count()).max().unwrap_or(0).max(1);
+            archive.add("annotations_description.npy", |out| {
+                out.write_all(&npy_header(&format!("<U{}", width), &[annotations.len()]))?;
+                for annotation in annotations {
+                    let mut text = vec![0u8; width * 4];
+                    for (slot, c) in text.chunks_mut(4).zip(annotation.description.chars()) {
+                        slot.copy_from_slice(&(c as u32).to_le_bytes());
+                    }
+                    out.write_all(&text)?;
+                }
+                Ok(())
+            })?;
+        }
+        archive.finish()?;
+
+        Ok(plan.summary(names, None))
+    }
+}
+
+impl<'a> ExportPlan<'a> {
+    fn new(reader: &'a EdfReader, options: &ExportOptions) -> Result<ExportPlan<'a>> {
+        let header = reader.header();
+        let signal_count = header.signals.len();
+        let signals = options.signals.clone().unwrap_or_else(|| (0..signal_count).collect());
+        if signals.is_empty() {
+            return Err(EdfError::InvalidFormat("No signals selected for export".to_string()));
+        }
+        if let Some(&invalid) = signals.iter().find(|&&s| s >= signal_count) {
+            return Err(EdfError::InvalidSignalIndex(invalid));
+        }
+        if let Some(&(invalid, _)) = options.filters.iter().find(|&&(s, _)| s >= signal_count) {
+            return Err(EdfError::InvalidSignalIndex(invalid));
+        }
+        if options.dtype == ExportDtype::Int16 && !options.filters.is_empty() {
+            return Err(EdfError::InvalidFormat("Raw int16 values cannot be filtered".to_string()));
+        }
+        if let Some((start, end)) = options.window {
+            if !(start.is_finite() && end.is_finite()) || start < 0.0 || end <= start {
+                return Err(EdfError::InvalidFormat(format!("Invalid export window from {} s to {} s", start, end)));
+            }
+        }
+
+        let (resampler, samples) = match options.rate {
+            Some(rate) => {
+                if options.dtype == ExportDtype::Int16 {
+                    return Err(EdfError::InvalidFormat("Raw int16 values cannot be resampled".to_string()));
+                }
+                // 重采样后的时间轴假定数据记录之间没有间隙
+                if header.discontinuous {
+                    return Err(EdfError::DiscontinuousFile);
+                }
+                let resampler = reader.resample(&ResampleOptions::new(rate).signals(&signals))?;
+                let total = resampler.samples_in_file();
+                let range = match options.window {
+                    Some((start, end)) => {
+                        let first = ((start * rate).round() as i64).min(total);
+                        first..((end * rate).round() as i64).clamp(first, total)
+                    }
+                    None => 0..total,
+                };
+                (Some(resampler), vec![range; signals.len()])
+            }
+            None => {
+                let samples = signals.iter()
+                    .map(|&signal| {
+                        let total = header.signals[signal].samples_per_record as i64 * header.datarecords_in_file;
+                        Ok(match options.window {
+                            Some((start, end)) => {
+                                let first = reader.seconds_to_sample(signal, start)?.min(total);
+                                first..reader.seconds_to_sample(signal, end)?.clamp(first, total)
+                            }
+                            None => 0..total,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                (None, samples)
+            }
+        };
+
+        let annotations = match (options.annotations, options.window) {
+            (false, _) => Vec::new(),
+            (true, Some((start, end))) => {
+                let (start, end) = (time(start), time(end));
+                reader.annotations().iter().filter(|a| overlaps(a, start, end)).collect()
+            }
+            (true, None) => reader.annotations().iter().collect(),
+        };
+
+        // 滤波需要整个信号（零相位时前后两遍），窗口和重采样作用于滤波结果
+        let filtered = signals.iter()
+            .map(|&signal| {
+                options.filters.iter()
+                    .find(|(filtered, _)| *filtered == signal)
+                    .map(|(_, chain)| reader.read_filtered(signal, chain))
+                    .transpose()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ExportPlan { reader, resampler, signals, samples, dtype: options.dtype, annotations, filtered })
+    }
+
+    /// 按行导出的格式要求所有信号使用同一时间轴
+    fn check_common_time_base(&self) -> Result<()> {
+        if self.resampler.is_some() {
+            return Ok(());
+        }
+        let header = self.reader.header();
+        let first = self.signals[0];
+        for &signal in &self.signals[1..] {
+            if header.signals[signal].samples_per_record != header.signals[first].samples_per_record {
+                return Err(EdfError::InvalidFormat(format!(
+                    "Signals '{}' and '{}' have different sampling rates; export them separately, to .npz, or resample",
+                    header.signals[first].label, header.signals[signal].label
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// 第 i 个所选信号的物理值（必要时滤波、重采样）
+    fn read_physical(&self, i: usize, start: i64, count: usize) -> Result<Vec<f64>> {
+        let read_source = |start: i64, count: usize| match &self.filtered[i] {
+            Some(samples) => {
+                let start = (start.max(0) as usize).min(samples.len());
+                Ok(samples[start..samples.len().min(start.saturating_add(count))].to_vec())
+            }
+            None => self.reader.read_physical_at(self.signals[i], start, count),
+        };
+        match &self.resampler {
+            Some(resampler) => resampler.read_signal_from(i, start, count, read_source),
+            None => read_source(start, count),
+        }
+    }
+
+    /// 样本时间（秒，相对于文件的第一个样本）
+    fn times(&self, i: usize, start: i64, count: usize) -> Result<Vec<f64>> {
+        match &self.resampler {
+            Some(resampler) => Ok((start..start + count as i64).map(|k| k as f64 / resampler.rate()).collect()),
+            None => self.reader.sample_times(self.signals[i], start, count),
+        }
+    }
+
+    /// CSV 中的样本值文本
+    fn read_text(&self, i: usize, start: i64, count: usize) -> Result<Vec<String>> {
+        Ok(match self.dtype {
+            ExportDtype::Float64 => self.read_physical(i, start, count)?.iter().map(f64::to_string).collect(),
+            ExportDtype::Float32 => self.read_physical(i, start, count)?.iter().map(|&v| (v as f32).to_string()).collect(),
+            ExportDtype::Int16 => self.reader.read_raw_digital_at(self.signals[i], start, count)?
+                .iter().map(i32::to_string).collect(),
+        })
+    }
+
+    /// 分块写出第 i 个所选信号的小端序二进制值
+    fn write_values(&self, out: &mut dyn Write, i: usize) -> Result<()> {
+        let range = self.samples[i].clone();
+        let mut start = range.start;
+        let mut bytes = Vec::new();
+        while start < range.end {
+            let count = CHUNK.min((range.end - start) as usize);
+            bytes.clear();
+            match self.dtype {
+                ExportDtype::Float64 => for value in self.read_physical(i, start, count)? {
+                    bytes.extend_from_slice(&value.to_le_bytes());
+                },
+                ExportDtype::Float32 => for value in self.read_physical(i, start, count)? {
+                    bytes.extend_from_slice(&(value as f32).to_le_bytes());
+                },
+                ExportDtype::Int16 => for value in self.reader.read_raw_digital_at(self.signals[i], start, count)? {
+                    bytes.extend_from_slice(&(value as i16).to_le_bytes());
+                },
+            }
+            out.write_all(&bytes)?;
+            start += count as i64;
+        }
+        Ok(())
+    }
+
+    /// 注释写入导出文件旁的 CSV 文件
+    fn write_annotations_csv(&self, path: &Path, options: &ExportOptions) -> Result<Option<PathBuf>> {
+        if !options.annotations {
+            return Ok(None);
+        }
+        let annotations_path = path.with_extension("annotations.csv");
+        let mut out = BufWriter::new(File::create(&annotations_path)?);
+        writeln!(out, "onset,duration,description")?;
+        for annotation in &self.annotations {
+            let duration = if annotation.duration >= 0 { seconds(annotation.duration).to_string() } else { String::new() };
+            writeln!(out, "{},{},{}", seconds(annotation.onset), duration, quote(&annotation.description, ','))?;
+        }
+        out.flush()?;
+        Ok(Some(annotations_path))
+    }
+
+    fn summary(&self, signals: Vec<String>, annotations_path: Option<PathBuf>) -> ExportSummary {
+        ExportSummary {
+            signals,
+            samples: self.samples.iter().map(|range| range.end - range.start).collect(),
+            annotations: self.annotations.len(),
+            annotations_path,
+        }
+    }
+}
+
+impl ExportDtype {
+    /// NumPy 类型描述
+    fn descr(self) -> &'static str {
+        match self {
+            ExportDtype::Float64 => "<f8",
+            ExportDtype::Float32 => "<f4",
+            ExportDtype::Int16 => "<i2",
+        }
+    }
+}
+
+/// NPY 1.0 头部：魔数、版本、头部长度和描述字典，总长度按 64 字节对齐
+fn npy_header(descr: &str, shape: &[usize]) -> Vec<u8> {
+    let shape = match shape {
+        [n] => format!("({},)", n),
+        _ => format!("({})", shape.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")),
+    };
+    let mut dict = format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}", descr, shape);
+    let padded = (10 + dict.len() + 1).div_ceil(64) * 64 - 10;
+    while dict.len() < padded - 1 {
+        dict.push(' ');
+    }
+    dict.push('\n');
+
+    let mut header = b"\x93NUMPY\x01\x00".to_vec();
+    header.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    header.extend_from_slice(dict.as_bytes());
+    header
+}
+
+/// 不压缩的 ZIP64 归档，逐个条目流式写入，写完后回填 CRC 和长度
+struct ZipWriter<W: Write + Seek> {
+    out: W,
+    entries: Vec<ZipEntry>,
+}
+
+struct ZipEntry {
+    name: String,
+    crc: u32,
+    size: u64,
+    offset: u64,
+}
+
+/// 写入时计算 CRC-32 和长度
+struct CrcWriter<'a, W: Write> {
+    inner: &'a mut W,
+    crc: u32,
+    size: u64,
+}
+
+impl<W: Write> Write for CrcWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.crc = crc32_update(self.crc, &buf[..written]);
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 版本 4.5（ZIP64），存储方式，日期 1980-01-01
+const ZIP_VERSION: u16 = 45;
+const ZIP_DOS_DATE: u16 = (1 << 5) | 1;
+
+impl<W: Write + Seek> ZipWriter<W> {
+    fn new(out: W) -> Self {
+        ZipWriter { out, entries: Vec::new() }
+    }
+
+    fn add(&mut self, name: &str, write: impl FnOnce(&mut dyn Write) -> Result<()>) -> Result<()> {
+        let offset = self.out.stream_position()?;
+        let mut entry = ZipEntry { name: name.to_string(), crc: 0, size: 0, offset };
+        self.out.write_all(&entry.local_header())?;
+
+        let mut data = CrcWriter { inner: &mut self.out, crc: !0, size: 0 };
+        write(&mut data)?;
+        entry.crc = !data.crc;
+        entry.size = data.size;
+
+        self.out.seek(SeekFrom::Start(offset))?;
+        self.out.write_all(&entry.local_header())?;
+        self.out.seek(SeekFrom::End(0))?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<()> {
+        let directory_offset = self.out.stream_position()?;
+        for entry in &self.entries {
+            self.out.write_all(&entry.central_header())?;
+        }
+        let end_offset = self.out.stream_position()?;
+        let count = self.entries.len() as u64;
+
+        let mut end = Vec::new();
+        // ZIP64 中央目录结束记录
+        end.extend_from_slice(&0x06064b50u32.to_le_bytes());
+        end.extend_from_slice(&44u64.to_le_bytes());
+        end.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        end.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        end.extend_from_slice(&[0; 8]);
+        end.extend_from_slice(&count.to_le_bytes());
+        end.extend_from_slice(&count.to_le_bytes());
+        end.extend_from_slice(&(end_offset - directory_offset).to_le_bytes());
+        end.extend_from_slice(&directory_offset.to_le_bytes());
+        // ZIP64 结束记录定位器
+        end.extend_from_slice(&0x07064b50u32.to_le_bytes());
+        end.extend_from_slice(&0u32.to_le_bytes());
+        end.extend_from_slice(&end_offset.to_le_bytes());
+        end.extend_from_slice(&1u32.to_le_bytes());
+        // 中央目录结束记录，数值字段指向 ZIP64 记录
+        end.extend_from_slice(&0x06054b50u32.to_le_bytes());
+        end.extend_from_slice(&[0; 4]);
+        end.extend_from_slice(&[0xff; 12]);
+        end.extend_from_slice(&0u16.to_le_bytes());
+        self.out.write_all(&end)?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+impl ZipEntry {
+    fn local_header(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(50 + self.name.len());
+        header.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        header.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        header.extend_from_slice(&[0; 6]);
+        header.extend_from_slice(&ZIP_DOS_DATE.to_le_bytes());
+        header.extend_from_slice(&self.crc.to_le_bytes());
+        header.extend_from_slice(&[0xff; 8]);
+        header.extend_from_slice(&(self.name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&20u16.to_le_bytes());
+        header.extend_from_slice(self.name.as_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&16u16.to_le_bytes());
+        header.extend_from_slice(&self.size.to_le_bytes());
+        header.extend_from_slice(&self.size.to_le_bytes());
+        header
+    }
+
+    fn central_header(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(74 + self.name.len());
+        header.extend_from_slice(&0x02014b50u32.to_le_bytes());
+        header.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        header.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        header.extend_from_slice(&[0; 6]);
+        header.extend_from_slice(&ZIP_DOS_DATE.to_le_bytes());
+        header.extend_from_slice(&self.crc.to_le_bytes());
+        header.extend_from_slice(&[0xff; 8]);
+        header.extend_from_slice(&(self.name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&28u16.to_le_bytes());
+        header.extend_from_slice(&[0; 10]);
+        header.extend_from_slice(&[0xff; 4]);
+        header.extend_from_slice(self.name.as_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&24u16.to_le_bytes());
+        header.extend_from_slice(&self.size.to_le_bytes());
+        header.extend_from_slice(&self.size.to_le_bytes());
+        header.extend_from_slice(&self.offset.to_le_bytes());
+        header
+    }
+}
+
+/// CRC-32（IEEE 802.3，反射多项式 0xEDB88320）查找表
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { 0xEDB88320 ^ (crc >> 1) } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32_update(crc: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(crc, |crc, &byte| CRC_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+fn seconds(time: i64) -> f64 {
+    time as f64 / EDFLIB_TIME_DIMENSION as f64
+}
+
+fn time(seconds: f64) -> i64 {
+    (seconds * EDFLIB_TIME_DIMENSION as f64).round() as i64
+}
+
+/// CSV 字段中包含逗号、引号或换行时加引号
+fn quote(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
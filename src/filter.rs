@@ -0,0 +1,345 @@
+use std::f64::consts::PI;
+use std::fmt;
+
+use crate::error::{EdfError, Result};
+use crate::reader::EdfReader;
+
+/// Highest supported Butterworth order
+const MAX_ORDER: usize = 8;
+
+/// One stage of a [`FilterChain`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterStage {
+    /// Butterworth high-pass filter
+    HighPass {
+        /// -3 dB cutoff frequency in Hz
+        cutoff_hz: f64,
+        /// Filter order (1 to 8)
+        order: usize,
+    },
+    /// Butterworth low-pass filter
+    LowPass {
+        /// -3 dB cutoff frequency in Hz
+        cutoff_hz: f64,
+        /// Filter order (1 to 8)
+        order: usize,
+    },
+    /// Second-order notch filter, e.g. for 50 or 60 Hz mains interference
+    Notch {
+        /// Centre frequency in Hz
+        frequency_hz: f64,
+        /// Quality factor: centre frequency divided by the -3 dB bandwidth
+        q: f64,
+    },
+}
+
+/// A sequence of IIR filters, described independently of the sampling rate
+///
+/// The chain is designed for a signal with [`design`](Self::design), or
+/// attached to signals with [`EdfReader::set_filter`](crate::EdfReader::set_filter)
+/// and [`EdfWriter::set_filter`](crate::EdfWriter::set_filter). Its
+/// `Display` text is the EDF+ prefilter notation, e.g. `HP:0.3Hz LP:35Hz N:50Hz`.
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::FilterChain;
+///
+/// // Routine EEG review filters
+/// let eeg = FilterChain::new().highpass(0.3, 2).lowpass(35.0, 4).notch(50.0, 30.0);
+/// assert_eq!(eeg.to_string(), "HP:0.3Hz LP:35Hz N:50Hz");
+///
+/// // Offline analysis without phase distortion
+/// let offline = FilterChain::new().lowpass(40.0, 4).zero_phase();
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FilterChain {
+    stages: Vec<FilterStage>,
+    zero_phase: bool,
+}
+
+impl FilterChain {
+    /// An empty chain, which passes samples unchanged
+    pub fn new() -> Self {
+        FilterChain::default()
+    }
+
+    /// Adds a Butterworth high-pass filter
+    pub fn highpass(mut self, cutoff_hz: f64, order: usize) -> Self {
+        self.stages.push(FilterStage::HighPass { cutoff_hz, order });
+        self
+    }
+
+    /// Adds a Butterworth low-pass filter
+    pub fn lowpass(mut self, cutoff_hz: f64, order: usize) -> Self {
+        self.stages.push(FilterStage::LowPass { cutoff_hz, order });
+        self
+    }
+
+    /// Adds a notch filter with quality factor `q` (30 is a common choice)
+    pub fn notch(mut self, frequency_hz: f64, q: f64) -> Self {
+        self.stages.push(FilterStage::Notch { frequency_hz, q });
+        self
+    }
+
+    /// Runs the chain forwards and backwards, cancelling its phase shift
+    ///
+    /// Zero-phase filtering needs the whole signal, so it is only available
+    /// for offline use ([`SignalFilter::filter_zero_phase`] and
+    /// [`EdfReader::read_filtered`](crate::EdfReader::read_filtered)). The
+    /// magnitude response is squared, i.e. the effective order doubles.
+    pub fn zero_phase(mut self) -> Self {
+        self.zero_phase = true;
+        self
+    }
+
+    /// 在本滤波链之后追加另一个滤波链的各级
+    pub(crate) fn then(mut self, other: &FilterChain) -> FilterChain {
+        self.stages.extend_from_slice(&other.stages);
+        self
+    }
+
+    /// The stages, in the order they are applied
+    pub fn stages(&self) -> &[FilterStage] {
+        &self.stages
+    }
+
+    /// Whether the chain is applied forwards and backwards
+    pub fn is_zero_phase(&self) -> bool {
+        self.zero_phase
+    }
+
+    /// Designs the chain for a sampling rate
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - The sampling rate is not positive, a
+    ///   frequency is not between 0 and the Nyquist frequency, an order is not
+    ///   between 1 and 8, or a notch quality factor is not positive
+    pub fn design(&self, sample_rate_hz: f64) -> Result<SignalFilter> {
+        if !sample_rate_hz.is_finite() || sample_rate_hz <= 0.0 {
+            return Err(EdfError::InvalidFormat(format!(
+                "Cannot design a filter for a sampling rate of {} Hz", sample_rate_hz
+            )));
+        }
+        let nyquist = sample_rate_hz / 2.0;
+        let check_frequency = |frequency: f64| {
+            if frequency > 0.0 && frequency < nyquist {
+                Ok(frequency / sample_rate_hz)
+            } else {
+                Err(EdfError::InvalidFormat(format!(
+                    "Filter frequency {} Hz must be between 0 and the Nyquist frequency ({} Hz)", frequency, nyquist
+                )))
+            }
+        };
+        let check_order = |order: usize| {
+            if (1..=MAX_ORDER).contains(&order) {
+                Ok(order)
+            } else {
+                Err(EdfError::InvalidFormat(format!("Filter order must be between 1 and {}, got {}", MAX_ORDER, order)))
+            }
+        };
+
+        let mut sections = Vec::new();
+        for stage in &self.stages {
+            match *stage {
+                FilterStage::HighPass { cutoff_hz, order } => {
+                    butterworth(&mut sections, check_frequency(cutoff_hz)?, check_order(order)?, true)
+                }
+                FilterStage::LowPass { cutoff_hz, order } => {
+                    butterworth(&mut sections, check_frequency(cutoff_hz)?, check_order(order)?, false)
+                }
+                FilterStage::Notch { frequency_hz, q } => {
+                    let frequency = check_frequency(frequency_hz)?;
+                    if !q.is_finite() || q <= 0.0 {
+                        return Err(EdfError::InvalidFormat(format!("Notch quality factor must be positive, got {}", q)));
+                    }
+                    sections.push(Biquad::notch(frequency, q));
+                }
+            }
+        }
+        Ok(SignalFilter { sections, zero_phase: self.zero_phase, started: false })
+    }
+}
+
+impl fmt::Display for FilterChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, stage) in self.stages.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            match stage {
+                FilterStage::HighPass { cutoff_hz, .. } => write!(f, "HP:{}Hz", cutoff_hz)?,
+                FilterStage::LowPass { cutoff_hz, .. } => write!(f, "LP:{}Hz", cutoff_hz)?,
+                FilterStage::Notch { frequency_hz, .. } => write!(f, "N:{}Hz", frequency_hz)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A [`FilterChain`] designed for one sampling rate, with its filter state
+///
+/// [`filter`](Self::filter) keeps its state between calls, so a signal can
+/// be filtered in chunks of any size with the same result as in one go. The
+/// state starts in the steady state for the first sample, which avoids the
+/// start-up transient of a high-pass filter on a signal with an offset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalFilter {
+    sections: Vec<Biquad>,
+    zero_phase: bool,
+    started: bool,
+}
+
+impl SignalFilter {
+    /// Filters the next chunk of a signal in place (causal)
+    ///
+    /// Non-finite samples are passed through unchanged and do not affect
+    /// the filter state.
+    pub fn filter(&mut self, samples: &mut [f64]) {
+        for sample in samples.iter_mut().filter(|s| s.is_finite()) {
+            if !self.started {
+                self.start(*sample);
+            }
+            *sample = self.sections.iter_mut().fold(*sample, |x, section| section.process(x));
+        }
+    }
+
+    /// Filters a whole signal in place forwards and then backwards
+    ///
+    /// Leaves the state of `self` untouched.
+    pub fn filter_zero_phase(&self, samples: &mut [f64]) {
+        let mut pass = self.clone();
+        pass.reset();
+        pass.filter(samples);
+        samples.reverse();
+        pass.reset();
+        pass.filter(samples);
+        samples.reverse();
+    }
+
+    /// Whether the chain was configured with [`FilterChain::zero_phase`]
+    pub fn is_zero_phase(&self) -> bool {
+        self.zero_phase
+    }
+
+    /// Clears the filter state, e.g. before filtering a non-adjacent chunk
+    pub fn reset(&mut self) {
+        self.started = false;
+    }
+
+    /// 按恒定输入 x 的稳态设置每一节的状态
+    fn start(&mut self, x: f64) {
+        let mut x = x;
+        for section in &mut self.sections {
+            x = section.settle(x);
+        }
+        self.started = true;
+    }
+}
+
+impl EdfReader {
+    /// Reads a whole signal through a filter chain
+    ///
+    /// Offline counterpart of [`set_filter`](Self::set_filter): takes
+    /// `&self`, leaves the read positions untouched and supports zero-phase
+    /// chains, which are run forwards and backwards over the whole signal.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidSignalIndex` - Signal index is out of bounds
+    /// * `EdfError::InvalidFormat` - The chain cannot be designed for the
+    ///   signal's sampling rate
+    /// * `EdfError::Io` - I/O error reading from file
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{EdfReader, FilterChain};
+    ///
+    /// # edfplus::doctest_utils::create_simple_test_file("offline.edf")?;
+    /// let reader = EdfReader::open("offline.edf")?;
+    /// let alpha_band = FilterChain::new().highpass(8.0, 4).lowpass(13.0, 4).zero_phase();
+    /// let alpha = reader.read_filtered(0, &alpha_band)?;
+    /// assert_eq!(alpha.len() as i64, reader.header().signals[0].samples_in_file);
+    ///
+    /// # std::fs::remove_file("offline.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn read_filtered(&self, signal: usize, filter: &FilterChain) -> Result<Vec<f64>> {
+        let mut filter = filter.design(self.header().sampling_frequency(signal)?)?;
+        let mut samples = self.read_physical_at(signal, 0, usize::MAX)?;
+        if filter.is_zero_phase() {
+            filter.filter_zero_phase(&mut samples);
+        } else {
+            filter.filter(&mut samples);
+        }
+        Ok(samples)
+    }
+}
+
+/// 二阶节（直接 II 型转置），a0 已归一化为 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 3]) -> Biquad {
+        Biquad { b: b.map(|b| b / a[0]), a: [a[1] / a[0], a[2] / a[0]], z: [0.0; 2] }
+    }
+
+    /// 一阶 Butterworth 节（双线性变换，预畸变），frequency 为相对采样率的频率
+    fn first_order(frequency: f64, highpass: bool) -> Biquad {
+        let k = (PI * frequency).tan();
+        let b = if highpass { [1.0, -1.0, 0.0] } else { [k, k, 0.0] };
+        Biquad::new(b, [1.0 + k, k - 1.0, 0.0])
+    }
+
+    /// 二阶低通/高通节（RBJ 公式，即预畸变的双线性变换）
+    fn second_order(frequency: f64, q: f64, highpass: bool) -> Biquad {
+        let w = 2.0 * PI * frequency;
+        let (cos, alpha) = (w.cos(), w.sin() / (2.0 * q));
+        let b = if highpass {
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0]
+        } else {
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0]
+        };
+        Biquad::new(b, [1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+    }
+
+    fn notch(frequency: f64, q: f64) -> Biquad {
+        let w = 2.0 * PI * frequency;
+        let (cos, alpha) = (w.cos(), w.sin() / (2.0 * q));
+        Biquad::new([1.0, -2.0 * cos, 1.0], [1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+
+    /// 设置恒定输入 x 下的稳态，返回稳态输出（直流增益乘 x）
+    fn settle(&mut self, x: f64) -> f64 {
+        let y = x * self.b.iter().sum::<f64>() / (1.0 + self.a[0] + self.a[1]);
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        self.z[0] = y - self.b[0] * x;
+        y
+    }
+}
+
+/// Butterworth 滤波器分解为二阶节：奇数阶另加一个一阶节
+fn butterworth(sections: &mut Vec<Biquad>, frequency: f64, order: usize, highpass: bool) {
+    if order % 2 == 1 {
+        sections.push(Biquad::first_order(frequency, highpass));
+    }
+    // 每对共轭极点的 Q = 1 / (2 cos θ)
+    for k in 0..order / 2 {
+        let angle = PI * (2 * k + 1 + order % 2) as f64 / (2 * order) as f64;
+        sections.push(Biquad::second_order(frequency, 1.0 / (2.0 * angle.cos()), highpass));
+    }
+}
@@ -0,0 +1,30 @@
+// Entry points for fuzz targets (see fuzz/ in the repository)
+// These wrap internal parsers so they can be driven with arbitrary bytes.
+// Every function must return normally, whatever the input.
+
+use std::io::Cursor;
+
+use crate::reader::EdfReader;
+use crate::types::{Annotation, EdfHeader};
+use crate::Result;
+
+/// Parses a complete file image: main header, signal headers and the
+/// annotations of the first data records
+pub fn parse_header(data: &[u8]) -> Result<EdfHeader> {
+    EdfReader::parse_header(&mut Cursor::new(data)).map(|(header, _, _)| header)
+}
+
+/// Parses signal headers; the first byte selects the number of signals
+/// (at most 255), the rest is the signal header block
+pub fn parse_signals(data: &[u8]) -> Result<usize> {
+    let (&count, signal_header) = match data.split_first() {
+        Some(split) => split,
+        None => return Ok(0),
+    };
+    EdfReader::parse_signals(signal_header, count as usize, 1).map(|(signals, _, _)| signals.len())
+}
+
+/// Parses the content of one annotation signal of a data record
+pub fn parse_tal_data(data: &[u8]) -> Result<Vec<Annotation>> {
+    EdfReader::parse_tal_data(data, 0, true, 0, 0)
+}
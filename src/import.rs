@@ -0,0 +1,667 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+
+use crate::crop::annotation_channels;
+use crate::error::{EdfError, Result};
+use crate::types::SignalParam;
+use crate::writer::{EdfWriter, PaddingPolicy, SamplePolicy, SampleStats};
+use crate::EDFLIB_TIME_DIMENSION;
+
+/// 每次传给写入器的行数
+const CHUNK_ROWS: usize = 4096;
+
+/// Sample encoding of interleaved little-endian binary input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawSampleType {
+    /// 16-bit signed integers
+    Int16,
+    /// 32-bit signed integers
+    Int32,
+    /// 32-bit floats
+    Float32,
+    /// 64-bit floats
+    Float64,
+}
+
+impl RawSampleType {
+    /// Size of one sample in bytes
+    pub fn size(self) -> usize {
+        match self {
+            RawSampleType::Int16 => 2,
+            RawSampleType::Int32 | RawSampleType::Float32 => 4,
+            RawSampleType::Float64 => 8,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> f64 {
+        match self {
+            RawSampleType::Int16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            RawSampleType::Int32 => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            RawSampleType::Float32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            RawSampleType::Float64 => {
+                let mut value = [0u8; 8];
+                value.copy_from_slice(&bytes[..8]);
+                f64::from_le_bytes(value)
+            }
+        }
+    }
+}
+
+/// A CSV column or binary channel imported as an EDF+ signal
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::ImportSignal;
+///
+/// // Column 1 in microvolts, range derived from the data
+/// let eeg = ImportSignal::new(1, "EEG Fp1").unit("uV");
+///
+/// // Column 3 with a fixed range
+/// let spo2 = ImportSignal::new(3, "SpO2").unit("%").physical_range(0.0, 100.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportSignal {
+    column: usize,
+    label: String,
+    physical_dimension: String,
+    physical_range: Option<(f64, f64)>,
+    prefilter: String,
+    transducer: String,
+}
+
+impl ImportSignal {
+    /// Imports column `column` (0-based) as a signal named `label`
+    pub fn new(column: usize, label: &str) -> Self {
+        ImportSignal {
+            column,
+            label: label.to_string(),
+            physical_dimension: String::new(),
+            physical_range: None,
+            prefilter: String::new(),
+            transducer: String::new(),
+        }
+    }
+
+    /// Sets the physical dimension (unit) of the values
+    pub fn unit(mut self, unit: &str) -> Self {
+        self.physical_dimension = unit.to_string();
+        self
+    }
+
+    /// Uses a fixed physical range instead of deriving it from the data;
+    /// values outside it are handled by the [`SamplePolicy`]
+    pub fn physical_range(mut self, min: f64, max: f64) -> Self {
+        self.physical_range = Some((min, max));
+        self
+    }
+
+    /// Sets the prefilter description
+    pub fn prefilter(mut self, prefilter: &str) -> Self {
+        self.prefilter = prefilter.to_string();
+        self
+    }
+
+    /// Sets the transducer description
+    pub fn transducer(mut self, transducer: &str) -> Self {
+        self.transducer = transducer.to_string();
+        self
+    }
+}
+
+/// Options for [`import_csv`] and [`import_binary`]
+///
+/// # Examples
+///
+/// ```rust
+/// use chrono::NaiveDate;
+/// use edfplus::{ImportOptions, ImportSignal};
+///
+/// let start = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap().and_hms_opt(9, 15, 0).unwrap();
+/// let options = ImportOptions::new(250.0)
+///     .signal(ImportSignal::new(1, "EEG Fp1").unit("uV"))
+///     .signal(ImportSignal::new(2, "EEG Fp2").unit("uV"))
+///     .marker_column(3)
+///     .start(start);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportOptions {
+    sampling_rate: f64,
+    record_duration: f64,
+    signals: Vec<ImportSignal>,
+    marker_column: Option<usize>,
+    start: Option<NaiveDateTime>,
+    header: bool,
+    delimiter: char,
+    padding: PaddingPolicy,
+    sample_policy: SamplePolicy,
+}
+
+impl ImportOptions {
+    /// Input sampled at `sampling_rate_hz`, one row per sample, written in
+    /// 1 second data records
+    ///
+    /// Without [`signal`](Self::signal), every column except the marker
+    /// column is imported, named after the CSV header (or `Ch1`, `Ch2`, ...)
+    /// with a range derived from the data.
+    pub fn new(sampling_rate_hz: f64) -> Self {
+        ImportOptions {
+            sampling_rate: sampling_rate_hz,
+            record_duration: 1.0,
+            signals: Vec::new(),
+            marker_column: None,
+            start: None,
+            header: true,
+            delimiter: ',',
+            padding: PaddingPolicy::default(),
+            sample_policy: SamplePolicy::default(),
+        }
+    }
+
+    /// Adds a signal; signals are written in the order they are added
+    pub fn signal(mut self, signal: ImportSignal) -> Self {
+        self.signals.push(signal);
+        self
+    }
+
+    /// Sets the data record duration in seconds (default: 1); the sampling
+    /// rate times the duration must be a whole number of samples
+    pub fn record_duration(mut self, seconds: f64) -> Self {
+        self.record_duration = seconds;
+        self
+    }
+
+    /// Turns a column into annotations
+    ///
+    /// A row starts an annotation when its marker is not empty, not `0` and
+    /// differs from the previous row's marker, so a trigger value held over
+    /// several rows gives a single annotation. The marker text (or value, for
+    /// binary input) is the description.
+    pub fn marker_column(mut self, column: usize) -> Self {
+        self.marker_column = Some(column);
+        self
+    }
+
+    /// Sets the start date and time of the recording
+    pub fn start(mut self, start: NaiveDateTime) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Sets whether the first CSV line is a header (default: true)
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Sets the CSV field delimiter (default: `,`)
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets how a final partial data record is completed (default: discarded)
+    pub fn padding(mut self, padding: PaddingPolicy) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets how out-of-range and missing (NaN) values are written
+    pub fn sample_policy(mut self, policy: SamplePolicy) -> Self {
+        self.sample_policy = policy;
+        self
+    }
+}
+
+/// Result of [`import_csv`] or [`import_binary`]
+#[derive(Debug, Clone)]
+pub struct ImportSummary {
+    /// Signals as written, including derived physical ranges
+    pub signals: Vec<SignalParam>,
+    /// Number of input rows read
+    pub rows: u64,
+    /// Number of data records written
+    pub records: i64,
+    /// Number of annotations written from the marker column
+    pub annotations: usize,
+    /// Per-signal sample counters from the writer
+    pub stats: Vec<SampleStats>,
+}
+
+/// Imports a CSV file with one row per sample into a new EDF+ file
+///
+/// Rows are streamed through [`EdfWriter`], so the input does not have to fit
+/// in memory. Empty cells are read as NaN (see
+/// [`ImportOptions::sample_policy`]) and blank lines are skipped. Fields may
+/// be quoted. Signals without a fixed physical range, and the marker column,
+/// need a first pass over the input before anything is written.
+///
+/// # Errors
+///
+/// * `EdfError::InvalidFormat` - Invalid sampling rate or record duration, a
+///   column that does not exist, a cell that is not a number, or a signal
+///   without finite values to derive its range from
+/// * `EdfError::InvalidSample` - A value rejected by the sample policy
+/// * `EdfError::Io` - I/O error reading the input or writing the output
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::{import_csv, EdfReader, ImportOptions, ImportSignal};
+///
+/// std::fs::write("device.csv", "\
+/// time,Fp1,Fp2,event
+/// 0.00,12.5,-3.0,
+/// 0.25,14.0,-2.5,start
+/// 0.50,13.0,-1.0,
+/// 0.75,11.5,0.5,
+/// ")?;
+///
+/// let options = ImportOptions::new(4.0)
+///     .signal(ImportSignal::new(1, "EEG Fp1").unit("uV"))
+///     .signal(ImportSignal::new(2, "EEG Fp2").unit("uV").physical_range(-100.0, 100.0))
+///     .marker_column(3);
+/// let summary = import_csv("device.csv", "device.edf", &options)?;
+/// assert_eq!((summary.rows, summary.records, summary.annotations), (4, 1, 1));
+///
+/// let reader = EdfReader::open("device.edf")?;
+/// assert_eq!(reader.annotations()[0].description, "start");
+/// assert_eq!(reader.annotations()[0].onset, 2_500_000);
+///
+/// # std::fs::remove_file("device.csv").ok();
+/// # std::fs::remove_file("device.edf").ok();
+/// # Ok::<(), edfplus::EdfError>(())
+/// ```
+pub fn import_csv<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q, options: &ImportOptions) -> Result<ImportSummary> {
+    import(input.as_ref(), output.as_ref(), Format::Csv, options)
+}
+
+/// Imports interleaved little-endian binary samples into a new EDF+ file
+///
+/// The input is a sequence of rows, each holding one `sample_type` value for
+/// each of the `channels` channels; signals refer to channels by index. The
+/// rows are streamed as for [`import_csv`].
+///
+/// # Errors
+///
+/// Same as [`import_csv`]; the input must also end on a whole row.
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::{import_binary, ImportOptions, ImportSignal, RawSampleType};
+///
+/// // Two int16 channels: ADC counts and a trigger line
+/// let mut dump = Vec::new();
+/// for i in 0..512i16 {
+///     let trigger: i16 = if (100..110).contains(&i) { 5 } else { 0 };
+///     dump.extend_from_slice(&(i * 10).to_le_bytes());
+///     dump.extend_from_slice(&trigger.to_le_bytes());
+/// }
+/// std::fs::write("dump.bin", dump)?;
+///
+/// let options = ImportOptions::new(256.0)
+///     .signal(ImportSignal::new(0, "Pressure").unit("mmHg"))
+///     .marker_column(1);
+/// let summary = import_binary("dump.bin", "dump.edf", RawSampleType::Int16, 2, &options)?;
+/// assert_eq!((summary.records, summary.annotations), (2, 1));
+///
+/// # std::fs::remove_file("dump.bin").ok();
+/// # std::fs::remove_file("dump.edf").ok();
+/// # Ok::<(), edfplus::EdfError>(())
+/// ```
+pub fn import_binary<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    sample_type: RawSampleType,
+    channels: usize,
+    options: &ImportOptions,
+) -> Result<ImportSummary> {
+    if channels == 0 {
+        return Err(EdfError::InvalidFormat("Binary input must have at least one channel".to_string()));
+    }
+    import(input.as_ref(), output.as_ref(), Format::Binary(sample_type, channels), options)
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Csv,
+    Binary(RawSampleType, usize),
+}
+
+/// 逐行读取的输入：CSV 文本或交错存储的二进制样本
+enum Source {
+    Csv { input: BufReader<File>, delimiter: char, line: usize, text: String },
+    Binary { input: BufReader<File>, sample_type: RawSampleType, bytes: Vec<u8> },
+}
+
+/// 一行中所选列的值和标记文本
+#[derive(Default)]
+struct Row {
+    values: Vec<f64>,
+    marker: String,
+}
+
+/// 第一遍扫描的结果：行数、各信号的取值范围和标记
+struct Scan {
+    rows: u64,
+    ranges: Vec<Option<(f64, f64)>>,
+    markers: Vec<(u64, String)>,
+}
+
+fn import(input: &Path, output: &Path, format: Format, options: &ImportOptions) -> Result<ImportSummary> {
+    let rate = options.sampling_rate;
+    if !rate.is_finite() || rate <= 0.0 {
+        return Err(EdfError::InvalidFormat(format!("Sampling rate must be a positive number of Hz, got {}", rate)));
+    }
+    let duration = options.record_duration;
+    if !duration.is_finite() || duration <= 0.0 {
+        return Err(EdfError::InvalidFormat(format!("Data record duration must be positive, got {} s", duration)));
+    }
+    let samples_per_record = (rate * duration).round();
+    if samples_per_record < 1.0 || samples_per_record > i32::MAX as f64 || (rate * duration - samples_per_record).abs() > 1e-6 {
+        return Err(EdfError::InvalidFormat(format!(
+            "{} Hz does not give a whole number of samples in a {} s data record; choose another record duration",
+            rate, duration
+        )));
+    }
+    let samples_per_record = samples_per_record as usize;
+
+    // 确定输入的列，未指定信号时导入除标记列以外的所有列
+    let (columns, names) = match format {
+        Format::Csv => {
+            let fields = Source::csv(input, options.delimiter)?.next_fields()?;
+            let names = if options.header { fields.clone() } else { Vec::new() };
+            (fields.len(), names)
+        }
+        Format::Binary(_, channels) => (channels, Vec::new()),
+    };
+    let signals: Vec<ImportSignal> = if options.signals.is_empty() {
+        (0..columns)
+            .filter(|&column| Some(column) != options.marker_column)
+            .map(|column| ImportSignal::new(column, &default_label(names.get(column), column)))
+            .collect()
+    } else {
+        options.signals.clone()
+    };
+    if signals.is_empty() {
+        return Err(EdfError::InvalidFormat("No columns to import".to_string()));
+    }
+    for column in signals.iter().map(|s| s.column).chain(options.marker_column) {
+        if column >= columns {
+            return Err(EdfError::InvalidFormat(format!(
+                "Column {} does not exist; the input has {} columns", column, columns
+            )));
+        }
+    }
+    let selected: Vec<usize> = signals.iter().map(|s| s.column).collect();
+
+    // 自动范围和标记需要先扫描一遍输入
+    let scan = if options.marker_column.is_some() || signals.iter().any(|s| s.physical_range.is_none()) {
+        Some(scan(input, format, options, &selected)?)
+    } else {
+        None
+    };
+
+    let mut params = Vec::with_capacity(signals.len());
+    for (i, signal) in signals.iter().enumerate() {
+        let mut param = SignalParam {
+            label: signal.label.clone(),
+            samples_in_file: 0,
+            physical_max: 1.0,
+            physical_min: -1.0,
+            digital_max: i16::MAX as i32,
+            digital_min: i16::MIN as i32,
+            samples_per_record: samples_per_record as i32,
+            physical_dimension: signal.physical_dimension.clone(),
+            prefilter: signal.prefilter.clone(),
+            transducer: signal.transducer.clone(),
+        };
+        if let Some((min, max)) = signal.physical_range {
+            param.physical_min = min;
+            param.physical_max = max;
+        } else {
+            // 没有给出物理范围的信号总会先扫描；扫描缺失时也返回错误而不是 panic
+            let range = scan.as_ref().and_then(|scan| scan.ranges[i]);
+            let (min, max) = range.ok_or_else(|| EdfError::InvalidFormat(format!(
+                "Signal '{}' has no finite samples to derive a physical range from", signal.label
+            )))?;
+            param.fit_physical_range(&[min, max])?;
+        }
+        params.push(param);
+    }
+
+    let mut writer = EdfWriter::create(output)?;
+    for param in &params {
+        writer.add_signal(param.clone())?;
+    }
+    writer.set_datarecord_duration(duration)?;
+    if let Some(start) = options.start {
+        writer.set_start_datetime(start)?;
+    }
+    writer.set_sample_policy(options.sample_policy);
+    writer.set_padding_policy(options.padding);
+
+    // 注释必须在覆盖它们的数据记录写入之前添加；丢弃的不完整记录中的标记不写入
+    let mut annotations = 0;
+    if let Some(scan) = &scan {
+        let kept_rows = match options.padding {
+            PaddingPolicy::Discard => scan.rows / samples_per_record as u64 * samples_per_record as u64,
+            _ => scan.rows,
+        };
+        let markers: Vec<&(u64, String)> = scan.markers.iter().filter(|(row, _)| *row < kept_rows).collect();
+        if !markers.is_empty() {
+            let records = kept_rows.div_ceil(samples_per_record as u64) as i64;
+            let record_time = (duration * EDFLIB_TIME_DIMENSION as f64).round() as i64;
+            let record_ends: Vec<i64> = (1..=records).map(|r| r * record_time).collect();
+            let onsets = markers.iter().map(|(row, _)| row_time(*row, rate));
+            writer.set_number_of_annotation_signals(annotation_channels(&record_ends, onsets))?;
+        }
+        for (row, description) in &markers {
+            writer.add_annotation(*row as f64 / rate, None, description)?;
+        }
+        annotations = markers.len();
+    }
+
+    // 分块把各列样本交给写入器
+    let mut source = Source::open(input, format, options)?;
+    let mut row = Row::default();
+    let mut chunk = vec![Vec::with_capacity(CHUNK_ROWS); signals.len()];
+    let mut rows = 0u64;
+    loop {
+        let more = source.next_row(&selected, None, &mut row)?;
+        if more {
+            for (buffer, &value) in chunk.iter_mut().zip(&row.values) {
+                buffer.push(value);
+            }
+            rows += 1;
+        }
+        if chunk[0].len() >= CHUNK_ROWS || (!more && !chunk[0].is_empty()) {
+            for (signal, buffer) in chunk.iter_mut().enumerate() {
+                writer.push_samples(signal, buffer)?;
+                buffer.clear();
+            }
+        }
+        if !more {
+            break;
+        }
+    }
+
+    let stats = writer.finalize()?;
+    let records = (stats[0].samples / samples_per_record as u64) as i64;
+    for param in &mut params {
+        param.samples_in_file = stats[0].samples as i64;
+    }
+    Ok(ImportSummary { signals: params, rows, records, annotations, stats })
+}
+
+/// 第一遍：统计行数、所选列的有限值范围，并收集标记
+fn scan(input: &Path, format: Format, options: &ImportOptions, selected: &[usize]) -> Result<Scan> {
+    let mut source = Source::open(input, format, options)?;
+    let mut row = Row::default();
+    let mut result = Scan { rows: 0, ranges: vec![None; selected.len()], markers: Vec::new() };
+    let mut previous_marker = String::new();
+
+    while source.next_row(selected, options.marker_column, &mut row)? {
+        for (range, &value) in result.ranges.iter_mut().zip(&row.values) {
+            if value.is_finite() {
+                *range = Some(match *range {
+                    Some((min, max)) => (min.min(value), max.max(value)),
+                    None => (value, value),
+                });
+            }
+        }
+        if options.marker_column.is_some() {
+            if !row.marker.is_empty() && row.marker != "0" && row.marker != previous_marker {
+                result.markers.push((result.rows, row.marker.clone()));
+            }
+            std::mem::swap(&mut previous_marker, &mut row.marker);
+        }
+        result.rows += 1;
+    }
+    Ok(result)
+}
+
+impl Source {
+    /// 打开输入，跳过 CSV 表头
+    fn open(path: &Path, format: Format, options: &ImportOptions) -> Result<Source> {
+        match format {
+            Format::Csv => {
+                let mut source = Source::csv(path, options.delimiter)?;
+                if options.header {
+                    source.next_fields()?;
+                }
+                Ok(source)
+            }
+            Format::Binary(sample_type, channels) => Ok(Source::Binary {
+                input: BufReader::new(File::open(path)?),
+                sample_type,
+                bytes: vec![0; sample_type.size() * channels],
+            }),
+        }
+    }
+
+    fn csv(path: &Path, delimiter: char) -> Result<Source> {
+        Ok(Source::Csv { input: BufReader::new(File::open(path)?), delimiter, line: 0, text: String::new() })
+    }
+
+    /// 读取下一个非空 CSV 行并拆分为字段
+    fn next_fields(&mut self) -> Result<Vec<String>> {
+        match self {
+            Source::Csv { input, delimiter, line, text } => {
+                loop {
+                    text.clear();
+                    if input.read_line(text)? == 0 {
+                        return Ok(Vec::new());
+                    }
+                    *line += 1;
+                    let trimmed = text.trim_end_matches(['\r', '\n']);
+                    if !trimmed.trim().is_empty() {
+                        return Ok(split_fields(trimmed, *delimiter));
+                    }
+                }
+            }
+            Source::Binary { .. } => Ok(Vec::new()),
+        }
+    }
+
+    /// 读取下一行的所选列（以及标记列），输入结束时返回 false
+    fn next_row(&mut self, selected: &[usize], marker: Option<usize>, row: &mut Row) -> Result<bool> {
+        row.values.clear();
+        row.marker.clear();
+        if let Source::Binary { input, sample_type, bytes } = self {
+            let mut filled = 0;
+            while filled < bytes.len() {
+                match input.read(&mut bytes[filled..])? {
+                    0 => break,
+                    n => filled += n,
+                }
+            }
+            if filled == 0 {
+                return Ok(false);
+            }
+            if filled < bytes.len() {
+                return Err(EdfError::InvalidFormat(format!(
+                    "Binary input ends with an incomplete row of {} bytes", filled
+                )));
+            }
+            let size = sample_type.size();
+            let value = |column: usize| sample_type.decode(&bytes[column * size..]);
+            row.values.extend(selected.iter().map(|&column| value(column)));
+            if let Some(column) = marker {
+                let marker = value(column);
+                if marker != 0.0 {
+                    row.marker = marker.to_string();
+                }
+            }
+            return Ok(true);
+        }
+
+        let fields = self.next_fields()?;
+        if fields.is_empty() {
+            return Ok(false);
+        }
+        let line = match self {
+            Source::Csv { line, .. } => *line,
+            Source::Binary { .. } => unreachable!(),
+        };
+        let field = |column: usize| fields.get(column).map(|f| f.trim()).ok_or_else(|| {
+            EdfError::InvalidFormat(format!("Line {} has {} columns, column {} is missing", line, fields.len(), column))
+        });
+        for &column in selected {
+            let text = field(column)?;
+            let value = if text.is_empty() {
+                f64::NAN
+            } else {
+                text.parse::<f64>().map_err(|_| EdfError::InvalidFormat(format!(
+                    "Line {}, column {}: '{}' is not a number", line, column, text
+                )))?
+            };
+            row.values.push(value);
+        }
+        if let Some(column) = marker {
+            row.marker.push_str(field(column)?);
+        }
+        Ok(true)
+    }
+}
+
+/// 按分隔符拆分一行 CSV，支持双引号包围的字段和 "" 转义
+fn split_fields(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// 由表头生成标签：只保留可打印 ASCII，最多 16 个字符
+fn default_label(name: Option<&String>, column: usize) -> String {
+    let label: String = name
+        .map(|name| name.trim().chars().filter(|c| c.is_ascii() && !c.is_ascii_control()).take(16).collect())
+        .unwrap_or_default();
+    if label.trim().is_empty() {
+        format!("Ch{}", column + 1)
+    } else {
+        label.trim_end().to_string()
+    }
+}
+
+fn row_time(row: u64, rate: f64) -> i64 {
+    (row as f64 / rate * EDFLIB_TIME_DIMENSION as f64).round() as i64
+}
@@ -204,15 +204,44 @@ pub mod types;
 pub mod utils;
 pub mod reader;
 pub mod writer; // 新增
+pub mod epoch;
+pub mod segment;
+pub mod sleep;
+pub mod stats;
+pub mod crop;
+pub mod merge;
+pub mod select;
+pub mod montage;
+pub mod resample;
+pub mod filter;
+pub mod export;
+pub mod import;
 
 #[doc(hidden)]
 pub mod doctest_utils; // For internal doctest support
 
+#[doc(hidden)]
+pub mod fuzzing; // Entry points for fuzz targets
+
 // Re-export main types for convenience
 pub use error::{EdfError, Result};
-pub use types::{EdfHeader, SignalParam, Annotation};
-pub use reader::EdfReader;
-pub use writer::EdfWriter; // 新增
+pub use types::{EdfHeader, SignalParam, SignalParamBuilder, Quantization, Annotation};
+pub use reader::{DataRecord, DataRecords, EdfReader};
+pub use epoch::{Epoch, EpochOptions, Epochs, PartialEpoch};
+pub use segment::{EventSegments, Segment, SegmentBoundary, SegmentOptions, SkippedEvent};
+pub use stats::{FlatLine, SignalStatistics, StatsOptions};
+pub use crop::{AnnotationCrop, CropOptions, CropSummary};
+pub use merge::{concatenate, Gap, MergeSummary};
+pub use select::{SignalRef, SignalSelection};
+pub use montage::{Derivation, Montage, MontageReader};
+pub use resample::{ResampleMethod, ResampleOptions, Resampler};
+pub use filter::{FilterChain, FilterStage, SignalFilter};
+pub use export::{ExportDtype, ExportOptions, ExportSummary};
+pub use import::{import_binary, import_csv, ImportOptions, ImportSignal, ImportSummary, RawSampleType};
+pub use writer::{
+    ClipPolicy, EdfWriter, HeaderRounding, InvalidSamplePolicy, PaddingPolicy, SamplePolicy,
+    SampleStats,
+}; // 新增
 
 // Important constants
 pub const EDFLIB_TIME_DIMENSION: i64 = 10_000_000; // 100 nanoseconds unit
@@ -0,0 +1,204 @@
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDateTime;
+
+use crate::error::{EdfError, Result};
+use crate::crop::write_records;
+use crate::reader::EdfReader;
+use crate::types::EdfHeader;
+use crate::utils::CONTIGUOUS_TOLERANCE;
+use crate::writer::EdfWriter;
+use crate::EDFLIB_TIME_DIMENSION;
+
+/// A gap between two merged data records
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gap {
+    /// Start of the gap in seconds after the start of the merged recording
+    pub start_seconds: f64,
+    /// Length of the gap in seconds
+    pub duration_seconds: f64,
+}
+
+/// Result of [`concatenate`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeSummary {
+    /// The input files in start-time order, as written
+    pub files: Vec<PathBuf>,
+    /// Start date and time of the merged recording (the earliest input)
+    pub start: NaiveDateTime,
+    /// Number of data records written
+    pub records: i64,
+    /// Gaps between the inputs; the output is EDF+D if there are any
+    pub gaps: Vec<Gap>,
+    /// Number of annotations copied
+    pub annotations: usize,
+}
+
+/// Concatenates EDF+ files with the same signal layout into one recording
+///
+/// The inputs are sorted by start date and time and must have the same data
+/// record duration and the same signals (label, samples per record, physical
+/// and digital range, physical dimension). Their stored digital samples are
+/// copied unchanged, including values outside the digital range. When every file starts where the previous one ends (within
+/// 1 ms) the output is EDF+C; otherwise each gap is kept and the output is
+/// EDF+D, with the onset of every data record in its time-keeping TAL.
+/// Annotations of all inputs are copied with onsets relative to the start of
+/// the merged recording. The patient identification is taken from the
+/// earliest file.
+///
+/// # Errors
+///
+/// * `EdfError::InvalidFormat` - No inputs, the signal layouts or record
+///   durations differ, or two recordings overlap in time
+/// * `EdfError::InvalidAnnotation` - The annotations of an input cannot be
+///   parsed (see [`EdfReader::open_strict`])
+/// * `EdfError::Io` - I/O error reading an input or writing the output
+///
+/// # Examples
+///
+/// ```rust
+/// use chrono::NaiveDate;
+/// use edfplus::{concatenate, EdfReader, EdfWriter, SignalParam};
+///
+/// # for (name, hour) in [("hour_1.edf", 22), ("hour_2.edf", 23)] {
+/// #     let mut writer = EdfWriter::create(name)?;
+/// #     let start = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(hour, 0, 0).unwrap();
+/// #     writer.set_start_datetime(start)?;
+/// #     writer.set_datarecord_duration(60.0)?;
+/// #     writer.add_signal(SignalParam::new_eeg("EEG C3", 100))?;
+/// #     for _ in 0..60 { writer.write_samples(&[vec![0.0; 100]])?; }
+/// #     writer.finalize()?;
+/// # }
+/// // Hourly files from the amplifier, in any order
+/// let summary = concatenate(&["hour_2.edf", "hour_1.edf"], "night.edf")?;
+/// assert!(summary.gaps.is_empty());
+///
+/// let night = EdfReader::open("night.edf")?;
+/// assert!(!night.header().discontinuous);
+/// assert_eq!(night.header().datarecords_in_file, 120);
+///
+/// # for name in ["hour_1.edf", "hour_2.edf", "night.edf"] { std::fs::remove_file(name).ok(); }
+/// # Ok::<(), edfplus::EdfError>(())
+/// ```
+pub fn concatenate<P: AsRef<Path>, Q: AsRef<Path>>(inputs: &[P], output: Q) -> Result<MergeSummary> {
+    // 注释无效的输入会丢失间隙和注释，因此严格打开
+    let mut readers = inputs.iter()
+        .map(|path| Ok((path.as_ref().to_path_buf(), EdfReader::open_strict(path)?)))
+        .collect::<Result<Vec<_>>>()?;
+    if readers.is_empty() {
+        return Err(EdfError::InvalidFormat("No files to concatenate".to_string()));
+    }
+    readers.sort_by_key(|(_, reader)| reader.header().start_datetime());
+
+    let (first_path, first) = &readers[0];
+    let start = first.header().start_datetime();
+    let record_duration = first.header().datarecord_duration;
+    for (path, reader) in &readers[1..] {
+        if let Some(reason) = layout_mismatch(first.header(), reader.header()) {
+            return Err(EdfError::InvalidFormat(format!(
+                "Cannot concatenate {} and {}: {}", first_path.display(), path.display(), reason
+            )));
+        }
+    }
+
+    // 每个输入文件相对于合并后开始时间的偏移，并确认记录不重叠
+    let mut offsets = Vec::with_capacity(readers.len());
+    let mut gaps = Vec::new();
+    let mut expected = 0i64;
+    for (path, reader) in &readers {
+        let offset = (reader.header().start_datetime() - start).num_nanoseconds()
+            .map(|nanos| nanos / 100)
+            .ok_or_else(|| EdfError::InvalidFormat(format!("{} starts too late to merge", path.display())))?;
+        for &onset in reader.record_onsets() {
+            let onset = offset + onset;
+            if onset < expected - CONTIGUOUS_TOLERANCE {
+                return Err(EdfError::InvalidFormat(format!(
+                    "{} overlaps the previous recording by {} s",
+                    path.display(), (expected - onset) as f64 / EDFLIB_TIME_DIMENSION as f64
+                )));
+            }
+            if onset > expected + CONTIGUOUS_TOLERANCE {
+                gaps.push(Gap {
+                    start_seconds: expected as f64 / EDFLIB_TIME_DIMENSION as f64,
+                    duration_seconds: (onset - expected) as f64 / EDFLIB_TIME_DIMENSION as f64,
+                });
+                expected = onset;
+            }
+            expected += record_duration;
+        }
+        offsets.push(offset);
+    }
+
+    // 按合并后的时间轴收集注释（空描述无法写入，跳过）
+    let mut annotations = Vec::new();
+    for ((_, reader), &offset) in readers.iter().zip(&offsets) {
+        for annotation in reader.annotations().iter().filter(|a| !a.description.is_empty()) {
+            annotations.push((offset + annotation.onset, annotation.duration, annotation.description.as_str()));
+        }
+    }
+    annotations.sort_by_key(|&(onset, _, _)| onset);
+
+    let header = first.header();
+    let mut writer = EdfWriter::create(output)?;
+    writer.set_patient_info(&header.patient_code, &header.sex, &header.birthdate, &header.patient_name)?;
+    writer.set_start_datetime(start)?;
+    writer.set_datarecord_duration(record_duration as f64 / EDFLIB_TIME_DIMENSION as f64)?;
+    for signal in &header.signals {
+        writer.add_signal(signal.clone())?;
+    }
+    // 合并后的每个记录：（输入文件，记录序号）及其开始时间
+    let (sources, onsets): (Vec<(usize, i64)>, Vec<i64>) = readers.iter().zip(&offsets).enumerate()
+        .flat_map(|(file, ((_, reader), &offset))| {
+            reader.record_onsets().iter().enumerate()
+                .map(move |(record, &onset)| ((file, record as i64), offset + onset))
+        })
+        .unzip();
+    let all_signals: Vec<usize> = (0..header.signals.len()).collect();
+    let mut data = Vec::new();
+    write_records(writer, record_duration, &onsets, &annotations, |writer, i| {
+        let (file, record) = sources[i];
+        data.clear();
+        readers[file].1.record_bytes(record, &all_signals, &mut data)?;
+        writer.write_raw_record(&data)
+    })?;
+
+    let annotations = annotations.len();
+    Ok(MergeSummary {
+        files: readers.into_iter().map(|(path, _)| path).collect(),
+        start,
+        records: sources.len() as i64,
+        gaps,
+        annotations,
+    })
+}
+
+/// Why two files cannot be concatenated, if they cannot
+fn layout_mismatch(first: &EdfHeader, other: &EdfHeader) -> Option<String> {
+    if first.datarecord_duration != other.datarecord_duration {
+        return Some(format!(
+            "data record durations differ ({} s and {} s)",
+            first.datarecord_duration as f64 / EDFLIB_TIME_DIMENSION as f64,
+            other.datarecord_duration as f64 / EDFLIB_TIME_DIMENSION as f64
+        ));
+    }
+    if first.signals.len() != other.signals.len() {
+        return Some(format!("signal counts differ ({} and {})", first.signals.len(), other.signals.len()));
+    }
+    for (i, (a, b)) in first.signals.iter().zip(&other.signals).enumerate() {
+        let field = if a.label != b.label {
+            "label"
+        } else if a.samples_per_record != b.samples_per_record {
+            "samples per record"
+        } else if a.physical_min != b.physical_min || a.physical_max != b.physical_max {
+            "physical range"
+        } else if a.digital_min != b.digital_min || a.digital_max != b.digital_max {
+            "digital range"
+        } else if a.physical_dimension != b.physical_dimension {
+            "physical dimension"
+        } else {
+            continue;
+        };
+        return Some(format!("signal {} ('{}' and '{}') has a different {}", i, a.label, b.label, field));
+    }
+    None
+}
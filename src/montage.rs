@@ -0,0 +1,341 @@
+use std::path::Path;
+
+use crate::crop::write_records;
+use crate::error::{EdfError, Result};
+use crate::reader::EdfReader;
+use crate::types::SignalParam;
+use crate::writer::EdfWriter;
+use crate::EDFLIB_TIME_DIMENSION;
+
+/// A derived signal: a weighted sum of source signals
+#[derive(Debug, Clone, PartialEq)]
+pub struct Derivation {
+    /// Label of the derived signal
+    pub label: String,
+    /// Source signal labels and their weights
+    pub terms: Vec<(String, f64)>,
+}
+
+/// A set of derived signals computed from the signals of a file
+///
+/// Every derivation is a linear combination of source signals, referenced
+/// by label. Sources combined in one derivation must have the same number
+/// of samples per data record and the same physical dimension.
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::Montage;
+///
+/// // Left temporal chain of the double banana
+/// let bipolar = Montage::new()
+///     .bipolar("Fp1-F7", "EEG Fp1", "EEG F7")
+///     .bipolar("F7-T3", "EEG F7", "EEG T3")
+///     .bipolar("T3-T5", "EEG T3", "EEG T5");
+///
+/// // Common average and linked-mastoid references
+/// let average = Montage::new().common_average(&["EEG C3", "EEG C4", "EEG O1", "EEG O2"]);
+/// let mastoids = Montage::new().linked_mastoids(&["EEG C3", "EEG C4"], "EEG A1", "EEG A2");
+///
+/// // Any other linear combination
+/// let laplacian = Montage::new().derived("C3-Lap", &[
+///     ("EEG C3", 1.0), ("EEG F3", -0.25), ("EEG P3", -0.25), ("EEG T3", -0.25), ("EEG Cz", -0.25),
+/// ]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Montage {
+    derivations: Vec<Derivation>,
+}
+
+impl Montage {
+    /// An empty montage
+    pub fn new() -> Self {
+        Montage::default()
+    }
+
+    /// Adds a derived signal with arbitrary weights
+    pub fn derived(mut self, label: &str, terms: &[(&str, f64)]) -> Self {
+        self.derivations.push(Derivation {
+            label: label.to_string(),
+            terms: terms.iter().map(|&(source, weight)| (source.to_string(), weight)).collect(),
+        });
+        self
+    }
+
+    /// Adds `active - reference`
+    pub fn bipolar(self, label: &str, active: &str, reference: &str) -> Self {
+        self.derived(label, &[(active, 1.0), (reference, -1.0)])
+    }
+
+    /// Adds each source minus the mean of all sources, labelled `"<source>-AVG"`
+    pub fn common_average(mut self, sources: &[&str]) -> Self {
+        let weight = 1.0 / sources.len() as f64;
+        for &source in sources {
+            let terms = sources.iter()
+                .map(|&other| (other.to_string(), if other == source { 1.0 - weight } else { -weight }))
+                .collect();
+            self.derivations.push(Derivation { label: format!("{}-AVG", source), terms });
+        }
+        self
+    }
+
+    /// Adds each source minus the mean of two mastoid (or ear) electrodes,
+    /// labelled `"<source>-LM"`
+    pub fn linked_mastoids(mut self, sources: &[&str], left: &str, right: &str) -> Self {
+        for &source in sources {
+            self = self.derived(&format!("{}-LM", source), &[(source, 1.0), (left, -0.5), (right, -0.5)]);
+        }
+        self
+    }
+
+    /// The derivations, in output order
+    pub fn derivations(&self) -> &[Derivation] {
+        &self.derivations
+    }
+}
+
+/// Derived signals of an open file, read like ordinary signals
+///
+/// Created by [`EdfReader::montage`]. Signal indices refer to the
+/// derivations of the montage. The read methods mirror those of
+/// [`EdfReader`]: positional reads take `&self`, cursor-based reads keep one
+/// position per derived signal, independent of the reader's own positions.
+pub struct MontageReader<'a> {
+    reader: &'a EdfReader,
+    signals: Vec<SignalParam>,
+    terms: Vec<Vec<(usize, f64)>>,
+    sample_positions: Vec<i64>,
+}
+
+impl EdfReader {
+    /// Resolves a montage against the signals of this file
+    ///
+    /// Each derived signal gets the samples per record, physical dimension
+    /// and prefilter of its sources, and a physical range that holds every
+    /// value the sources can take. Source labels match the trimmed signal
+    /// label exactly; the first matching signal is used.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - The montage or a derivation is empty, a
+    ///   source label does not exist, the sources of a derivation differ in
+    ///   samples per record or physical dimension, or a derived label is not a
+    ///   valid EDF label
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{EdfReader, EdfWriter, Montage, SignalParam};
+    ///
+    /// # let mut writer = EdfWriter::create("routine.edf")?;
+    /// # writer.add_signal(SignalParam::new_eeg("EEG Fp1", 256))?;
+    /// # writer.add_signal(SignalParam::new_eeg("EEG Fp2", 256))?;
+    /// # let fp1: Vec<f64> = (0..256).map(|i| (i as f64 * 0.1).sin() * 50.0).collect();
+    /// # let fp2: Vec<f64> = (0..256).map(|i| (i as f64 * 0.3).cos() * 20.0).collect();
+    /// # writer.write_samples(&[fp1, fp2])?;
+    /// # writer.finalize()?;
+    /// let reader = EdfReader::open("routine.edf")?;
+    /// let montage = Montage::new().bipolar("Fp1-Fp2", "EEG Fp1", "EEG Fp2");
+    /// let mut derived = reader.montage(&montage)?;
+    ///
+    /// let fp1 = reader.read_physical_at(0, 0, 256)?;
+    /// let fp2 = reader.read_physical_at(1, 0, 256)?;
+    /// let fp1_fp2 = derived.read_physical_samples(0, 256)?;
+    /// assert!((fp1_fp2[10] - (fp1[10] - fp2[10])).abs() < 1e-9);
+    ///
+    /// // The derived signals can also be written to a new file
+    /// derived.write("routine_bipolar.edf")?;
+    ///
+    /// # std::fs::remove_file("routine.edf").ok();
+    /// # std::fs::remove_file("routine_bipolar.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn montage(&self, montage: &Montage) -> Result<MontageReader<'_>> {
+        if montage.derivations.is_empty() {
+            return Err(EdfError::InvalidFormat("Montage has no derivations".to_string()));
+        }
+        let sources = &self.header().signals;
+
+        let mut signals = Vec::with_capacity(montage.derivations.len());
+        let mut terms = Vec::with_capacity(montage.derivations.len());
+        for derivation in &montage.derivations {
+            let resolved = derivation.terms.iter()
+                .map(|(label, weight)| {
+                    let index = sources.iter().position(|s| s.label == *label)
+                        .ok_or_else(|| EdfError::InvalidFormat(format!("No signal labelled '{}'", label)))?;
+                    Ok((index, *weight))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let Some(&(first, _)) = resolved.first() else {
+                return Err(EdfError::InvalidFormat(format!("Derivation '{}' has no source signals", derivation.label)));
+            };
+
+            let first = &sources[first];
+            let mut param = SignalParam {
+                label: derivation.label.clone(),
+                samples_in_file: first.samples_in_file,
+                physical_max: 0.0,
+                physical_min: 0.0,
+                digital_max: i16::MAX as i32,
+                digital_min: i16::MIN as i32,
+                samples_per_record: first.samples_per_record,
+                physical_dimension: first.physical_dimension.clone(),
+                prefilter: first.prefilter.clone(),
+                transducer: String::new(),
+            };
+            for &(index, weight) in &resolved {
+                let source = &sources[index];
+                if source.samples_per_record != first.samples_per_record {
+                    return Err(EdfError::InvalidFormat(format!(
+                        "Derivation '{}' mixes sample rates ('{}' and '{}')", derivation.label, first.label, source.label
+                    )));
+                }
+                if source.physical_dimension != first.physical_dimension {
+                    return Err(EdfError::InvalidFormat(format!(
+                        "Derivation '{}' mixes physical dimensions ('{}' and '{}')",
+                        derivation.label, first.physical_dimension, source.physical_dimension
+                    )));
+                }
+                if source.prefilter != param.prefilter {
+                    param.prefilter.clear();
+                }
+                // 每一项的取值区间相加，得到派生信号可能的取值范围
+                let (low, high) = (weight * source.physical_min, weight * source.physical_max);
+                param.physical_min += low.min(high);
+                param.physical_max += low.max(high);
+            }
+            param.validate()?;
+            signals.push(param);
+            terms.push(resolved);
+        }
+
+        Ok(MontageReader { reader: self, sample_positions: vec![0; signals.len()], signals, terms })
+    }
+}
+
+impl MontageReader<'_> {
+    /// Parameters of the derived signals
+    pub fn signals(&self) -> &[SignalParam] {
+        &self.signals
+    }
+
+    /// Reads physical values of a derived signal starting at a sample position
+    ///
+    /// See [`EdfReader::read_physical_at`].
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidSignalIndex` - Signal index is out of bounds
+    /// * `EdfError::InvalidFormat` - `start` is negative
+    /// * `EdfError::Io` - I/O error reading from file
+    pub fn read_physical_at(&self, signal: usize, start: i64, count: usize) -> Result<Vec<f64>> {
+        let mut samples = vec![0.0; count.min(self.samples_from(signal, start)?)];
+        self.read_physical_at_into(signal, start, &mut samples)?;
+        Ok(samples)
+    }
+
+    /// Reads physical values of a derived signal at a sample position into a
+    /// caller-provided buffer
+    ///
+    /// Returns the number of samples written to `buf`.
+    pub fn read_physical_at_into(&self, signal: usize, start: i64, buf: &mut [f64]) -> Result<usize> {
+        let count = buf.len().min(self.samples_from(signal, start)?);
+        let buf = &mut buf[..count];
+        buf.fill(0.0);
+        let mut source = vec![0.0; count];
+        for &(index, weight) in &self.terms[signal] {
+            self.reader.read_physical_at_into(index, start, &mut source)?;
+            for (value, &sample) in buf.iter_mut().zip(&source) {
+                *value += weight * sample;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Reads physical values of a derived signal from its current position
+    ///
+    /// See [`EdfReader::read_physical_samples`].
+    pub fn read_physical_samples(&mut self, signal: usize, count: usize) -> Result<Vec<f64>> {
+        let mut samples = vec![0.0; count.min(self.samples_from(signal, self.tell(signal)?)?)];
+        self.read_physical_into(signal, &mut samples)?;
+        Ok(samples)
+    }
+
+    /// Reads physical values of a derived signal from its current position
+    /// into a caller-provided buffer
+    ///
+    /// Returns the number of samples written to `buf`; less than `buf.len()`
+    /// only at the end of the signal.
+    pub fn read_physical_into(&mut self, signal: usize, buf: &mut [f64]) -> Result<usize> {
+        let count = self.read_physical_at_into(signal, self.tell(signal)?, buf)?;
+        self.sample_positions[signal] += count as i64;
+        Ok(count)
+    }
+
+    /// Sets the position of a derived signal, clamped to the signal
+    ///
+    /// Returns the new position. See [`EdfReader::seek`].
+    pub fn seek(&mut self, signal: usize, position: i64) -> Result<i64> {
+        let samples_in_file = self.samples_from(signal, 0)? as i64;
+        self.sample_positions[signal] = position.clamp(0, samples_in_file);
+        Ok(self.sample_positions[signal])
+    }
+
+    /// Gets the current sample position of a derived signal
+    pub fn tell(&self, signal: usize) -> Result<i64> {
+        self.sample_positions.get(signal).copied().ok_or(EdfError::InvalidSignalIndex(signal))
+    }
+
+    /// Moves a derived signal back to its first sample
+    pub fn rewind(&mut self, signal: usize) -> Result<()> {
+        self.seek(signal, 0)?;
+        Ok(())
+    }
+
+    /// Writes the derived signals into a new EDF+ file
+    ///
+    /// The derived physical values are quantized with the range reported by
+    /// [`signals`](Self::signals). The start time, patient identification,
+    /// data record timing (including the gaps of an EDF+D file) and all
+    /// annotations are copied from the source.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::Io` - I/O error reading the source or writing the new file
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let header = self.reader.header();
+        let record_duration = header.datarecord_duration;
+        let mut writer = EdfWriter::create(path)?;
+        writer.set_patient_info(&header.patient_code, &header.sex, &header.birthdate, &header.patient_name)?;
+        writer.set_start_datetime(header.start_datetime())?;
+        writer.set_datarecord_duration(record_duration as f64 / EDFLIB_TIME_DIMENSION as f64)?;
+        for signal in &self.signals {
+            writer.add_signal(signal.clone())?;
+        }
+
+        // 空描述无法写入，跳过
+        let annotations: Vec<_> = self.reader.annotations().iter()
+            .filter(|a| !a.description.is_empty())
+            .map(|a| (a.onset, a.duration, a.description.as_str()))
+            .collect();
+        write_records(writer, record_duration, self.reader.record_onsets(), &annotations, |writer, record| {
+            let samples = self.signals.iter().enumerate()
+                .map(|(signal, param)| {
+                    let samples_per_record = param.samples_per_record as i64;
+                    self.read_physical_at(signal, record as i64 * samples_per_record, samples_per_record as usize)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            writer.write_samples(&samples)
+        })
+    }
+
+    /// 从 start 开始到派生信号结尾的样本数
+    fn samples_from(&self, signal: usize, start: i64) -> Result<usize> {
+        let param = self.signals.get(signal).ok_or(EdfError::InvalidSignalIndex(signal))?;
+        if start < 0 {
+            return Err(EdfError::InvalidFormat(format!("Negative sample position {}", start)));
+        }
+        let samples_in_file = param.samples_per_record as i64 * self.reader.header().datarecords_in_file;
+        Ok((samples_in_file - start).max(0) as usize)
+    }
+}
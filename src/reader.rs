@@ -3,11 +3,17 @@ use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
 use chrono::{NaiveDate, NaiveTime};
 
+use crate::filter::{FilterChain, SignalFilter};
 use crate::types::{EdfHeader, SignalParam, Annotation};
 use crate::error::{EdfError, Result};
-use crate::utils::{atoi_nonlocalized, atof_nonlocalized, parse_edf_time};
+use crate::utils::{
+    atoi_nonlocalized, atof_nonlocalized, parse_edf_time, seconds_to_time, time_to_sample, CONTIGUOUS_TOLERANCE,
+};
 use crate::EDFLIB_TIME_DIMENSION;
 
+/// 单个数据记录的最大字节数（与 edflib 相同）
+const MAX_RECORD_SIZE: usize = 10 * 1024 * 1024;
+
 /// TAL parsing state machine states
 #[derive(Debug, Clone, PartialEq)]
 enum TalState {
@@ -22,6 +28,11 @@ enum TalState {
 /// The `EdfReader` provides methods to open and read EDF+ files, which are
 /// commonly used for storing biosignal recordings like EEG, ECG, EMG, etc.
 /// 
+/// The cursor-based methods (`read_physical_samples`, `seek`, ...) take
+/// `&mut self`. The positional methods ([`read_physical_at`](Self::read_physical_at)
+/// and friends) take `&self`, and `EdfReader` is `Sync`, so one open reader can
+/// be shared between threads (e.g. behind an `Arc`) to decode signals in parallel.
+/// 
 /// # Examples
 /// 
 /// ## Basic usage
@@ -96,10 +107,87 @@ pub struct EdfReader {
     record_size: usize,
     /// 注释列表
     annotations: Vec<Annotation>,
+    /// 每个数据记录的开始时间（相对于第一个样本）
+    record_onsets: Vec<i64>,
+    /// 读取原始字节的复用缓冲区
+    scratch: Vec<u8>,
+    /// 是否将数字值限制在 digital_min..=digital_max
+    pub(crate) clamp_digital: bool,
+    /// 游标读取物理值时应用的滤波器（含跨块的滤波状态）
+    filters: Vec<Option<SignalFilter>>,
+    /// 打开文件时解析注释遇到的错误（宽松打开时保留）
+    annotation_error: Option<EdfError>,
+}
+
+/// One data record read by [`EdfReader::records`]
+#[derive(Debug, Clone)]
+pub struct DataRecord {
+    /// Zero-based index of the record in the file
+    pub index: usize,
+    /// Record start in 100-nanosecond units since the recording start, taken
+    /// from the time-keeping TAL (falls back to `index * datarecord_duration`)
+    pub onset: i64,
+    /// Annotations of each annotation channel in this record, excluding the
+    /// time-keeping TAL
+    pub annotations: Vec<Vec<Annotation>>,
+    /// 所有普通信号的数字值，按信号顺序连续存放
+    samples: Vec<i32>,
+    /// 每个信号在 samples 中的起始位置（最后一项为总长度）
+    offsets: Vec<usize>,
+}
+
+impl DataRecord {
+    /// Number of ordinary (non-annotation) signals in the record
+    pub fn signal_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+    
+    /// Digital samples of an ordinary signal in this record
+    /// 
+    /// Convert with [`SignalParam::to_physical`] from the reader's header.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `signal >= signal_count()`.
+    pub fn signal(&self, signal: usize) -> &[i32] {
+        &self.samples[self.offsets[signal]..self.offsets[signal + 1]]
+    }
+    
+    /// Iterates over the digital samples of all ordinary signals
+    pub fn signals(&self) -> impl Iterator<Item = &[i32]> + '_ {
+        (0..self.signal_count()).map(move |signal| self.signal(signal))
+    }
+}
+
+/// Iterator over the data records of an EDF+ file, see [`EdfReader::records`]
+pub struct DataRecords<'a> {
+    reader: &'a mut EdfReader,
+    next: usize,
+    end: usize,
+    buffer: Vec<u8>,
+}
+
+impl Iterator for DataRecords<'_> {
+    type Item = Result<DataRecord>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.end {
+            return None;
+        }
+        let result = self.reader.read_record(self.next, &mut self.buffer);
+        // 出错后停止迭代
+        self.next = if result.is_ok() { self.next + 1 } else { self.end };
+        Some(result)
+    }
+    
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.next;
+        (0, Some(remaining))
+    }
 }
 
 #[derive(Debug, Clone)]
-struct SignalInfo {
+pub(crate) struct SignalInfo {
     /// 信号在数据记录中的字节偏移
     buffer_offset: usize,
     /// 每个数据记录中的样本数
@@ -128,8 +216,14 @@ impl EdfReader {
     /// 
     /// * `EdfError::FileNotFound` - File doesn't exist or can't be opened
     /// * `EdfError::UnsupportedFileType` - File is not EDF+ format
-    /// * `EdfError::InvalidHeader` - File header is corrupted or invalid
-    /// * `EdfError::InvalidSignalCount` - Invalid number of signals
+    /// * `EdfError::InvalidHeader` - A header field is invalid, or the number
+    ///   of data records does not match the file size; the error names the
+    ///   field, its byte range, the signal and the raw text
+    /// 
+    /// Problems in the annotation signals do not make opening fail: the file
+    /// is opened without annotations and the problem is available from
+    /// [`annotation_error`](Self::annotation_error). Use
+    /// [`open_strict`](Self::open_strict) to treat them as errors.
     /// 
     /// # Examples
     /// 
@@ -163,6 +257,53 @@ impl EdfReader {
     /// # Ok::<(), edfplus::EdfError>(())
     /// ```
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_with(path, false)
+    }
+    
+    /// Opens an EDF+ file and fails on invalid annotations
+    /// 
+    /// Like [`open`](Self::open), but a malformed TAL or an out-of-sequence
+    /// time-keeping annotation is returned as an error instead of being
+    /// recorded in [`annotation_error`](Self::annotation_error).
+    /// 
+    /// # Errors
+    /// 
+    /// Same as [`open`](Self::open), plus:
+    /// 
+    /// * `EdfError::InvalidAnnotation` - A TAL is malformed (invalid onset or
+    ///   duration, misplaced separator, missing terminator), or a time-keeping
+    ///   annotation is out of sequence (a gap in an EDF+C file, or overlapping
+    ///   records in EDF+D); the error holds the data record and the byte
+    ///   offset of the TAL in the file
+    /// * `EdfError::Io` - I/O error reading the annotation signals
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::EdfReader;
+    /// 
+    /// # edfplus::doctest_utils::create_simple_test_file("strict.edf")?;
+    /// let reader = EdfReader::open_strict("strict.edf")?;
+    /// assert!(reader.annotation_error().is_none());
+    /// 
+    /// # std::fs::remove_file("strict.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn open_strict<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_with(path, true)
+    }
+    
+    /// Problem found in the annotation signals while opening the file
+    /// 
+    /// When this is `Some`, [`open`](Self::open) skipped the annotations:
+    /// [`annotations`](Self::annotations) is empty and data records are
+    /// assumed to be contiguous.
+    pub fn annotation_error(&self) -> Option<&EdfError> {
+        self.annotation_error.as_ref()
+    }
+    
+    /// 打开文件；strict 为 true 时注释错误导致打开失败
+    fn open_with<P: AsRef<Path>>(path: P, strict: bool) -> Result<Self> {
         let file = File::open(&path)
             .map_err(|e| EdfError::FileNotFound(format!("{}: {}", path.as_ref().display(), e)))?;
         
@@ -177,6 +318,7 @@ impl EdfReader {
         
         // 初始化样本位置指针
         let sample_positions = vec![0i64; header.signals.len()];
+        let filters = vec![None; header.signals.len()];
         
         // 解析注释以获取准确的注释数量和可能的subsecond时间
         let (annotations_count, starttime_subsecond) = Self::count_annotations_and_parse_subsecond(
@@ -200,11 +342,26 @@ impl EdfReader {
             header_size,
             record_size,
             annotations: Vec::new(),
+            record_onsets: Vec::new(),
+            scratch: Vec::new(),
+            clamp_digital: true,
+            filters,
+            annotation_error: None,
         };
         
-        // 解析注释数据
-        let annotations = temp_reader.parse_annotations().unwrap_or_else(|_| Vec::new());
-        temp_reader.annotations = annotations;
+        // 解析注释数据；宽松模式下出错时不使用注释，只保留错误
+        match temp_reader.parse_annotations() {
+            Ok(annotations) => temp_reader.annotations = annotations,
+            Err(error) if strict => return Err(error),
+            Err(error) => {
+                temp_reader.record_onsets.clear();
+                temp_reader.annotation_error = Some(error);
+            }
+        }
+        if temp_reader.record_onsets.is_empty() {
+            let duration = temp_reader.header.datarecord_duration;
+            temp_reader.record_onsets = (0..temp_reader.header.datarecords_in_file).map(|i| i * duration).collect();
+        }
         
         Ok(temp_reader)
     }
@@ -308,6 +465,154 @@ impl EdfReader {
         &self.annotations
     }
     
+    /// Start of each data record in 100-nanosecond units after the first sample
+    /// 
+    /// Taken from the time-keeping TALs. In a continuous (EDF+C) file this is
+    /// `index * datarecord_duration`; in a discontinuous (EDF+D) file, see
+    /// [`EdfHeader::discontinuous`], records may be separated by gaps.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::EdfReader;
+    /// 
+    /// # edfplus::doctest_utils::create_simple_test_file("onsets.edf")?;
+    /// let reader = EdfReader::open("onsets.edf")?;
+    /// let header = reader.header();
+    /// for pair in reader.record_onsets().windows(2) {
+    ///     let gap = pair[1] - pair[0] - header.datarecord_duration;
+    ///     if gap > 0 {
+    ///         println!("{} s gap at {} s", gap as f64 / 1e7, pair[1] as f64 / 1e7);
+    ///     }
+    /// }
+    /// 
+    /// # std::fs::remove_file("onsets.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn record_onsets(&self) -> &[i64] {
+        &self.record_onsets
+    }
+    
+    /// Index of the sample nearest to a time in seconds since the first sample
+    /// 
+    /// Like [`EdfHeader::seconds_to_sample`], but also handles discontinuous
+    /// (EDF+D) files: the time is located in the data record that covers it,
+    /// using [`record_onsets`](Self::record_onsets). A time in a gap between
+    /// records maps to the first sample after the gap.
+    /// 
+    /// # Errors
+    /// 
+    /// * `EdfError::InvalidSignalIndex` - Signal index is out of bounds
+    /// * `EdfError::InvalidFormat` - The time is not finite, or the signal has no sampling rate
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::EdfReader;
+    /// 
+    /// # edfplus::doctest_utils::create_simple_test_file("locate.edf")?;
+    /// let reader = EdfReader::open("locate.edf")?;
+    /// let sample = reader.seconds_to_sample(0, 2.5)?;
+    /// assert_eq!(reader.sample_times(0, sample, 1)?, vec![2.5]);
+    /// 
+    /// # std::fs::remove_file("locate.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn seconds_to_sample(&self, signal: usize, seconds: f64) -> Result<i64> {
+        if !self.header.discontinuous {
+            return self.header.seconds_to_sample(signal, seconds);
+        }
+        self.header.sampling_frequency(signal)?;
+        Ok(self.time_to_sample(signal, seconds_to_time(seconds)?))
+    }
+    
+    /// Times in seconds since the first sample of `count` consecutive samples
+    /// starting at `start`
+    /// 
+    /// Like [`EdfHeader::sample_times`], but takes the gaps between the data
+    /// records of a discontinuous (EDF+D) file into account.
+    pub fn sample_times(&self, signal: usize, start: i64, count: usize) -> Result<Vec<f64>> {
+        if !self.header.discontinuous {
+            return self.header.sample_times(signal, start, count);
+        }
+        self.header.sampling_frequency(signal)?;
+        let samples_per_record = self.header.signals[signal].samples_per_record as i64;
+        let record_seconds = self.header.datarecord_duration as f64 / EDFLIB_TIME_DIMENSION as f64;
+        Ok((start..start + count as i64)
+            .map(|sample| {
+                let onset = self.record_onset(sample.div_euclid(samples_per_record));
+                onset as f64 / EDFLIB_TIME_DIMENSION as f64
+                    + sample.rem_euclid(samples_per_record) as f64 * record_seconds / samples_per_record as f64
+            })
+            .collect())
+    }
+    
+    /// 数据记录的开始时间，文件之外的记录按记录时长外推
+    fn record_onset(&self, record: i64) -> i64 {
+        let duration = self.header.datarecord_duration;
+        match (self.record_onsets.first(), self.record_onsets.last()) {
+            (Some(&first), Some(&last)) => match usize::try_from(record).ok().and_then(|r| self.record_onsets.get(r)) {
+                Some(&onset) => onset,
+                None if record < 0 => first.saturating_add(record.saturating_mul(duration)),
+                None => last.saturating_add((record - self.record_onsets.len() as i64 + 1).saturating_mul(duration)),
+            },
+            _ => record.saturating_mul(duration),
+        }
+    }
+    
+    /// 开始时间不晚于 time 的最后一个数据记录
+    fn record_before(&self, time: i64) -> Option<usize> {
+        self.record_onsets.partition_point(|&onset| onset <= time).checked_sub(1)
+    }
+    
+    /// 时间（100纳秒单位）对应的样本序号（四舍五入），按记录开始时间定位；
+    /// 间隙中的时间对应间隙之后的第一个样本
+    pub(crate) fn time_to_sample(&self, signal: usize, time: i64) -> i64 {
+        let samples_per_record = self.header.signals[signal].samples_per_record as i64;
+        let duration = self.header.datarecord_duration;
+        if !self.header.discontinuous {
+            return time_to_sample(time, samples_per_record, duration, true);
+        }
+        match self.record_before(time) {
+            Some(record) if time - self.record_onsets[record] >= duration => (record as i64 + 1) * samples_per_record,
+            Some(record) => {
+                let offset = time - self.record_onsets[record];
+                record as i64 * samples_per_record + time_to_sample(offset, samples_per_record, duration, true)
+            }
+            None => time_to_sample(time - self.record_onset(0), samples_per_record, duration, true),
+        }
+    }
+    
+    /// 最后一个数据记录的结束时间（100纳秒单位）
+    pub(crate) fn recording_end(&self) -> i64 {
+        let records = self.header.datarecords_in_file.max(0);
+        if !self.header.discontinuous || records == 0 {
+            return records * self.header.datarecord_duration;
+        }
+        self.record_onset(records - 1).saturating_add(self.header.datarecord_duration)
+    }
+    
+    /// 时间窗 [start, end) 是否完全位于数据记录中，且不跨越记录之间的间隙
+    pub(crate) fn is_recorded(&self, start: i64, end: i64) -> bool {
+        let duration = self.header.datarecord_duration;
+        let Some(mut record) = self.record_before(start) else {
+            return false;
+        };
+        if start >= self.record_onsets[record] + duration {
+            return false;
+        }
+        loop {
+            let record_end = self.record_onsets[record] + duration;
+            if end <= record_end {
+                return true;
+            }
+            match self.record_onsets.get(record + 1) {
+                Some(&next) if next <= record_end + CONTIGUOUS_TOLERANCE => record += 1,
+                _ => return false,
+            }
+        }
+    }
+    
     /// Reads physical value samples from the specified signal
     /// 
     /// Physical values are the real-world measurements (e.g., microvolts for EEG,
@@ -394,19 +699,10 @@ impl EdfReader {
     /// # Ok::<(), edfplus::EdfError>(())
     /// ```
     pub fn read_physical_samples(&mut self, signal: usize, count: usize) -> Result<Vec<f64>> {
-        let digital_samples = self.read_digital_samples(signal, count)?;
-        
-        if signal >= self.header.signals.len() {
-            return Err(EdfError::InvalidSignalIndex(signal));
-        }
-        
-        let signal_param = &self.header.signals[signal];
-        let physical_samples = digital_samples
-            .into_iter()
-            .map(|d| signal_param.to_physical(d))
-            .collect();
-        
-        Ok(physical_samples)
+        let count = count.min(self.available_samples(signal)?);
+        let mut samples = vec![0.0; count];
+        self.read_physical_into(signal, &mut samples)?;
+        Ok(samples)
     }
     
     /// Reads digital value samples from the specified signal
@@ -499,91 +795,391 @@ impl EdfReader {
     /// # Ok::<(), edfplus::EdfError>(())
     /// ```
     pub fn read_digital_samples(&mut self, signal: usize, count: usize) -> Result<Vec<i32>> {
+        let count = count.min(self.available_samples(signal)?);
+        let mut samples = vec![0; count];
+        self.read_digital_into(signal, &mut samples)?;
+        Ok(samples)
+    }
+    
+    /// Reads physical values into a caller-provided buffer
+    /// 
+    /// Allocation-free counterpart of [`read_physical_samples`](Self::read_physical_samples):
+    /// fills `buf` from the current position of `signal` and advances it. The
+    /// signal's gain and offset are computed once per call and whole record
+    /// segments are decoded in one pass, so reusing the same buffer streams
+    /// large files with constant memory.
+    /// 
+    /// # Returns
+    /// 
+    /// Number of samples written to `buf`; less than `buf.len()` only at the
+    /// end of the signal.
+    /// 
+    /// # Errors
+    /// 
+    /// * `EdfError::InvalidSignalIndex` - Signal index is out of bounds
+    /// * `EdfError::Io` - I/O error reading from file
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::EdfReader;
+    /// 
+    /// # edfplus::doctest_utils::create_simple_test_file("streamed.edf")?;
+    /// let mut reader = EdfReader::open("streamed.edf")?;
+    /// 
+    /// // Process the whole signal in blocks of 1000 samples
+    /// let mut block = vec![0.0; 1000];
+    /// let mut sum = 0.0;
+    /// loop {
+    ///     let n = reader.read_physical_into(0, &mut block)?;
+    ///     if n == 0 {
+    ///         break;
+    ///     }
+    ///     sum += block[..n].iter().sum::<f64>();
+    /// }
+    /// println!("Sum: {}", sum);
+    /// 
+    /// # std::fs::remove_file("streamed.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn read_physical_into(&mut self, signal: usize, buf: &mut [f64]) -> Result<usize> {
+        let (gain, offset) = self.physical_gain_offset(signal)?;
+        let count = self.read_decoded_into(signal, buf, |digital| gain * digital as f64 + offset)?;
+        if let Some(filter) = &mut self.filters[signal] {
+            filter.filter(&mut buf[..count]);
+        }
+        Ok(count)
+    }
+    
+    /// Reads physical values as `f32` into a caller-provided buffer
+    /// 
+    /// Same as [`read_physical_into`](Self::read_physical_into) with single
+    /// precision output, which is plenty for 16-bit data and halves memory use.
+    pub fn read_physical_f32_into(&mut self, signal: usize, buf: &mut [f32]) -> Result<usize> {
+        let (gain, offset) = self.physical_gain_offset(signal)?;
+        if self.filters[signal].is_some() {
+            // 滤波在双精度下进行
+            let mut samples = vec![0.0; buf.len()];
+            let count = self.read_physical_into(signal, &mut samples)?;
+            buf.iter_mut().zip(&samples[..count]).for_each(|(out, &sample)| *out = sample as f32);
+            return Ok(count);
+        }
+        self.read_decoded_into(signal, buf, |digital| (gain * digital as f64 + offset) as f32)
+    }
+    
+    /// Reads digital values into a caller-provided buffer
+    /// 
+    /// Allocation-free counterpart of [`read_digital_samples`](Self::read_digital_samples).
+    /// 
+    /// # Returns
+    /// 
+    /// Number of samples written to `buf`; less than `buf.len()` only at the
+    /// end of the signal.
+    /// 
+    /// # Errors
+    /// 
+    /// * `EdfError::InvalidSignalIndex` - Signal index is out of bounds
+    /// * `EdfError::Io` - I/O error reading from file
+    pub fn read_digital_into(&mut self, signal: usize, buf: &mut [i32]) -> Result<usize> {
+        self.read_decoded_into(signal, buf, |digital| digital)
+    }
+    
+    /// Enables or disables clamping of stored values to the digital range
+    /// 
+    /// By default every read clamps values to `digital_min..=digital_max`, like
+    /// edflib. Files written by well-behaved software never exceed that range,
+    /// so disabling the check saves work in tight loops; it also exposes
+    /// out-of-range values in files that do contain them. Applies to all read
+    /// methods.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::EdfReader;
+    /// 
+    /// # edfplus::doctest_utils::create_simple_test_file("raw.edf")?;
+    /// let mut reader = EdfReader::open("raw.edf")?;
+    /// reader.set_digital_clamping(false);
+    /// let raw = reader.read_digital_samples(0, 10)?;
+    /// assert_eq!(raw.len(), 10);
+    /// 
+    /// # std::fs::remove_file("raw.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn set_digital_clamping(&mut self, enabled: bool) {
+        self.clamp_digital = enabled;
+    }
+    
+    /// Attaches a filter to the cursor-based physical reads of a signal
+    /// 
+    /// [`read_physical_samples`](Self::read_physical_samples),
+    /// [`read_physical_into`](Self::read_physical_into) and
+    /// [`read_physical_f32_into`](Self::read_physical_f32_into) then return
+    /// filtered values. The filter state carries over from one read to the
+    /// next, so chunked reads give the same values as one large read;
+    /// [`seek`](Self::seek) and [`rewind`](Self::rewind) restart the filter.
+    /// Digital and positional reads are not filtered (exports are filtered
+    /// with [`ExportOptions::filter`](crate::ExportOptions::filter)). Pass
+    /// `None` to remove the filter.
+    /// 
+    /// # Errors
+    /// 
+    /// * `EdfError::InvalidSignalIndex` - Signal index is out of bounds
+    /// * `EdfError::InvalidFormat` - The chain cannot be designed for the
+    ///   signal's sampling rate, or it is zero-phase (use
+    ///   [`read_filtered`](Self::read_filtered) for offline zero-phase filtering)
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::{EdfReader, FilterChain};
+    /// 
+    /// # edfplus::doctest_utils::create_simple_test_file("review.edf")?;
+    /// let mut reader = EdfReader::open("review.edf")?;
+    /// let filter = FilterChain::new().highpass(0.5, 2).notch(50.0, 30.0);
+    /// reader.set_filter(0, Some(&filter))?;
+    /// 
+    /// // Page through the recording; the filter runs on across pages
+    /// let first_page = reader.read_physical_samples(0, 128)?;
+    /// let second_page = reader.read_physical_samples(0, 128)?;
+    /// 
+    /// # std::fs::remove_file("review.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn set_filter(&mut self, signal: usize, filter: Option<&FilterChain>) -> Result<()> {
         if signal >= self.header.signals.len() {
             return Err(EdfError::InvalidSignalIndex(signal));
         }
-        
-        if count == 0 {
-            return Ok(Vec::new());
-        }
-        
-        // 找到实际的信号索引（跳过注释信号）
-        let mut actual_signal_idx = 0;
-        let mut user_signal_count = 0;
-        
-        for i in 0..self.signal_info.len() {
-            if !self.signal_info[i].is_annotation {
-                if user_signal_count == signal {
-                    actual_signal_idx = i;
-                    break;
-                }
-                user_signal_count += 1;
+        self.filters[signal] = match filter {
+            Some(chain) if chain.is_zero_phase() => {
+                return Err(EdfError::InvalidFormat(
+                    "Zero-phase filters need the whole signal; use read_filtered".to_string()
+                ));
             }
+            Some(chain) => Some(chain.design(self.header.sampling_frequency(signal)?)?),
+            None => None,
+        };
+        Ok(())
+    }
+    
+    /// 信号从当前位置到结尾剩余的样本数
+    fn available_samples(&self, signal: usize) -> Result<usize> {
+        let signal_param = self.header.signals.get(signal)
+            .ok_or(EdfError::InvalidSignalIndex(signal))?;
+        let samples_in_file = signal_param.samples_per_record as i64 * self.header.datarecords_in_file;
+        Ok((samples_in_file - self.sample_positions[signal]).max(0) as usize)
+    }
+    
+    /// 预计算的物理值转换系数：physical = gain * digital + offset
+    fn physical_gain_offset(&self, signal: usize) -> Result<(f64, f64)> {
+        let signal_param = self.header.signals.get(signal)
+            .ok_or(EdfError::InvalidSignalIndex(signal))?;
+        let gain = signal_param.bit_value();
+        Ok((gain, gain * signal_param.offset()))
+    }
+    
+    /// 用户信号索引对应的 signal_info（跳过注释信号）
+    fn data_signal_info(&self, signal: usize) -> &SignalInfo {
+        self.signal_info.iter()
+            .filter(|info| !info.is_annotation)
+            .nth(signal)
+            .expect("signal index checked against header")
+    }
+    
+    /// 从当前位置读取并推进位置
+    fn read_decoded_into<T>(
+        &mut self,
+        signal: usize,
+        buf: &mut [T],
+        decode: impl Fn(i32) -> T,
+    ) -> Result<usize> {
+        if signal >= self.header.signals.len() {
+            return Err(EdfError::InvalidSignalIndex(signal));
         }
         
-        let signal_info = &self.signal_info[actual_signal_idx];
-        let signal_param = &self.header.signals[signal];
+        let current_pos = self.sample_positions[signal];
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let result = self.decode_at(signal, current_pos, buf, &mut scratch, self.clamp_digital, decode);
+        self.scratch = scratch;
+        let samples_read = result?;
         
-        // 计算可读取的最大样本数
-        let samples_in_file = signal_param.samples_per_record as i64 * self.header.datarecords_in_file;
-        let available_samples = (samples_in_file - self.sample_positions[signal]).max(0) as usize;
-        let actual_count = count.min(available_samples);
+        // 更新样本位置
+        self.sample_positions[signal] = current_pos + samples_read as i64;
         
+        Ok(samples_read)
+    }
+    
+    /// Reads physical values starting at a sample position, without a cursor
+    /// 
+    /// Positional counterpart of [`read_physical_samples`](Self::read_physical_samples):
+    /// takes `&self`, uses positional (pread-style) I/O and leaves the
+    /// per-signal positions untouched. Since `EdfReader` is `Sync`, many
+    /// threads can read different signals or windows of one open file at once.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `signal` - Zero-based index of the signal
+    /// * `start` - Index of the first sample to read
+    /// * `count` - Number of samples to read; fewer are returned at the end of the signal
+    /// 
+    /// # Errors
+    /// 
+    /// * `EdfError::InvalidSignalIndex` - Signal index is out of bounds
+    /// * `EdfError::InvalidFormat` - `start` is negative
+    /// * `EdfError::Io` - I/O error reading from file
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::EdfReader;
+    /// 
+    /// # edfplus::doctest_utils::create_multi_channel_test_file("parallel.edf")?;
+    /// let reader = EdfReader::open("parallel.edf")?;
+    /// let signal_count = reader.header().signals.len();
+    /// 
+    /// // Decode all signals in parallel from one open file
+    /// let means: Vec<f64> = std::thread::scope(|scope| {
+    ///     let handles: Vec<_> = (0..signal_count)
+    ///         .map(|signal| {
+    ///             let reader = &reader;
+    ///             scope.spawn(move || {
+    ///                 let samples = reader.read_physical_at(signal, 0, 1024)?;
+    ///                 Ok::<_, edfplus::EdfError>(samples.iter().sum::<f64>() / samples.len() as f64)
+    ///             })
+    ///         })
+    ///         .collect();
+    ///     handles.into_iter().map(|h| h.join().unwrap()).collect::<Result<_, _>>()
+    /// })?;
+    /// assert_eq!(means.len(), signal_count);
+    /// 
+    /// # std::fs::remove_file("parallel.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn read_physical_at(&self, signal: usize, start: i64, count: usize) -> Result<Vec<f64>> {
+        let mut samples = vec![0.0; count.min(self.samples_from(signal, start)?)];
+        self.read_physical_at_into(signal, start, &mut samples)?;
+        Ok(samples)
+    }
+    
+    /// Reads digital values starting at a sample position, without a cursor
+    /// 
+    /// Positional counterpart of [`read_digital_samples`](Self::read_digital_samples);
+    /// see [`read_physical_at`](Self::read_physical_at).
+    pub fn read_digital_at(&self, signal: usize, start: i64, count: usize) -> Result<Vec<i32>> {
+        let mut samples = vec![0; count.min(self.samples_from(signal, start)?)];
+        self.read_digital_at_into(signal, start, &mut samples)?;
+        Ok(samples)
+    }
+    
+    /// Reads physical values at a sample position into a caller-provided buffer
+    /// 
+    /// Combines [`read_physical_at`](Self::read_physical_at) and
+    /// [`read_physical_into`](Self::read_physical_into). Returns the number of
+    /// samples written to `buf`.
+    pub fn read_physical_at_into(&self, signal: usize, start: i64, buf: &mut [f64]) -> Result<usize> {
+        let (gain, offset) = self.physical_gain_offset(signal)?;
+        with_scratch(|scratch| self.decode_at(signal, start, buf, scratch, self.clamp_digital, |digital| gain * digital as f64 + offset))
+    }
+    
+    /// Reads digital values at a sample position into a caller-provided buffer
+    /// 
+    /// Returns the number of samples written to `buf`.
+    pub fn read_digital_at_into(&self, signal: usize, start: i64, buf: &mut [i32]) -> Result<usize> {
+        with_scratch(|scratch| self.decode_at(signal, start, buf, scratch, self.clamp_digital, |digital| digital))
+    }
+    
+    /// 按位置读取存储的原始数字值，不论是否设置了范围限制
+    pub(crate) fn read_raw_digital_at(&self, signal: usize, start: i64, count: usize) -> Result<Vec<i32>> {
+        let mut samples = vec![0; count.min(self.samples_from(signal, start)?)];
+        with_scratch(|scratch| self.decode_at(signal, start, &mut samples, scratch, false, |digital| digital))?;
+        Ok(samples)
+    }
+    
+    /// 将一个数据记录中某信号的原始字节（16位小端序样本）追加到 out，不做范围限制
+    pub(crate) fn read_record_bytes(&self, record: i64, signal: usize, out: &mut Vec<u8>) -> Result<()> {
+        let signal_param = self.header.signals.get(signal)
+            .ok_or(EdfError::InvalidSignalIndex(signal))?;
+        if record < 0 || record >= self.header.datarecords_in_file {
+            return Err(EdfError::InvalidFormat(format!("No data record {}", record)));
+        }
+        let file_offset = self.header_size as u64
+            + record as u64 * self.record_size as u64
+            + self.data_signal_info(signal).buffer_offset as u64;
+        let start = out.len();
+        out.resize(start + signal_param.samples_per_record as usize * 2, 0);
+        read_exact_at(self.file.get_ref(), &mut out[start..], file_offset)?;
+        Ok(())
+    }
+    
+    /// 从 start 开始到信号结尾的样本数
+    fn samples_from(&self, signal: usize, start: i64) -> Result<usize> {
+        let signal_param = self.header.signals.get(signal)
+            .ok_or(EdfError::InvalidSignalIndex(signal))?;
+        if start < 0 {
+            return Err(EdfError::InvalidFormat(format!("Negative sample position {}", start)));
+        }
+        let samples_in_file = signal_param.samples_per_record as i64 * self.header.datarecords_in_file;
+        Ok((samples_in_file - start).max(0) as usize)
+    }
+    
+    /// 从指定位置按记录段读取原始样本并用 decode 转换写入 buf（位置读取，不修改状态）
+    fn decode_at<T>(
+        &self,
+        signal: usize,
+        start: i64,
+        buf: &mut [T],
+        scratch: &mut Vec<u8>,
+        clamp: bool,
+        decode: impl Fn(i32) -> T,
+    ) -> Result<usize> {
+        let actual_count = buf.len().min(self.samples_from(signal, start)?);
         if actual_count == 0 {
-            return Ok(Vec::new());
+            return Ok(0);
         }
         
-        let mut samples = Vec::with_capacity(actual_count);
+        let signal_param = &self.header.signals[signal];
+        let samples_per_record = signal_param.samples_per_record as i64;
+        let (digital_min, digital_max) = (signal_param.digital_min, signal_param.digital_max);
+        let buffer_offset = self.data_signal_info(signal).buffer_offset;
+        let file = self.file.get_ref();
         let mut samples_read = 0;
-        let current_pos = self.sample_positions[signal];
         
         // ✅ 性能优化：使用类似 edflib 的直接计算方式
         while samples_read < actual_count {
-            let pos = current_pos + samples_read as i64;
-            let record_index = pos / signal_param.samples_per_record as i64;
-            let sample_in_record = pos % signal_param.samples_per_record as i64;
+            let pos = start + samples_read as i64;
+            let record_index = pos / samples_per_record;
+            let sample_in_record = pos % samples_per_record;
             
             // 计算连续可读取的样本数（避免跨记录）
-            let samples_remaining_in_record = 
-                (signal_param.samples_per_record as i64 - sample_in_record) as usize;
+            let samples_remaining_in_record = (samples_per_record - sample_in_record) as usize;
             let samples_to_read = (actual_count - samples_read).min(samples_remaining_in_record);
             
             // ✅ 使用预计算的 buffer_offset 直接定位
             let file_offset = self.header_size as u64 
                 + record_index as u64 * self.record_size as u64
-                + signal_info.buffer_offset as u64
+                + buffer_offset as u64
                 + sample_in_record as u64 * 2; // EDF每个样本2字节
             
-            // 定位到正确位置
-            self.file.seek(SeekFrom::Start(file_offset))?;
+            // ✅ 批量位置读取到复用的缓冲区
+            scratch.resize(samples_to_read * 2, 0);
+            read_exact_at(file, scratch, file_offset)?;
             
-            // ✅ 批量读取以提高性能
-            let bytes_to_read = samples_to_read * 2;
-            let mut buffer = vec![0u8; bytes_to_read];
-            self.file.read_exact(&mut buffer)?;
-            
-            // 转换字节到数字值并应用范围限制
-            for chunk in buffer.chunks_exact(2) {
-                let digital_value = i16::from_le_bytes([chunk[0], chunk[1]]) as i32;
-                
-                // ✅ 应用数字范围限制（类似 edflib 的 clamping）
-                let clamped_value = digital_value
-                    .max(signal_param.digital_min)
-                    .min(signal_param.digital_max);
-                
-                samples.push(clamped_value);
-                samples_read += 1;
-                
-                if samples_read >= actual_count {
-                    break;
+            // 转换字节到数字值并应用范围限制（类似 edflib 的 clamping）
+            let out = &mut buf[samples_read..samples_read + samples_to_read];
+            if clamp {
+                for (dst, chunk) in out.iter_mut().zip(scratch.chunks_exact(2)) {
+                    let digital = i16::from_le_bytes([chunk[0], chunk[1]]) as i32;
+                    *dst = decode(digital.max(digital_min).min(digital_max));
+                }
+            } else {
+                for (dst, chunk) in out.iter_mut().zip(scratch.chunks_exact(2)) {
+                    *dst = decode(i16::from_le_bytes([chunk[0], chunk[1]]) as i32);
                 }
             }
+            samples_read += samples_to_read;
         }
         
-        // 更新样本位置
-        self.sample_positions[signal] = current_pos + samples_read as i64;
-        
-        Ok(samples)
+        Ok(samples_read)
     }
     
     /// Sets the sample position for the specified signal
@@ -670,6 +1266,9 @@ impl EdfReader {
         
         let new_position = position.max(0).min(max_position);
         self.sample_positions[signal] = new_position;
+        if let Some(filter) = &mut self.filters[signal] {
+            filter.reset();
+        }
         
         Ok(new_position)
     }
@@ -838,8 +1437,99 @@ impl EdfReader {
         Ok(())
     }
     
+    /// Iterates over the data records of the file
+    /// 
+    /// Each record is read with a single I/O operation and yields the samples
+    /// of every ordinary signal together with the TALs (time-stamped annotation
+    /// lists) of every annotation channel. Useful for record-oriented work such
+    /// as validation, copying, or custom annotation handling.
+    /// 
+    /// The per-signal positions used by [`read_physical_samples`](Self::read_physical_samples)
+    /// and friends are not affected. A record with a malformed TAL yields
+    /// `EdfError::InvalidAnnotation`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::EdfReader;
+    /// 
+    /// # edfplus::doctest_utils::create_simple_test_file("records.edf")?;
+    /// let mut reader = EdfReader::open("records.edf")?;
+    /// let gain = reader.header().signals[0].bit_value();
+    /// 
+    /// for record in reader.records() {
+    ///     let record = record?;
+    ///     let samples = record.signal(0);
+    ///     let peak = samples.iter().map(|&d| d.abs()).max().unwrap_or(0);
+    ///     println!("record {} at {:.1}s: {} samples, peak {:.1}, {} annotations",
+    ///         record.index,
+    ///         record.onset as f64 / 10_000_000.0,
+    ///         samples.len(),
+    ///         peak as f64 * gain,
+    ///         record.annotations.iter().map(Vec::len).sum::<usize>());
+    /// }
+    /// 
+    /// # std::fs::remove_file("records.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn records(&mut self) -> DataRecords<'_> {
+        let end = self.header.datarecords_in_file.max(0) as usize;
+        DataRecords { reader: self, next: 0, end, buffer: Vec::new() }
+    }
+    
+    /// 读取并解码单个数据记录
+    fn read_record(&mut self, index: usize, buffer: &mut Vec<u8>) -> Result<DataRecord> {
+        let record_offset = self.header_size as u64 + index as u64 * self.record_size as u64;
+        buffer.resize(self.record_size, 0);
+        self.file.seek(SeekFrom::Start(record_offset))?;
+        self.file.read_exact(buffer)?;
+        
+        let mut samples = Vec::new();
+        let mut offsets = vec![0];
+        let mut annotations = Vec::new();
+        let mut onset = None;
+        let mut data_signal = 0;
+        
+        for info in &self.signal_info {
+            let bytes = &buffer[info.buffer_offset..info.buffer_offset + info.samples_per_record as usize * 2];
+            if info.is_annotation {
+                let is_first = annotations.is_empty();
+                // 第一个注释信号的第一个TAL是记录的时间戳
+                if is_first {
+                    onset = Self::first_tal_time(bytes);
+                }
+                let tal_offset = record_offset as usize + info.buffer_offset;
+                annotations.push(Self::parse_tal_data(
+                    bytes, self.header.starttime_subsecond, is_first, index as i64, tal_offset,
+                )?);
+            } else {
+                let param = &self.header.signals[data_signal];
+                let (digital_min, digital_max) = (param.digital_min, param.digital_max);
+                samples.extend(bytes.chunks_exact(2).map(|chunk| {
+                    let digital = i16::from_le_bytes([chunk[0], chunk[1]]) as i32;
+                    if self.clamp_digital {
+                        digital.max(digital_min).min(digital_max)
+                    } else {
+                        digital
+                    }
+                }));
+                offsets.push(samples.len());
+                data_signal += 1;
+            }
+        }
+        
+        let onset = onset
+            .map(|timestamp| timestamp.saturating_sub(self.header.starttime_subsecond))
+            .unwrap_or(index as i64 * self.header.datarecord_duration);
+        
+        Ok(DataRecord { index, onset, samples, offsets, annotations })
+    }
+    
     /// 解析EDF+文件头部
-    fn parse_header(reader: &mut BufReader<File>) -> Result<(EdfHeader, Vec<SignalInfo>, usize)> {
+    /// 
+    /// Never panics on malformed input: every field is validated before it is
+    /// used for indexing, allocation or arithmetic.
+    pub(crate) fn parse_header<R: Read + Seek>(reader: &mut R) -> Result<(EdfHeader, Vec<SignalInfo>, usize)> {
         // 读取主头部（256字节）
         reader.seek(SeekFrom::Start(0))?;
         let mut main_header = vec![0u8; 256];
@@ -855,7 +1545,10 @@ impl EdfReader {
         let signals_str = String::from_utf8_lossy(&main_header[252..256]);
         let total_signal_count = atoi_nonlocalized(&signals_str);
         if total_signal_count < 1 || total_signal_count > crate::EDFLIB_MAXSIGNALS as i32 {
-            return Err(EdfError::InvalidSignalCount(total_signal_count));
+            return Err(header_error(
+                "number of signals", None, 252..256, &main_header,
+                format!("must be between 1 and {}", crate::EDFLIB_MAXSIGNALS),
+            ));
         }
         
         // 验证头部大小
@@ -863,13 +1556,16 @@ impl EdfReader {
         let expected_header_size = (total_signal_count + 1) * 256;
         let actual_header_size = atoi_nonlocalized(&header_size_str);
         if actual_header_size != expected_header_size {
-            return Err(EdfError::InvalidHeader);
+            return Err(header_error(
+                "number of bytes in header", None, 184..192, &main_header,
+                format!("expected {} for {} signals", expected_header_size, total_signal_count),
+            ));
         }
         
         // 检查EDF+标识
         let reserved = String::from_utf8_lossy(&main_header[192..236]);
-        let is_edfplus = reserved.starts_with("EDF+C");
-        if !is_edfplus {
+        let discontinuous = reserved.starts_with("EDF+D");
+        if !reserved.starts_with("EDF+C") && !discontinuous {
             return Err(EdfError::UnsupportedFileType("Only EDF+ files are supported".to_string()));
         }
         
@@ -878,20 +1574,30 @@ impl EdfReader {
         let recording_field = String::from_utf8_lossy(&main_header[88..168]).trim().to_string();
         
         // 解析日期和时间
-        let date_str = String::from_utf8_lossy(&main_header[168..176]);
-        let time_str = String::from_utf8_lossy(&main_header[176..184]);
-        
-        let (start_date, start_time) = Self::parse_datetime(&date_str, &time_str)?;
+        let start_date = Self::parse_date(&main_header)?;
+        let start_time = Self::parse_time(&main_header)?;
         
         // 解析数据记录信息
         let datarecords_str = String::from_utf8_lossy(&main_header[236..244]);
         let datarecords = atoi_nonlocalized(&datarecords_str) as i64;
+        if datarecords < 0 {
+            return Err(header_error(
+                "number of data records", None, 236..244, &main_header,
+                "must not be negative",
+            ));
+        }
         
         let duration_str = String::from_utf8_lossy(&main_header[244..252]);
         let datarecord_duration = if duration_str.trim() == "1" {
             EDFLIB_TIME_DIMENSION
         } else {
-            parse_edf_time(&duration_str)?
+            parse_edf_time(&duration_str)
+                .ok()
+                .filter(|&duration| duration >= 0)
+                .ok_or_else(|| header_error(
+                    "duration of a data record", None, 244..252, &main_header,
+                    "must be a non-negative number of seconds",
+                ))?
         };
         
         // 读取信号头部信息
@@ -913,15 +1619,42 @@ impl EdfReader {
         let (admin_code, technician, equipment, recording_additional) = 
             Self::parse_edfplus_recording(&recording_field)?;
         
+        // 记录过大时拒绝，避免按头部声明分配巨大缓冲区
+        if total_record_size > MAX_RECORD_SIZE {
+            return Err(EdfError::InvalidFormat(format!(
+                "Data record size of {} bytes exceeds the limit of {} bytes", total_record_size, MAX_RECORD_SIZE
+            )));
+        }
+        // 与 edflib 一样要求文件大小与头部声明的数据记录数一致
+        let header_size = (total_signal_count as u64 + 1) * 256;
+        let file_size = reader.seek(SeekFrom::End(0))?;
+        let expected_size = (total_record_size as u64).checked_mul(datarecords as u64)
+            .and_then(|size| size.checked_add(header_size));
+        if expected_size != Some(file_size) {
+            return Err(header_error(
+                "number of data records", None, 236..244, &main_header,
+                format!(
+                    "{} data records of {} bytes do not match the file size of {} bytes",
+                    datarecords, total_record_size, file_size
+                ),
+            ));
+        }
+        let file_duration = datarecord_duration.checked_mul(datarecords)
+            .ok_or_else(|| header_error(
+                "number of data records", None, 236..244, &main_header,
+                "total recording duration is out of range",
+            ))?;
+        
         // 创建临时头部用于注释解析
         let mut temp_header = EdfHeader {
             signals,
-            file_duration: datarecord_duration * datarecords,
+            file_duration,
             start_date,
             start_time,
             starttime_subsecond: 0,
             datarecords_in_file: datarecords,
             datarecord_duration,
+            discontinuous,
             annotations_in_file: 0,
             patient_code,
             sex,
@@ -940,7 +1673,7 @@ impl EdfReader {
             &signal_info, 
             datarecords,
             total_record_size,
-            (total_signal_count as usize + 1) * 256
+            header_size as usize
         ).unwrap_or((0, 0));
         
         // 更新头部信息
@@ -950,12 +1683,12 @@ impl EdfReader {
         Ok((temp_header, signal_info, total_record_size))
     }
     
-    /// 解析日期时间
-    fn parse_datetime(date_str: &str, time_str: &str) -> Result<(NaiveDate, NaiveTime)> {
-        // 解析日期 "dd.mm.yy"
+    /// 解析起始日期 "dd.mm.yy"（字节 168..176）
+    fn parse_date(main_header: &[u8]) -> Result<NaiveDate> {
+        let date_str = String::from_utf8_lossy(&main_header[168..176]);
         let date_parts: Vec<&str> = date_str.split('.').collect();
         if date_parts.len() != 3 {
-            return Err(EdfError::FormatError);
+            return Err(header_error("startdate", None, 168..176, main_header, "expected dd.mm.yy"));
         }
         
         let day = atoi_nonlocalized(date_parts[0]);
@@ -965,34 +1698,41 @@ impl EdfReader {
             if yy > 84 { 1900 + yy } else { 2000 + yy }
         };
         
-        let start_date = NaiveDate::from_ymd_opt(year, month as u32, day as u32)
-            .ok_or(EdfError::FormatError)?;
-        
-        // 解析时间 "hh.mm.ss"
+        NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+            .ok_or_else(|| header_error("startdate", None, 168..176, main_header, "not a valid date"))
+    }
+    
+    /// 解析起始时间 "hh.mm.ss"（字节 176..184）
+    fn parse_time(main_header: &[u8]) -> Result<NaiveTime> {
+        let time_str = String::from_utf8_lossy(&main_header[176..184]);
         let time_parts: Vec<&str> = time_str.split('.').collect();
         if time_parts.len() != 3 {
-            return Err(EdfError::FormatError);
+            return Err(header_error("starttime", None, 176..184, main_header, "expected hh.mm.ss"));
         }
         
         let hour = atoi_nonlocalized(time_parts[0]);
         let minute = atoi_nonlocalized(time_parts[1]);
         let second = atoi_nonlocalized(time_parts[2]);
         
-        let start_time = NaiveTime::from_hms_opt(hour as u32, minute as u32, second as u32)
-            .ok_or(EdfError::FormatError)?;
-        
-        Ok((start_date, start_time))
+        NaiveTime::from_hms_opt(hour as u32, minute as u32, second as u32)
+            .ok_or_else(|| header_error("starttime", None, 176..184, main_header, "not a valid time of day"))
     }
     
     /// 解析信号参数
-    fn parse_signals(
+    pub(crate) fn parse_signals(
         signal_header: &[u8], 
         total_signal_count: usize,
         datarecords: i64
     ) -> Result<(Vec<SignalParam>, Vec<SignalInfo>, usize)> {
+        if signal_header.len() < total_signal_count * 256 {
+            return Err(EdfError::InvalidFormat(format!(
+                "Signal header of {} bytes is too short for {} signals", signal_header.len(), total_signal_count
+            )));
+        }
+        
         let mut signals = Vec::new();
         let mut signal_info = Vec::new();
-        let mut buffer_offset = 0;
+        let mut buffer_offset = 0usize;
         
         // 解析每个信号的各个字段
         for i in 0..total_signal_count {
@@ -1064,14 +1804,33 @@ impl EdfReader {
                 is_annotation,
             };
             
+            if samples_per_record < 1 {
+                return Err(signal_error(
+                    "number of samples in each data record", i, samples_start, signal_header,
+                    "must be positive",
+                ));
+            }
+            if !physical_min.is_finite() {
+                return Err(signal_error("physical minimum", i, phys_min_start, signal_header, "must be a finite number"));
+            }
+            if !physical_max.is_finite() {
+                return Err(signal_error("physical maximum", i, phys_max_start, signal_header, "must be a finite number"));
+            }
+            
             // 只有非注释信号才添加到用户可见的信号列表中
             if !is_annotation {
                 // 验证参数
                 if physical_min == physical_max {
-                    return Err(EdfError::PhysicalMinEqualsMax);
+                    return Err(signal_error(
+                        "physical minimum", i, phys_min_start, signal_header,
+                        "physical minimum equals physical maximum",
+                    ));
                 }
                 if digital_min == digital_max {
-                    return Err(EdfError::DigitalMinEqualsMax);
+                    return Err(signal_error(
+                        "digital minimum", i, dig_min_start, signal_header,
+                        "digital minimum equals digital maximum",
+                    ));
                 }
                 
                 let signal_param = SignalParam {
@@ -1095,7 +1854,7 @@ impl EdfReader {
             
             // ✅ 关键修复：为所有信号（包括注释信号）更新 buffer_offset
             // 每个样本占用 2 字节（EDF 格式固定）
-            buffer_offset += samples_per_record as usize * 2;
+            buffer_offset = buffer_offset.saturating_add(samples_per_record as usize * 2);
         }
         
         Ok((signals, signal_info, buffer_offset))
@@ -1106,7 +1865,7 @@ impl EdfReader {
         // EDF+ 患者字段格式: "patientcode sex birthdate patientname additional_info"
         let parts: Vec<&str> = patient_field.split_whitespace().collect();
         
-        let patient_code = parts.get(0).unwrap_or(&"").to_string();
+        let patient_code = parts.first().unwrap_or(&"").to_string();
         let sex = parts.get(1).unwrap_or(&"").to_string();
         let birthdate = parts.get(2).unwrap_or(&"").to_string();
         let patient_name = parts.get(3).unwrap_or(&"").to_string();
@@ -1159,6 +1918,7 @@ impl EdfReader {
             // 读取整个数据记录
             let mut record_data = vec![0u8; self.record_size];
             self.file.read_exact(&mut record_data)?;
+            let mut record_onset = None;
             
             // 处理每个注释信号
             for (ann_idx, &ann_signal_idx) in annotation_signals.iter().enumerate() {
@@ -1172,344 +1932,233 @@ impl EdfReader {
                 if signal_offset + bytes_to_read <= record_data.len() {
                     let tal_data = &record_data[signal_offset..signal_offset + bytes_to_read];
                     
-                    // 第一个注释信号需要验证时间戳
+                    // 第一个注释信号需要验证时间戳（时间戳TAL位于注释信号开头）
+                    let tal_offset = record_offset as usize + signal_offset;
                     if ann_idx == 0 {
-                        if let Some(timestamp) = self.extract_timestamp(tal_data, record_idx)? {
+                        if let Some(timestamp) = Self::first_tal_time(tal_data) {
                             if record_idx > 0 {
-                                // 验证时间连续性
-                                let expected_time = elapsed_time + self.header.datarecord_duration;
-                                let time_diff = (timestamp - expected_time).abs();
-                                if time_diff > EDFLIB_TIME_DIMENSION / 1000 {
+                                // 验证时间连续性；EDF+D 只要求记录不重叠
+                                let expected_time = elapsed_time.saturating_add(self.header.datarecord_duration);
+                                if self.header.discontinuous && timestamp < expected_time.saturating_sub(CONTIGUOUS_TOLERANCE) {
+                                    return Err(EdfError::InvalidAnnotation {
+                                        record: record_idx,
+                                        offset: tal_offset,
+                                        reason: format!(
+                                            "time-keeping TAL is {} s, overlapping the previous data record ending at {} s",
+                                            timestamp as f64 / EDFLIB_TIME_DIMENSION as f64,
+                                            expected_time as f64 / EDFLIB_TIME_DIMENSION as f64,
+                                        ),
+                                    });
+                                }
+                                if !self.header.discontinuous && timestamp.abs_diff(expected_time) > CONTIGUOUS_TOLERANCE as u64 {
                                     // 时间不连续，可能是discontinuous文件
-                                    return Err(EdfError::InvalidHeader);
+                                    return Err(EdfError::InvalidAnnotation {
+                                        record: record_idx,
+                                        offset: tal_offset,
+                                        reason: format!(
+                                            "time-keeping TAL is {} s, expected {} s for a continuous recording",
+                                            timestamp as f64 / EDFLIB_TIME_DIMENSION as f64,
+                                            expected_time as f64 / EDFLIB_TIME_DIMENSION as f64,
+                                        ),
+                                    });
                                 }
                             } else if !first_record_processed {
                                 // 第一个记录，设置subsecond偏移 (如果还没有设置)
                                 if self.header.starttime_subsecond == 0 {
-                                    self.header.starttime_subsecond = timestamp % EDFLIB_TIME_DIMENSION;
+                                    self.header.starttime_subsecond = timestamp.rem_euclid(EDFLIB_TIME_DIMENSION);
                                 }
                                 first_record_processed = true;
                             }
                             elapsed_time = timestamp;
+                            record_onset = Some(timestamp.saturating_sub(self.header.starttime_subsecond));
                         }
                     }
                     
                     // 解析注释
-                    let record_annotations = self.parse_tal_data(
+                    let record_annotations = Self::parse_tal_data(
                         tal_data, 
-                        record_idx as usize, 
-                        ann_idx == 0
+                        self.header.starttime_subsecond, 
+                        ann_idx == 0,
+                        record_idx,
+                        tal_offset,
                     )?;
                     annotations.extend(record_annotations);
                 }
             }
+            
+            // 缺少时间戳的记录紧接上一个记录
+            let record_onset = record_onset.unwrap_or_else(|| match self.record_onsets.last() {
+                Some(previous) => previous.saturating_add(self.header.datarecord_duration),
+                None => 0,
+            });
+            self.record_onsets.push(record_onset);
         }
         
         // 按时间排序
-        annotations.sort_by(|a, b| a.onset.cmp(&b.onset));
+        annotations.sort_by_key(|a| a.onset);
         
         Ok(annotations)
     }
 
-    fn extract_timestamp(&self, data: &[u8], _record_idx: i64) -> Result<Option<i64>> {
-        // 提取第一个时间戳用于验证
-        let mut k = 0;
-        let mut n = 0;
-        let mut scratchpad = vec![0u8; 64];
-        
-        while k < data.len() - 1 {
-            let byte = data[k];
-            
-            if byte == 0 {
-                break;
-            }
-            
-            if byte == 20 { // TAL分隔符
-                scratchpad[n] = 0;
-                let time_str = String::from_utf8_lossy(&scratchpad[0..n]);
-                // 移除前导'+'号
-                let time_str = time_str.trim_start_matches('+');
-                if let Ok(timestamp) = time_str.parse::<f64>() {
-                    return Ok(Some((timestamp * EDFLIB_TIME_DIMENSION as f64) as i64));
-                }
-                break;
-            }
-            
-            if n < scratchpad.len() - 1 {
-                scratchpad[n] = byte;
-                n += 1;
-            }
-            
-            k += 1;
+    /// 第一个TAL的onset（记录时间戳），格式错误时返回 None
+    fn first_tal_time(data: &[u8]) -> Option<i64> {
+        let end = data.iter().position(|&byte| byte == 20 || byte == 0)?;
+        if data[end] != 20 {
+            return None;
         }
-        
-        Ok(None)
+        parse_edf_time(&String::from_utf8_lossy(&data[..end])).ok()
     }
     
 
     /// Parses TAL data from a byte buffer following edflib implementation
     /// 
-    /// TAL format: "+<onset>[\x15<duration>]\x14<description>\x14"
+    /// TAL format: "+<onset>[\x15<duration>]\x14<description>\x14[<description>\x14...]\x00"
     /// 
     /// This closely follows the edflib_get_annotations logic for parsing TAL data.
-    fn parse_tal_data(&self, data: &[u8], _record_idx: usize, is_first_annotation_signal: bool) -> Result<Vec<Annotation>> {
+    /// 
+    /// Never panics: a malformed TAL, or an onset or duration that cannot be
+    /// represented, is returned as `EdfError::InvalidAnnotation` with the
+    /// record index and the byte offset of the TAL (`offset` is the file
+    /// offset of `data`).
+    pub(crate) fn parse_tal_data(
+        data: &[u8],
+        starttime_subsecond: i64,
+        is_first_annotation_signal: bool,
+        record: i64,
+        offset: usize,
+    ) -> Result<Vec<Annotation>> {
         let mut annotations = Vec::new();
-        let max = data.len();
+        let error = |at: usize, reason: String| EdfError::InvalidAnnotation { record, offset: offset + at, reason };
         
-        if max == 0 || data[max - 1] != 0 {
-            return Ok(annotations);
-        }
-        
-        // // 临时调试输出
-        // println!("DEBUG: 开始解析TAL数据，长度: {}", max);
-        // let preview_len = 50.min(max);
-        // print!("DEBUG: 前{}字节: ", preview_len);
-        // for i in 0..preview_len {
-        //     let byte = data[i];
-        //     if byte >= 32 && byte <= 126 {
-        //         print!("{}", byte as char);
-        //     } else if byte == 0x14 {
-        //         print!("\\x14");
-        //     } else if byte == 0x15 {
-        //         print!("\\x15");
-        //     } else if byte == 0 {
-        //         print!("\\0");
-        //     } else {
-        //         print!("\\x{:02x}", byte);
-        //     }
-        // }
-        // println!();
-        
-        let mut k = 0;
         let mut state = TalState::WaitingForOnset;
-        let mut n = 0;
-        let mut scratchpad = vec![0u8; max + 16];
-        let mut time_in_txt = vec![0u8; 32];
-        let mut duration_in_txt = vec![0u8; 32];
+        let mut tal_start = 0;
+        let mut field = Vec::new();
+        let mut time_in_txt = Vec::new();
+        let mut duration_in_txt: Option<Vec<u8>> = None;
         let mut zero = 0;
         let mut annots_in_record = 0;
-        let mut _annots_in_tal = 0;
-        let mut duration = false;
+        let mut descriptions_in_tal = 0;
         
-        while k < max - 1 {
-            let byte = data[k];
-            
-            // 处理null字节（TAL结束标记）
+        for (k, &byte) in data.iter().enumerate() {
+            // 处理null字节：TAL结束标记或之后的填充
             if byte == 0 {
-                if zero == 0 {
-                    if k > 0 && data[k - 1] != 20 {
-                        // 格式错误：null字节前应该是分隔符
-                        break;
+                if state != TalState::WaitingForOnset {
+                    // TAL 必须以 \x14\x00 结束
+                    if state != TalState::CollectingDescription || data[k - 1] != 20 {
+                        return Err(error(tal_start, "TAL is not terminated by 0x14 before 0x00".to_string()));
                     }
-                    // 重置状态到新TAL开始
                     state = TalState::WaitingForOnset;
-                    n = 0;
-                    scratchpad.fill(0);
-                    _annots_in_tal = 0;
                 }
                 zero += 1;
-                k += 1;
                 continue;
             }
-            
             if zero > 1 {
-                // 格式错误：连续的null字节太多
-                break;
+                // 填充的null字节之后不能再有数据
+                return Err(error(k, "data after the 0x00 padding that ends the TALs".to_string()));
             }
             zero = 0;
             
-            // 主状态机逻辑 - 基于edflib的布尔逻辑适应到Rust enum
+            // 描述之后紧接 '+' 或 '-' 的是下一个TAL：本库的写入器在TAL之间不写 0x00
+            if state == TalState::CollectingDescription
+                && descriptions_in_tal > 0
+                && field.is_empty()
+                && (byte == b'+' || byte == b'-')
+            {
+                state = TalState::WaitingForOnset;
+            }
+            
             match state {
                 TalState::WaitingForOnset => {
-                    // 等待onset开始，跳过前导'+'
-                    if byte == b'+' {
-                        state = TalState::CollectingOnset;
-                        n = 0;
-                    } else if byte == 20 || byte == 21 {
-                        // 如果没有onset就遇到分隔符，说明格式错误
-                        break;
+                    // 每个TAL以onset的符号开始
+                    if byte != b'+' && byte != b'-' {
+                        return Err(error(k, format!("TAL starts with byte 0x{:02x} instead of '+' or '-'", byte)));
                     }
-                    k += 1;
+                    tal_start = k;
+                    time_in_txt.clear();
+                    time_in_txt.push(byte);
+                    duration_in_txt = None;
+                    descriptions_in_tal = 0;
+                    field.clear();
+                    state = TalState::CollectingOnset;
                 }
                 
                 TalState::CollectingOnset => {
-                    if byte == 20 { // Onset分隔符
-                        // 完成onset收集
-                        scratchpad[n] = 0;
-                        let onset_str = String::from_utf8_lossy(&scratchpad[0..n]);
-                        
-                        // 验证onset格式
-                        if !Self::is_valid_onset(&onset_str) {
-                            // println!("DEBUG: 无效的onset格式: '{}'", onset_str);
-                            break;
-                        }
-                        
-                        // 保存onset时间
-                        let copy_len = n.min(time_in_txt.len() - 1);
-                        time_in_txt[..copy_len].copy_from_slice(&scratchpad[..copy_len]);
-                        time_in_txt[copy_len] = 0;
-                        
-                        state = TalState::CollectingDescription;
-                        n = 0;
-                        
-                        // println!("DEBUG: 完成onset字段: '{}'", onset_str);
-                    } else if byte == 21 { // Duration分隔符
-                        // 完成onset收集，开始duration
-                        scratchpad[n] = 0;
-                        let onset_str = String::from_utf8_lossy(&scratchpad[0..n]);
-                        
-                        // 验证onset格式
-                        if !Self::is_valid_onset(&onset_str) {
-                            // println!("DEBUG: 无效的onset格式: '{}'", onset_str);
-                            break;
+                    if byte == 20 || byte == 21 { // Onset结束：描述或duration分隔符
+                        if !Self::is_valid_onset(&String::from_utf8_lossy(&field)) {
+                            return Err(error(tal_start, format!("invalid onset '{}'", String::from_utf8_lossy(&field))));
                         }
-                        
-                        // 保存onset时间
-                        let copy_len = n.min(time_in_txt.len() - 1);
-                        time_in_txt[..copy_len].copy_from_slice(&scratchpad[..copy_len]);
-                        time_in_txt[copy_len] = 0;
-                        
-                        state = TalState::CollectingDuration;
-                        n = 0;
-                        
-                        // println!("DEBUG: 完成onset字段: '{}', 开始duration", onset_str);
+                        time_in_txt.extend_from_slice(&field);
+                        field.clear();
+                        state = if byte == 20 { TalState::CollectingDescription } else { TalState::CollectingDuration };
                     } else {
-                        // 收集onset字符
-                        if n < scratchpad.len() - 1 {
-                            scratchpad[n] = byte;
-                            n += 1;
-                        }
+                        field.push(byte);
                     }
-                    k += 1;
                 }
                 
                 TalState::CollectingDuration => {
-                    if byte == 20 { // Duration分隔符（转向描述）
-                        // 完成duration收集
-                        scratchpad[n] = 0;
-                        let duration_str = String::from_utf8_lossy(&scratchpad[0..n]);
-                        
-                        // 验证duration格式
-                        if !Self::is_valid_duration(&duration_str) {
-                            // println!("DEBUG: 无效的duration格式: '{}'", duration_str);
-                            break;
+                    if byte == 20 { // Duration结束，转向描述
+                        if !Self::is_valid_duration(&String::from_utf8_lossy(&field)) {
+                            return Err(error(tal_start, format!("invalid duration '{}'", String::from_utf8_lossy(&field))));
                         }
-                        
-                        // 保存duration
-                        let copy_len = n.min(duration_in_txt.len() - 1);
-                        duration_in_txt[..copy_len].copy_from_slice(&scratchpad[..copy_len]);
-                        duration_in_txt[copy_len] = 0;
-                        
-                        duration = true;
+                        duration_in_txt = Some(std::mem::take(&mut field));
                         state = TalState::CollectingDescription;
-                        n = 0;
-                        
-                        // println!("DEBUG: 完成duration字段: '{}'", duration_str);
                     } else if byte == 21 {
-                        // 不允许在duration状态下再次遇到duration分隔符
-                        // println!("DEBUG: 错误 - 多个duration字段");
-                        break;
+                        return Err(error(tal_start, "second 0x15 duration separator in the TAL".to_string()));
                     } else {
-                        // 收集duration字符
-                        if n < scratchpad.len() - 1 {
-                            scratchpad[n] = byte;
-                            n += 1;
-                        }
+                        field.push(byte);
                     }
-                    k += 1;
                 }
                 
                 TalState::CollectingDescription => {
-                    if byte == 20 { // 描述结束分隔符
-                        // 完成一个注释的收集
-                        let description = if n > 0 {
-                            String::from_utf8_lossy(&scratchpad[0..n]).to_string()
-                        } else {
-                            String::new()
-                        };
-                        
-                        // println!("DEBUG: 描述字段结束，描述='{}', 记录中的注释数={}", description, annots_in_record);
+                    if byte == 20 { // 描述结束；同一TAL中可以有多个描述
+                        let description = String::from_utf8_lossy(&field).to_string();
+                        field.clear();
                         
                         // 根据EDF+标准，时间戳注释（timestamp annotations）有空描述
-                        // 且通常在每个数据记录的开头。用户注释即使描述为空也应该保留
-                        let is_timestamp_annotation = is_first_annotation_signal && 
-                                                       annots_in_record == 0 && 
-                                                       description.is_empty();
-                        
-                        // println!("DEBUG: 是时间戳注释={}, 是第一个注释信号={}, 记录中注释数={}", 
-                        //         is_timestamp_annotation, is_first_annotation_signal, annots_in_record);
-                        
-                        if !is_timestamp_annotation {
-                            let time_str = String::from_utf8_lossy(&time_in_txt)
-                                .trim_end_matches('\0').to_string();
-                            
-                            if let Ok(onset_seconds) = time_str.parse::<f64>() {
-                                // 计算绝对时间戳
-                                let onset_time = (onset_seconds * EDFLIB_TIME_DIMENSION as f64) as i64;
-                                
-                                // 从注释时间戳中减去文件的 starttime_offset（类似 edflib）
-                                let adjusted_onset = onset_time - self.header.starttime_subsecond;
-                                
-                                let duration_time = if duration {
-                                    let duration_str = String::from_utf8_lossy(&duration_in_txt)
-                                        .trim_end_matches('\0').to_string();
-                                    if let Ok(duration_seconds) = duration_str.parse::<f64>() {
-                                        (duration_seconds * EDFLIB_TIME_DIMENSION as f64) as i64
-                                    } else {
-                                        -1
-                                    }
-                                } else {
-                                    -1
-                                };
-                                
-                                annotations.push(Annotation {
-                                    onset: adjusted_onset,
-                                    duration: duration_time,
-                                    description,
-                                });
-                                
-                                // println!("DEBUG: 添加注释 - onset={:.3}s, duration={:?}ms, 描述='{}'",
-                                        // onset_seconds, 
-                                        // if duration_time >= 0 { Some(duration_time as f64 / EDFLIB_TIME_DIMENSION as f64) } else { None },
-                                        // annotations.last().unwrap().description);
-                            } else {
-                                // println!("DEBUG: 无法解析onset时间: '{}'", time_str);
-                            }
-                        } else {
-                            // println!("DEBUG: 跳过时间戳注释");
+                        // 且在每个数据记录的开头。用户注释即使描述为空也应该保留
+                        let is_timestamp_annotation = is_first_annotation_signal
+                            && annots_in_record == 0
+                            && description.is_empty();
+                        annots_in_record += 1;
+                        descriptions_in_tal += 1;
+                        if is_timestamp_annotation {
+                            continue;
                         }
                         
-                        annots_in_record += 1;
-                        _annots_in_tal += 1;
+                        // 按十进制文本精确解析为100纳秒单位
+                        let time_str = String::from_utf8_lossy(&time_in_txt);
+                        let onset_time = parse_edf_time(&time_str)
+                            .map_err(|_| error(tal_start, format!("onset '{}' is out of range", time_str)))?;
+                        let duration_time = match &duration_in_txt {
+                            Some(duration) => {
+                                let duration = String::from_utf8_lossy(duration);
+                                parse_edf_time(&duration)
+                                    .map_err(|_| error(tal_start, format!("duration '{}' is out of range", duration)))?
+                            }
+                            None => -1,
+                        };
                         
-                        // 重置状态变量以处理下一个注释
-                        state = TalState::WaitingForOnset;
-                        duration = false;
-                        n = 0;
-                        // 清理缓冲区
-                        scratchpad.fill(0);
-                        time_in_txt.fill(0);
-                        duration_in_txt.fill(0);
+                        // 从注释时间戳中减去文件的 starttime_offset（类似 edflib）
+                        annotations.push(Annotation {
+                            onset: onset_time.saturating_sub(starttime_subsecond),
+                            duration: duration_time,
+                            description,
+                        });
                     } else if byte == 21 {
-                        // 在描述状态下不应该遇到duration分隔符
-                        break;
+                        return Err(error(tal_start, "0x15 duration separator in an annotation description".to_string()));
                     } else {
-                        // 收集描述字符
-                        if n < scratchpad.len() - 1 {
-                            scratchpad[n] = byte;
-                            n += 1;
-                        }
+                        field.push(byte);
                     }
-                    k += 1;
                 }
             }
         }
         
+        if state != TalState::WaitingForOnset {
+            return Err(error(tal_start, "TAL is not terminated by 0x14 0x00 before the end of the annotation signal".to_string()));
+        }
         Ok(annotations)
     }
 
-
-
     // 添加辅助验证函数
     fn is_valid_onset(s: &str) -> bool {
         if s.is_empty() {
@@ -1567,8 +2216,8 @@ impl EdfReader {
     }
     
     /// 计算注释数量并解析subsecond时间（如果存在）
-    fn count_annotations_and_parse_subsecond(
-        reader: &mut BufReader<File>,
+    fn count_annotations_and_parse_subsecond<R: Read + Seek>(
+        reader: &mut R,
         signal_info: &[SignalInfo],
         datarecords: i64,
         record_size: usize,
@@ -1789,48 +2438,92 @@ impl EdfReader {
             }
         }
         
-        // 在第一个记录中尝试提取subsecond信息
-        if is_first_record {
-            subsecond = Self::extract_subsecond_from_tal(data);
+        // 在第一个记录中尝试提取subsecond信息（只有第一个注释信号含时间戳）
+        if is_first_record && is_first_annotation_signal {
+            subsecond = Self::first_tal_time(data)
+                .map_or(0, |timestamp| timestamp.rem_euclid(EDFLIB_TIME_DIMENSION));
         }
         
         Ok((count, subsecond))
     }
+}
 
-    /// 从TAL数据中提取subsecond时间信息
-    fn extract_subsecond_from_tal(data: &[u8]) -> i64 {
-        // 寻找第一个时间戳
-        let mut k = 0;
-        let mut n = 0;
-        let mut scratchpad = vec![0u8; 64];
-        
-        while k < data.len() - 1 {
-            let byte = data[k];
-            
-            if byte == 0 {
-                break;
-            }
-            
-            if byte == 20 { // TAL分隔符
-                scratchpad[n] = 0;
-                let time_str = String::from_utf8_lossy(&scratchpad[0..n]);
-                let time_str = time_str.trim_start_matches('+');
-                
-                if let Ok(timestamp) = time_str.parse::<f64>() {
-                    let timestamp_units = (timestamp * EDFLIB_TIME_DIMENSION as f64) as i64;
-                    return timestamp_units % EDFLIB_TIME_DIMENSION;
-                }
-                break;
-            }
-            
-            if n < scratchpad.len() - 1 {
-                scratchpad[n] = byte;
-                n += 1;
+thread_local! {
+    // 位置读取的字节缓冲，每个线程复用一个
+    static AT_SCRATCH: std::cell::RefCell<Vec<u8>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// 使用当前线程的位置读取缓冲
+fn with_scratch<R>(f: impl FnOnce(&mut Vec<u8>) -> R) -> R {
+    AT_SCRATCH.with(|scratch| f(&mut scratch.borrow_mut()))
+}
+
+/// 位置读取（pread），不使用也不改变文件游标
+#[cfg(unix)]
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+    use std::os::unix::fs::FileExt;
+    file.read_exact_at(buf, offset)
+}
+
+/// 位置读取（Windows 的 seek_read 会移动游标，但所有游标读取前都会重新 seek）
+#[cfg(windows)]
+fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> std::io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    while !buf.is_empty() {
+        match file.seek_read(buf, offset) {
+            Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => {
+                buf = &mut buf[n..];
+                offset += n as u64;
             }
-            
-            k += 1;
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
         }
-        
-        0
+    }
+    Ok(())
+}
+
+/// 位置读取的通用实现：加锁后 seek 再读取，保证并发读取互不干扰
+/// （会移动游标，但所有游标读取前都会重新 seek）
+#[cfg(not(any(unix, windows)))]
+fn read_exact_at(mut file: &File, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+    static SEEK_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let _guard = SEEK_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(buf)
+}
+
+/// 主头部字段错误
+fn header_error(
+    field: &'static str,
+    signal: Option<usize>,
+    bytes: std::ops::Range<usize>,
+    header: &[u8],
+    reason: impl Into<String>,
+) -> EdfError {
+    EdfError::InvalidHeader {
+        field,
+        signal,
+        raw: String::from_utf8_lossy(&header[bytes.clone()]).into_owned(),
+        bytes,
+        reason: reason.into(),
+    }
+}
+
+/// 信号头部字段错误（start 为字段在信号头部中的偏移，数值字段均为8字节）
+fn signal_error(
+    field: &'static str,
+    signal: usize,
+    start: usize,
+    signal_header: &[u8],
+    reason: impl Into<String>,
+) -> EdfError {
+    EdfError::InvalidHeader {
+        field,
+        signal: Some(signal),
+        raw: String::from_utf8_lossy(&signal_header[start..start + 8]).into_owned(),
+        // 信号头部紧跟在256字节的主头部之后
+        bytes: 256 + start..256 + start + 8,
+        reason: reason.into(),
     }
 }
@@ -0,0 +1,364 @@
+use std::f64::consts::PI;
+
+use crate::error::{EdfError, Result};
+use crate::reader::EdfReader;
+use crate::EDFLIB_TIME_DIMENSION;
+
+/// 多相滤波器表中每个源样本间隔的相位数（相位之间线性插值）
+const PHASES: usize = 64;
+
+/// 多相滤波器每侧抽头数的上限，限制滤波器表的内存和每个输出样本的计算量
+const MAX_HALF_TAPS: f64 = 4096.0;
+
+/// How [`EdfReader::resample`] computes samples at the target rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResampleMethod {
+    /// The nearest source sample (no anti-aliasing)
+    Nearest,
+    /// Linear interpolation between the two neighbouring source samples (no
+    /// anti-aliasing; suited to upsampling slow signals such as SpO2)
+    Linear,
+    /// Polyphase windowed-sinc filter with this many zero crossings on each
+    /// side. The low-pass cutoff is the lower of the two Nyquist frequencies,
+    /// so downsampling is anti-aliased; more zero crossings give a sharper
+    /// cutoff at a higher cost.
+    ///
+    /// When downsampling, the kernel is widened by the rate ratio, giving
+    /// `zero_crossings * source rate / target rate` taps on each side. At
+    /// most 4096 taps per side are allowed (a ratio of 256 with the default
+    /// 16 zero crossings); resample in several steps for larger ratios.
+    Polyphase {
+        /// Zero crossings of the sinc kernel on each side (at least 1)
+        zero_crossings: usize,
+    },
+}
+
+impl Default for ResampleMethod {
+    fn default() -> Self {
+        ResampleMethod::Polyphase { zero_crossings: 16 }
+    }
+}
+
+/// Options for [`EdfReader::resample`]
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::{ResampleMethod, ResampleOptions};
+///
+/// // Every signal at 128 Hz, anti-aliased
+/// let model_input = ResampleOptions::new(128.0);
+///
+/// // SpO2 and pulse upsampled to 32 Hz by linear interpolation
+/// let oximetry = ResampleOptions::new(32.0)
+///     .signals(&[4, 5])
+///     .method(ResampleMethod::Linear);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResampleOptions {
+    rate: f64,
+    method: ResampleMethod,
+    signals: Option<Vec<usize>>,
+}
+
+impl ResampleOptions {
+    /// Resamples all signals to `rate_hz` with the default polyphase filter
+    pub fn new(rate_hz: f64) -> Self {
+        ResampleOptions { rate: rate_hz, method: ResampleMethod::default(), signals: None }
+    }
+
+    /// Sets the resampling method
+    pub fn method(mut self, method: ResampleMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Restricts the output to these signals, in this order
+    pub fn signals(mut self, signals: &[usize]) -> Self {
+        self.signals = Some(signals.to_vec());
+        self
+    }
+}
+
+/// Signals of an open file at a common sampling rate
+///
+/// Created by [`EdfReader::resample`]. Output sample `k` of every selected
+/// signal lies at `k / rate` seconds after the first sample of the file, so
+/// all signals share one time base. The time base counts data time only:
+/// gaps between the data records of an EDF+D file are not filled.
+///
+/// Each output sample depends only on the source samples around it, read
+/// across data record boundaries, so reading in chunks of any size gives
+/// exactly the same values as reading everything at once. Beyond the ends
+/// of a signal the first and last samples are repeated.
+pub struct Resampler<'a> {
+    reader: &'a EdfReader,
+    rate: f64,
+    signals: Vec<usize>,
+    kernels: Vec<Kernel>,
+    samples_in_file: i64,
+    position: i64,
+}
+
+/// 单个信号的插值方式：源样本与输出样本的比例及多相滤波器表
+#[derive(Debug, Clone)]
+struct Kernel {
+    /// 每个输出样本对应的源样本数
+    ratio: f64,
+    /// 源信号的样本数
+    source_len: i64,
+    method: ResampleMethod,
+    /// 每侧的抽头数，第 p 行为小数偏移 p / PHASES 时的权重
+    half: i64,
+    phases: Vec<Vec<f64>>,
+}
+
+impl EdfReader {
+    /// Reads signals resampled to a common rate
+    ///
+    /// Every selected signal is resampled from its own rate (samples per
+    /// record / record duration) to `rate` Hz with the configured
+    /// [`ResampleMethod`]. The resampler reads with the positional methods,
+    /// so it leaves the per-signal read positions untouched and several
+    /// resamplers can share one reader.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - The rate is not positive and finite, the
+    ///   polyphase filter has no zero crossings or would need more than 4096
+    ///   taps per side (see [`ResampleMethod::Polyphase`]), or the data record
+    ///   duration is 0
+    /// * `EdfError::InvalidSignalIndex` - A selected signal does not exist
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{EdfReader, ResampleOptions};
+    ///
+    /// # edfplus::doctest_utils::create_multi_channel_test_file("mixed_rates.edf")?;
+    /// let reader = EdfReader::open("mixed_rates.edf")?;
+    /// let mut resampler = reader.resample(&ResampleOptions::new(100.0))?;
+    ///
+    /// // Stream 30 s windows of every signal at 100 Hz
+    /// loop {
+    ///     let window = resampler.read(3000)?;
+    ///     if window[0].is_empty() {
+    ///         break;
+    ///     }
+    ///     assert!(window.iter().all(|signal| signal.len() == window[0].len()));
+    /// }
+    /// assert_eq!(resampler.tell(), resampler.samples_in_file());
+    ///
+    /// # std::fs::remove_file("mixed_rates.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn resample(&self, options: &ResampleOptions) -> Result<Resampler<'_>> {
+        let rate = options.rate;
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(EdfError::InvalidFormat(format!("Resampling rate must be positive, got {} Hz", rate)));
+        }
+        if options.method == (ResampleMethod::Polyphase { zero_crossings: 0 }) {
+            return Err(EdfError::InvalidFormat("Polyphase filter needs at least one zero crossing".to_string()));
+        }
+        let header = self.header();
+        if header.datarecord_duration <= 0 {
+            return Err(EdfError::InvalidFormat("Cannot resample a file with a data record duration of 0".to_string()));
+        }
+
+        let signal_count = header.signals.len();
+        let signals = options.signals.clone().unwrap_or_else(|| (0..signal_count).collect());
+        if let Some(&invalid) = signals.iter().find(|&&s| s >= signal_count) {
+            return Err(EdfError::InvalidSignalIndex(invalid));
+        }
+
+        let record_seconds = header.datarecord_duration as f64 / EDFLIB_TIME_DIMENSION as f64;
+        let records = header.datarecords_in_file.max(0);
+        let kernels = signals.iter()
+            .map(|&signal| {
+                let samples_per_record = header.signals[signal].samples_per_record as i64;
+                let source_rate = samples_per_record as f64 / record_seconds;
+                let ratio = source_rate / rate;
+                if let ResampleMethod::Polyphase { zero_crossings } = options.method {
+                    let half = zero_crossings as f64 * ratio.max(1.0);
+                    if half > MAX_HALF_TAPS {
+                        return Err(EdfError::InvalidFormat(format!(
+                            "Resampling signal {} from {} Hz to {} Hz needs a polyphase filter with {} taps per side (at most {}); \
+                             resample in several steps or use fewer zero crossings",
+                            signal, source_rate, rate, half.ceil(), MAX_HALF_TAPS
+                        )));
+                    }
+                }
+                Ok(Kernel::new(ratio, samples_per_record * records, options.method))
+            })
+            .collect::<Result<_>>()?;
+        // 时间小于数据总时长的输出样本
+        let samples_in_file = ((records as f64 * record_seconds * rate) - 1e-9).ceil().max(0.0) as i64;
+
+        Ok(Resampler { reader: self, rate, signals, kernels, samples_in_file, position: 0 })
+    }
+}
+
+impl Resampler<'_> {
+    /// Output sampling rate in Hz
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Indices of the resampled signals, in output order
+    pub fn signals(&self) -> &[usize] {
+        &self.signals
+    }
+
+    /// Number of output samples per signal
+    pub fn samples_in_file(&self) -> i64 {
+        self.samples_in_file
+    }
+
+    /// Reads output samples `start..start + count` of every selected signal
+    ///
+    /// Fewer samples are returned at the end of the file.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - `start` is negative
+    /// * `EdfError::Io` - I/O error reading from file
+    pub fn read_at(&self, start: i64, count: usize) -> Result<Vec<Vec<f64>>> {
+        (0..self.signals.len()).map(|i| self.read_signal_at(i, start, count)).collect()
+    }
+
+    /// Reads output samples of one selected signal
+    ///
+    /// `signal` is the position in [`signals`](Self::signals), not the
+    /// signal index in the file.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidSignalIndex` - `signal` is not a selected position
+    /// * `EdfError::InvalidFormat` - `start` is negative
+    /// * `EdfError::Io` - I/O error reading from file
+    pub fn read_signal_at(&self, signal: usize, start: i64, count: usize) -> Result<Vec<f64>> {
+        let source = self.signals.get(signal).copied().ok_or(EdfError::InvalidSignalIndex(signal))?;
+        self.read_signal_from(signal, start, count, |first, count| self.reader.read_physical_at(source, first, count))
+    }
+
+    /// 与 read_signal_at 相同，但源样本由 read_source(first, count) 提供（例如已滤波的信号）
+    pub(crate) fn read_signal_from(
+        &self,
+        signal: usize,
+        start: i64,
+        count: usize,
+        read_source: impl FnOnce(i64, usize) -> Result<Vec<f64>>,
+    ) -> Result<Vec<f64>> {
+        let kernel = self.kernels.get(signal).ok_or(EdfError::InvalidSignalIndex(signal))?;
+        if start < 0 {
+            return Err(EdfError::InvalidFormat(format!("Negative sample position {}", start)));
+        }
+        let count = count.min((self.samples_in_file - start).max(0) as usize);
+        if count == 0 || kernel.source_len == 0 {
+            return Ok(Vec::new());
+        }
+
+        // 一次读取这段输出需要的全部源样本（跨越数据记录边界）
+        let (first, last) = kernel.source_range(start, start + count as i64 - 1);
+        let first = first.clamp(0, kernel.source_len - 1);
+        let last = last.clamp(0, kernel.source_len - 1);
+        let source = read_source(first, (last - first + 1) as usize)?;
+
+        Ok((start..start + count as i64).map(|k| kernel.sample(k, &source, first)).collect())
+    }
+
+    /// Reads the next `count` output samples of every selected signal
+    ///
+    /// Streaming counterpart of [`read_at`](Self::read_at); the position is
+    /// shared by all selected signals.
+    pub fn read(&mut self, count: usize) -> Result<Vec<Vec<f64>>> {
+        let samples = self.read_at(self.position, count)?;
+        self.position += samples.first().map_or(0, |s| s.len() as i64);
+        Ok(samples)
+    }
+
+    /// Sets the output position, clamped to `0..=samples_in_file()`
+    pub fn seek(&mut self, position: i64) -> i64 {
+        self.position = position.clamp(0, self.samples_in_file);
+        self.position
+    }
+
+    /// Current output position
+    pub fn tell(&self) -> i64 {
+        self.position
+    }
+}
+
+impl Kernel {
+    fn new(ratio: f64, source_len: i64, method: ResampleMethod) -> Kernel {
+        let ResampleMethod::Polyphase { zero_crossings } = method else {
+            return Kernel { ratio, source_len, method, half: 1, phases: Vec::new() };
+        };
+
+        // 降采样时按目标奈奎斯特频率截止，核按比例展宽
+        let stretch = ratio.max(1.0);
+        let half = (zero_crossings as f64 * stretch).ceil() as i64;
+        let phases = (0..=PHASES)
+            .map(|p| {
+                let fraction = p as f64 / PHASES as f64;
+                let mut weights: Vec<f64> = (1 - half..=half)
+                    .map(|j| {
+                        let t = j as f64 - fraction;
+                        sinc(t / stretch) * blackman(t / (half as f64 + 1.0))
+                    })
+                    .collect();
+                // 每个相位的权重之和为 1，保证直流增益不变
+                let sum: f64 = weights.iter().sum();
+                weights.iter_mut().for_each(|w| *w /= sum);
+                weights
+            })
+            .collect();
+        Kernel { ratio, source_len, method, half, phases }
+    }
+
+    /// 计算输出样本 first..=last 需要的源样本范围（可能超出信号两端）
+    fn source_range(&self, first: i64, last: i64) -> (i64, i64) {
+        let low = (first as f64 * self.ratio).floor() as i64;
+        let high = (last as f64 * self.ratio).floor() as i64;
+        (low - self.half + 1, high + self.half)
+    }
+
+    /// 输出样本 k；source 为从 offset 开始的源样本，越界时取两端的值
+    fn sample(&self, k: i64, source: &[f64], offset: i64) -> f64 {
+        let at = |j: i64| source[(j.clamp(0, self.source_len - 1) - offset) as usize];
+        let x = k as f64 * self.ratio;
+        let base = x.floor();
+        let fraction = x - base;
+        let base = base as i64;
+
+        match self.method {
+            ResampleMethod::Nearest => at(x.round() as i64),
+            ResampleMethod::Linear => at(base) + fraction * (at(base + 1) - at(base)),
+            ResampleMethod::Polyphase { .. } => {
+                let position = fraction * PHASES as f64;
+                let phase = (position.floor() as usize).min(PHASES - 1);
+                let blend = position - phase as f64;
+                self.phases[phase].iter().zip(&self.phases[phase + 1])
+                    .zip(1 - self.half..=self.half)
+                    .map(|((w0, w1), j)| (w0 + blend * (w1 - w0)) * at(base + j))
+                    .sum()
+            }
+        }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Blackman 窗，x 在 -1..1 之间
+fn blackman(x: f64) -> f64 {
+    if x.abs() >= 1.0 {
+        return 0.0;
+    }
+    0.42 + 0.5 * (PI * x).cos() + 0.08 * (2.0 * PI * x).cos()
+}
@@ -0,0 +1,202 @@
+use crate::error::{EdfError, Result};
+use crate::reader::EdfReader;
+use crate::types::Annotation;
+use crate::utils::{seconds_to_time, time_to_sample};
+
+/// Window around each event for [`EdfReader::event_segments`]
+///
+/// Offsets are in seconds relative to the event onset, so a window from
+/// 200 ms before to 800 ms after the event is `SegmentOptions::new(-0.2, 0.8)`.
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::SegmentOptions;
+///
+/// let erp = SegmentOptions::new(-0.2, 0.8).signals(&[0, 2]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentOptions {
+    start: f64,
+    end: f64,
+    signals: Option<Vec<usize>>,
+}
+
+impl SegmentOptions {
+    /// Window from `start_seconds` to `end_seconds` around each event, over all signals
+    pub fn new(start_seconds: f64, end_seconds: f64) -> Self {
+        SegmentOptions { start: start_seconds, end: end_seconds, signals: None }
+    }
+
+    /// Restricts the segments to these signals, in this order
+    pub fn signals(mut self, signals: &[usize]) -> Self {
+        self.signals = Some(signals.to_vec());
+        self
+    }
+}
+
+/// Signal data around one event, see [`EdfReader::event_segments`]
+#[derive(Debug, Clone)]
+pub struct Segment {
+    /// The annotation the segment is locked to
+    pub event: Annotation,
+    /// Physical samples of each selected signal, in selection order
+    ///
+    /// Every segment has the same number of samples per signal.
+    pub signals: Vec<Vec<f64>>,
+    /// Index of the sample closest to the event onset, per selected signal,
+    /// counted from the start of the signal
+    pub event_samples: Vec<i64>,
+}
+
+/// Where an event's window falls outside the recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentBoundary {
+    /// The window starts before the first sample
+    BeforeStart,
+    /// The window ends after the last sample
+    AfterEnd,
+    /// The window overlaps a gap between the data records of a
+    /// discontinuous (EDF+D) file
+    Gap,
+}
+
+/// An event whose window does not fit in the recording
+#[derive(Debug, Clone)]
+pub struct SkippedEvent {
+    /// The annotation that was skipped
+    pub event: Annotation,
+    /// Which file boundary the window crosses
+    pub boundary: SegmentBoundary,
+}
+
+/// Result of [`EdfReader::event_segments`]
+#[derive(Debug, Clone, Default)]
+pub struct EventSegments {
+    /// Segments of events whose window lies entirely inside the recording
+    pub segments: Vec<Segment>,
+    /// Events too close to the start or end of the file
+    pub skipped: Vec<SkippedEvent>,
+}
+
+impl EdfReader {
+    /// Extracts event-locked signal segments around matching annotations
+    ///
+    /// For each annotation accepted by `filter` (in onset order), reads the
+    /// selected signals from `start` to `end` seconds relative to its onset.
+    /// Per signal the event is mapped to the nearest sample and the window
+    /// bounds to a fixed number of samples, so all segments of a signal have
+    /// the same length and stay aligned with the data even when the file has
+    /// a sub-second start time. In a discontinuous (EDF+D) file events are
+    /// located through [`record_onsets`](Self::record_onsets). Events whose
+    /// window does not fit in the file, or overlaps a gap between data
+    /// records, are reported in [`EventSegments::skipped`].
+    ///
+    /// The per-signal read positions of the selected signals are moved.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - `end` is not after `start`
+    /// * `EdfError::InvalidSignalIndex` - A selected signal does not exist
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{EdfReader, EdfWriter, SegmentOptions, SignalParam};
+    ///
+    /// # let mut writer = EdfWriter::create("erp.edf")?;
+    /// # writer.add_signal(SignalParam::new_eeg("EEG Cz", 250))?;
+    /// # for t in [2.0, 4.5, 7.25] {
+    /// #     writer.add_annotation(t, None, "Stimulus A")?;
+    /// # }
+    /// # for _ in 0..10 { writer.write_samples(&[vec![0.0; 250]])?; }
+    /// # writer.finalize()?;
+    /// let mut reader = EdfReader::open("erp.edf")?;
+    ///
+    /// let result = reader.event_segments(
+    ///     |a| a.matches("Stimulus*"),
+    ///     &SegmentOptions::new(-0.2, 0.8),
+    /// )?;
+    ///
+    /// for segment in &result.segments {
+    ///     // 50 samples before and 200 samples after the stimulus at 250 Hz
+    ///     assert_eq!(segment.signals[0].len(), 250);
+    /// }
+    /// println!("{} events skipped at the file boundaries", result.skipped.len());
+    ///
+    /// # std::fs::remove_file("erp.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn event_segments<F>(&mut self, mut filter: F, options: &SegmentOptions) -> Result<EventSegments>
+    where
+        F: FnMut(&Annotation) -> bool,
+    {
+        if !(options.start.is_finite() && options.end.is_finite()) || options.end <= options.start {
+            return Err(EdfError::InvalidFormat(format!(
+                "Segment window end ({} s) must be after its start ({} s)", options.end, options.start
+            )));
+        }
+        let window_start = seconds_to_time(options.start)?;
+        let window_end = seconds_to_time(options.end)?;
+
+        let signal_count = self.header().signals.len();
+        let signals = options.signals.clone().unwrap_or_else(|| (0..signal_count).collect());
+        if let Some(&invalid) = signals.iter().find(|&&s| s >= signal_count) {
+            return Err(EdfError::InvalidSignalIndex(invalid));
+        }
+
+        // 每个信号的窗口偏移（样本数）与总样本数
+        let record_duration = self.header().datarecord_duration;
+        let records = self.header().datarecords_in_file.max(0);
+        let layout: Vec<(i64, i64, i64)> = signals.iter()
+            .map(|&signal| {
+                let samples_per_record = self.header().signals[signal].samples_per_record as i64;
+                (
+                    time_to_sample(window_start, samples_per_record, record_duration, true),
+                    time_to_sample(window_end, samples_per_record, record_duration, true),
+                    samples_per_record * records,
+                )
+            })
+            .collect();
+
+        let events: Vec<Annotation> = self.annotations().iter().filter(|a| filter(a)).cloned().collect();
+        let mut result = EventSegments::default();
+
+        'events: for event in events {
+            // EDF+D 文件中窗口必须位于连续的数据记录内
+            let in_gap = self.header().discontinuous
+                && !self.is_recorded(event.onset + window_start, event.onset + window_end);
+            let mut event_samples = Vec::with_capacity(signals.len());
+            for (&signal, &(start_offset, end_offset, total)) in signals.iter().zip(&layout) {
+                let event_sample = self.time_to_sample(signal, event.onset);
+                let boundary = if event_sample + start_offset < 0 {
+                    Some(SegmentBoundary::BeforeStart)
+                } else if event_sample + end_offset > total {
+                    Some(SegmentBoundary::AfterEnd)
+                } else if in_gap {
+                    Some(SegmentBoundary::Gap)
+                } else {
+                    None
+                };
+                if let Some(boundary) = boundary {
+                    result.skipped.push(SkippedEvent { event, boundary });
+                    continue 'events;
+                }
+                event_samples.push(event_sample);
+            }
+
+            let mut data = Vec::with_capacity(signals.len());
+            for ((&signal, &(start_offset, end_offset, _)), &event_sample) in
+                signals.iter().zip(&layout).zip(&event_samples)
+            {
+                let mut samples = vec![0.0; (end_offset - start_offset) as usize];
+                self.seek(signal, event_sample + start_offset)?;
+                self.read_physical_into(signal, &mut samples)?;
+                data.push(samples);
+            }
+            result.segments.push(Segment { event, signals: data, event_samples });
+        }
+
+        Ok(result)
+    }
+}
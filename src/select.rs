@@ -0,0 +1,150 @@
+use std::path::Path;
+
+use crate::crop::{CropOptions, CropSummary};
+use crate::error::{EdfError, Result};
+use crate::reader::EdfReader;
+use crate::types::SignalParam;
+
+/// A signal of a file, by index or by label
+///
+/// Indices count ordinary signals only, like the rest of the reader API.
+/// Labels match the trimmed label exactly; the first matching signal is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignalRef {
+    /// Zero-based signal index
+    Index(usize),
+    /// Signal label, e.g. `"EEG Fp1"`
+    Label(String),
+}
+
+impl From<usize> for SignalRef {
+    fn from(index: usize) -> Self {
+        SignalRef::Index(index)
+    }
+}
+
+impl From<&str> for SignalRef {
+    fn from(label: &str) -> Self {
+        SignalRef::Label(label.to_string())
+    }
+}
+
+impl From<String> for SignalRef {
+    fn from(label: String) -> Self {
+        SignalRef::Label(label)
+    }
+}
+
+/// Signals to copy into a new file, in output order, optionally renamed
+///
+/// Used by [`EdfReader::extract_signals`] and [`CropOptions::signals`].
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::SignalSelection;
+///
+/// // Only the EEG channels, C4 before C3, with the reference in the label
+/// let selection = SignalSelection::new()
+///     .renamed("EEG C4", "C4-A1")
+///     .renamed("EEG C3", "C3-A2")
+///     .signal(5);
+///
+/// // From a list of labels or indices
+/// let frontal: SignalSelection = ["EEG Fp1", "EEG Fp2"].into_iter().collect();
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SignalSelection {
+    signals: Vec<(SignalRef, Option<String>)>,
+}
+
+impl SignalSelection {
+    /// An empty selection
+    pub fn new() -> Self {
+        SignalSelection::default()
+    }
+
+    /// Adds a signal under its own label
+    pub fn signal(mut self, signal: impl Into<SignalRef>) -> Self {
+        self.signals.push((signal.into(), None));
+        self
+    }
+
+    /// Adds a signal under a new label
+    pub fn renamed(mut self, signal: impl Into<SignalRef>, label: &str) -> Self {
+        self.signals.push((signal.into(), Some(label.to_string())));
+        self
+    }
+}
+
+impl<S: Into<SignalRef>> FromIterator<S> for SignalSelection {
+    fn from_iter<I: IntoIterator<Item = S>>(signals: I) -> Self {
+        SignalSelection { signals: signals.into_iter().map(|signal| (signal.into(), None)).collect() }
+    }
+}
+
+impl EdfReader {
+    /// Writes selected signals, in the selected order, into a new EDF+ file
+    ///
+    /// Copies the stored digital samples of the selected signals unchanged
+    /// (no requantization, no clamping) and keeps all annotations, the start time and the
+    /// patient identification. A signal may be selected more than once.
+    /// Equivalent to [`crop`](Self::crop) over all data records with
+    /// [`CropOptions::signals`].
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidSignalIndex` - A selected index does not exist
+    /// * `EdfError::InvalidFormat` - The selection is empty, a label does not
+    ///   exist, a new label is not a valid EDF label, or the file has no
+    ///   data records
+    /// * `EdfError::Io` - I/O error reading the source or writing the new file
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{EdfReader, SignalSelection};
+    ///
+    /// # edfplus::doctest_utils::create_multi_channel_test_file("psg.edf")?;
+    /// let reader = EdfReader::open("psg.edf")?;
+    /// let selection = SignalSelection::new().signal(1).renamed(0, "Fp1-Ref");
+    /// reader.extract_signals("psg_subset.edf", &selection)?;
+    ///
+    /// let subset = EdfReader::open("psg_subset.edf")?;
+    /// assert_eq!(subset.header().signals.len(), 2);
+    /// assert_eq!(subset.header().signals[1].label, "Fp1-Ref");
+    ///
+    /// # std::fs::remove_file("psg.edf").ok();
+    /// # std::fs::remove_file("psg_subset.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn extract_signals<P: AsRef<Path>>(&self, path: P, selection: &SignalSelection) -> Result<CropSummary> {
+        let options = CropOptions::records(0..self.header().datarecords_in_file).signals(selection.clone());
+        self.crop(path, &options)
+    }
+
+    /// Source index and output parameters of each selected signal
+    pub(crate) fn resolve_selection(&self, selection: &SignalSelection) -> Result<Vec<(usize, SignalParam)>> {
+        let signals = &self.header().signals;
+        if selection.signals.is_empty() {
+            return Err(EdfError::InvalidFormat("No signals selected".to_string()));
+        }
+
+        selection.signals.iter()
+            .map(|(signal, label)| {
+                let index = match signal {
+                    SignalRef::Index(index) if *index < signals.len() => *index,
+                    SignalRef::Index(index) => return Err(EdfError::InvalidSignalIndex(*index)),
+                    SignalRef::Label(label) => signals.iter().position(|s| s.label == *label)
+                        .ok_or_else(|| EdfError::InvalidFormat(format!("No signal labelled '{}'", label)))?,
+                };
+                let mut param = signals[index].clone();
+                if let Some(label) = label {
+                    param.label = label.clone();
+                    param.validate()?;
+                }
+                Ok((index, param))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,294 @@
+//! Sleep staging helpers: hypnograms from sleep-stage annotations
+//!
+//! Polysomnography files store the scored sleep stages as annotations such as
+//! `"Sleep stage W"`, `"Sleep stage N2"` or, in older R&K scored files,
+//! `"Sleep stage 3"`. [`Hypnogram`] turns these into one stage per epoch and
+//! computes the usual summary statistics.
+//!
+//! ```rust
+//! use edfplus::{EdfReader, EdfWriter, SignalParam};
+//! use edfplus::sleep::{Hypnogram, SleepStage};
+//!
+//! # let mut writer = EdfWriter::create("psg.edf")?;
+//! # writer.add_signal(SignalParam::new_eeg("EEG C3-M2", 100))?;
+//! # writer.add_annotation(0.0, Some(60.0), "Sleep stage W")?;
+//! # writer.add_annotation(60.0, Some(90.0), "Sleep stage N1")?;
+//! # writer.add_annotation(150.0, Some(150.0), "Sleep stage N2")?;
+//! # for _ in 0..300 { writer.write_samples(&[vec![0.0; 100]])?; }
+//! # writer.finalize()?;
+//! let reader = EdfReader::open("psg.edf")?;
+//! let hypnogram = Hypnogram::from_reader(&reader, 30.0)?;
+//!
+//! assert_eq!(hypnogram.stages[0], SleepStage::Wake);
+//! let stats = hypnogram.statistics();
+//! println!("TST {:.0} min, efficiency {:.0}%",
+//!     stats.total_sleep_time as f64 / 600_000_000.0, stats.sleep_efficiency * 100.0);
+//! # std::fs::remove_file("psg.edf").ok();
+//! # Ok::<(), edfplus::EdfError>(())
+//! ```
+
+use crate::epoch::{overlaps, positive_time};
+use crate::error::Result;
+use crate::reader::EdfReader;
+use crate::types::Annotation;
+use crate::writer::EdfWriter;
+use crate::EDFLIB_TIME_DIMENSION;
+
+/// Sleep stage of one epoch
+///
+/// R&K stages 3 and 4 are both mapped to [`SleepStage::N3`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SleepStage {
+    /// Wakefulness
+    Wake,
+    /// Stage N1 (R&K stage 1)
+    N1,
+    /// Stage N2 (R&K stage 2)
+    N2,
+    /// Stage N3 (R&K stages 3 and 4)
+    N3,
+    /// REM sleep
+    Rem,
+    /// Movement time
+    Movement,
+    /// Unscored or unscorable epoch
+    Unknown,
+}
+
+impl SleepStage {
+    /// Recognises a sleep-stage annotation text
+    ///
+    /// Accepts the EDF+ standard texts (`"Sleep stage W"`, `"Sleep stage 1"`
+    /// ... `"Sleep stage 4"`, `"Sleep stage R"`, `"Sleep stage ?"`,
+    /// `"Movement time"`), their AASM variants (`"Sleep stage N1"`,
+    /// `"Sleep stage N3"`, `"Sleep stage REM"`, ...) and the bare stage codes.
+    /// Matching ignores case and treats `_` like a space. Returns `None` for
+    /// annotations that are not sleep stages.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::sleep::SleepStage;
+    ///
+    /// assert_eq!(SleepStage::from_annotation("Sleep stage 4"), Some(SleepStage::N3));
+    /// assert_eq!(SleepStage::from_annotation("Sleep stage N2"), Some(SleepStage::N2));
+    /// assert_eq!(SleepStage::from_annotation("sleep_stage_R"), Some(SleepStage::Rem));
+    /// assert_eq!(SleepStage::from_annotation("Lights off"), None);
+    /// ```
+    pub fn from_annotation(text: &str) -> Option<SleepStage> {
+        let text = text.trim().to_ascii_lowercase().replace('_', " ");
+        if text == "movement time" || text == "movement" {
+            return Some(SleepStage::Movement);
+        }
+        let code = text.strip_prefix("sleep stage").unwrap_or(&text).trim();
+        let stage = match code {
+            "w" | "wake" | "0" => SleepStage::Wake,
+            "1" | "n1" | "s1" => SleepStage::N1,
+            "2" | "n2" | "s2" => SleepStage::N2,
+            "3" | "4" | "n3" | "n4" | "s3" | "s4" => SleepStage::N3,
+            "r" | "rem" => SleepStage::Rem,
+            "mt" => SleepStage::Movement,
+            "?" | "unknown" | "unscored" => SleepStage::Unknown,
+            _ => return None,
+        };
+        Some(stage)
+    }
+
+    /// Annotation text written by [`Hypnogram::write_annotations`]
+    pub fn annotation_text(self) -> &'static str {
+        match self {
+            SleepStage::Wake => "Sleep stage W",
+            SleepStage::N1 => "Sleep stage N1",
+            SleepStage::N2 => "Sleep stage N2",
+            SleepStage::N3 => "Sleep stage N3",
+            SleepStage::Rem => "Sleep stage R",
+            SleepStage::Movement => "Movement time",
+            SleepStage::Unknown => "Sleep stage ?",
+        }
+    }
+
+    /// Returns true for N1, N2, N3 and REM
+    pub fn is_sleep(self) -> bool {
+        matches!(self, SleepStage::N1 | SleepStage::N2 | SleepStage::N3 | SleepStage::Rem)
+    }
+}
+
+/// One sleep stage per fixed-length epoch, starting at the recording start
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hypnogram {
+    /// Epoch length in 100-nanosecond units
+    pub epoch_length: i64,
+    /// Stage of each epoch
+    pub stages: Vec<SleepStage>,
+}
+
+/// Summary statistics of a [`Hypnogram`]
+///
+/// Times are in 100-nanosecond units like the rest of the crate. The time in
+/// bed is the whole hypnogram; the sleep period runs from the first to the
+/// last sleep epoch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SleepStatistics {
+    /// Duration of the hypnogram
+    pub time_in_bed: i64,
+    /// Time from sleep onset to the end of the last sleep epoch
+    pub sleep_period: i64,
+    /// Time in N1, N2, N3 and REM (TST)
+    pub total_sleep_time: i64,
+    /// Total sleep time divided by time in bed (0 when the hypnogram is empty)
+    pub sleep_efficiency: f64,
+    /// Time from the start to the first sleep epoch
+    pub sleep_latency: Option<i64>,
+    /// Time from sleep onset to the first REM epoch
+    pub rem_latency: Option<i64>,
+    /// Wake time within the sleep period (WASO)
+    pub wake_after_sleep_onset: i64,
+    /// Time in wake
+    pub wake: i64,
+    /// Time in N1
+    pub n1: i64,
+    /// Time in N2
+    pub n2: i64,
+    /// Time in N3
+    pub n3: i64,
+    /// Time in REM
+    pub rem: i64,
+    /// Movement time
+    pub movement: i64,
+    /// Unscored time
+    pub unknown: i64,
+}
+
+impl Hypnogram {
+    /// Builds a hypnogram from sleep-stage annotations
+    ///
+    /// Covers `total_duration` (100-nanosecond units) with epochs of
+    /// `epoch_seconds`. An epoch takes the stage of the annotation covering
+    /// its midpoint; stage annotations without a duration cover one epoch.
+    /// Epochs without a stage annotation are [`SleepStage::Unknown`].
+    /// Non-stage annotations are ignored.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - `epoch_seconds` is not positive
+    pub fn from_annotations(annotations: &[Annotation], epoch_seconds: f64, total_duration: i64) -> Result<Hypnogram> {
+        let epoch_length = positive_time(epoch_seconds, "Epoch length")?;
+        let epochs = (total_duration.max(0) + epoch_length - 1) / epoch_length;
+        let mut stages = vec![SleepStage::Unknown; epochs as usize];
+
+        for annotation in annotations {
+            let Some(stage) = SleepStage::from_annotation(&annotation.description) else {
+                continue;
+            };
+            let duration = if annotation.duration > 0 { annotation.duration } else { epoch_length };
+            let scored = Annotation { duration, ..annotation.clone() };
+            for (k, slot) in stages.iter_mut().enumerate() {
+                let midpoint = k as i64 * epoch_length + epoch_length / 2;
+                if overlaps(&scored, midpoint, midpoint + 1) {
+                    *slot = stage;
+                }
+            }
+        }
+
+        Ok(Hypnogram { epoch_length, stages })
+    }
+
+    /// Builds a hypnogram from the annotations of an open file
+    ///
+    /// Same as [`from_annotations`](Self::from_annotations) over the whole
+    /// recording, up to the end of the last data record. Epochs in the gaps
+    /// of a discontinuous (EDF+D) file are unscored unless annotated.
+    pub fn from_reader(reader: &EdfReader, epoch_seconds: f64) -> Result<Hypnogram> {
+        Hypnogram::from_annotations(reader.annotations(), epoch_seconds, reader.recording_end())
+    }
+
+    /// Time spent in `stage`, in 100-nanosecond units
+    pub fn time_in(&self, stage: SleepStage) -> i64 {
+        self.stages.iter().filter(|&&s| s == stage).count() as i64 * self.epoch_length
+    }
+
+    /// Computes the standard sleep statistics
+    pub fn statistics(&self) -> SleepStatistics {
+        let epoch = self.epoch_length;
+        let first_sleep = self.stages.iter().position(|s| s.is_sleep());
+        let last_sleep = self.stages.iter().rposition(|s| s.is_sleep());
+
+        let time_in_bed = self.stages.len() as i64 * epoch;
+        let total_sleep_time = self.stages.iter().filter(|s| s.is_sleep()).count() as i64 * epoch;
+        let (sleep_period, wake_after_sleep_onset) = match (first_sleep, last_sleep) {
+            (Some(first), Some(last)) => {
+                let period = &self.stages[first..=last];
+                let wake = period.iter().filter(|&&s| s == SleepStage::Wake).count() as i64;
+                (period.len() as i64 * epoch, wake * epoch)
+            }
+            _ => (0, 0),
+        };
+        let rem_latency = first_sleep.and_then(|first| {
+            self.stages[first..].iter().position(|&s| s == SleepStage::Rem).map(|k| k as i64 * epoch)
+        });
+
+        SleepStatistics {
+            time_in_bed,
+            sleep_period,
+            total_sleep_time,
+            sleep_efficiency: if time_in_bed > 0 { total_sleep_time as f64 / time_in_bed as f64 } else { 0.0 },
+            sleep_latency: first_sleep.map(|k| k as i64 * epoch),
+            rem_latency,
+            wake_after_sleep_onset,
+            wake: self.time_in(SleepStage::Wake),
+            n1: self.time_in(SleepStage::N1),
+            n2: self.time_in(SleepStage::N2),
+            n3: self.time_in(SleepStage::N3),
+            rem: self.time_in(SleepStage::Rem),
+            movement: self.time_in(SleepStage::Movement),
+            unknown: self.time_in(SleepStage::Unknown),
+        }
+    }
+
+    /// Adds the hypnogram to a file being written, as sleep-stage annotations
+    ///
+    /// Consecutive epochs of the same stage become one annotation with a
+    /// duration, using the texts of [`SleepStage::annotation_text`]. Unscored
+    /// epochs are skipped. Like any annotation, call this before writing the
+    /// data records it covers.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`EdfWriter::add_annotation`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{EdfWriter, SignalParam};
+    /// use edfplus::sleep::{Hypnogram, SleepStage};
+    ///
+    /// let hypnogram = Hypnogram {
+    ///     epoch_length: 300_000_000, // 30 s
+    ///     stages: vec![SleepStage::Wake, SleepStage::N1, SleepStage::N1, SleepStage::N2],
+    /// };
+    ///
+    /// let mut writer = EdfWriter::create("scored.edf")?;
+    /// writer.add_signal(SignalParam::new_eeg("EEG C3-M2", 100))?;
+    /// hypnogram.write_annotations(&mut writer)?;
+    /// assert_eq!(writer.annotation_count(), 3);
+    /// # std::fs::remove_file("scored.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn write_annotations(&self, writer: &mut EdfWriter) -> Result<()> {
+        let to_seconds = |time: i64| time as f64 / EDFLIB_TIME_DIMENSION as f64;
+        let mut start = 0;
+        while start < self.stages.len() {
+            let stage = self.stages[start];
+            let run = self.stages[start..].iter().take_while(|&&s| s == stage).count();
+            if stage != SleepStage::Unknown {
+                writer.add_annotation(
+                    to_seconds(start as i64 * self.epoch_length),
+                    Some(to_seconds(run as i64 * self.epoch_length)),
+                    stage.annotation_text(),
+                )?;
+            }
+            start += run;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use crate::error::{EdfError, Result};
+use crate::reader::EdfReader;
+use crate::EDFLIB_TIME_DIMENSION;
+
+/// Options for [`EdfReader::signal_statistics`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsOptions {
+    /// Minimum duration in seconds of a run of (nearly) constant samples to
+    /// report it as a flat line (default 5 s)
+    pub flat_line_seconds: f64,
+    /// Largest digital deviation from the first sample of a run that still
+    /// counts as flat (default 0, i.e. identical values)
+    pub flat_line_tolerance: i32,
+}
+
+impl Default for StatsOptions {
+    fn default() -> Self {
+        StatsOptions { flat_line_seconds: 5.0, flat_line_tolerance: 0 }
+    }
+}
+
+/// A run of constant samples in one signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlatLine {
+    /// Index of the first sample of the run
+    pub start: i64,
+    /// Number of samples in the run
+    pub length: u64,
+}
+
+/// Summary statistics and quality indicators of one signal
+///
+/// Digital extremes and the out-of-range counts use the values as stored in
+/// the file; everything physical uses the values the reader returns, i.e.
+/// clamped to the declared digital range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalStatistics {
+    /// Signal label
+    pub label: String,
+    /// Number of samples
+    pub samples: u64,
+    /// Smallest stored digital value
+    pub digital_min: i32,
+    /// Largest stored digital value
+    pub digital_max: i32,
+    /// Smallest physical value
+    pub physical_min: f64,
+    /// Largest physical value
+    pub physical_max: f64,
+    /// Mean physical value
+    pub mean: f64,
+    /// Root mean square of the physical values
+    pub rms: f64,
+    /// Samples exactly at the declared `digital_min` (negative saturation)
+    pub at_digital_min: u64,
+    /// Samples exactly at the declared `digital_max` (positive saturation)
+    pub at_digital_max: u64,
+    /// Samples stored below the declared `digital_min` (clamped when read)
+    pub below_range: u64,
+    /// Samples stored above the declared `digital_max` (clamped when read)
+    pub above_range: u64,
+    /// Flat-line intervals longer than [`StatsOptions::flat_line_seconds`]
+    pub flat_lines: Vec<FlatLine>,
+}
+
+impl SignalStatistics {
+    /// Fraction of samples at either end of the digital range
+    pub fn saturation_ratio(&self) -> f64 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        (self.at_digital_min + self.at_digital_max + self.below_range + self.above_range) as f64
+            / self.samples as f64
+    }
+
+    /// Total number of samples in flat-line intervals
+    pub fn flat_line_samples(&self) -> u64 {
+        self.flat_lines.iter().map(|f| f.length).sum()
+    }
+}
+
+/// 单个信号的流式累加状态
+struct Accumulator {
+    stats: SignalStatistics,
+    declared: (i32, i32),
+    gain: f64,
+    offset: f64,
+    sum: f64,
+    sum_squares: f64,
+    min_flat: u64,
+    tolerance: i32,
+    run_value: i32,
+    run_start: i64,
+    run_length: u64,
+}
+
+impl Accumulator {
+    fn push(&mut self, raw: i32) {
+        let (declared_min, declared_max) = self.declared;
+        let position = self.stats.samples as i64;
+        self.stats.samples += 1;
+        self.stats.digital_min = self.stats.digital_min.min(raw);
+        self.stats.digital_max = self.stats.digital_max.max(raw);
+        if raw < declared_min {
+            self.stats.below_range += 1;
+        } else if raw > declared_max {
+            self.stats.above_range += 1;
+        } else if raw == declared_min {
+            self.stats.at_digital_min += 1;
+        } else if raw == declared_max {
+            self.stats.at_digital_max += 1;
+        }
+
+        let physical = self.gain * raw.max(declared_min).min(declared_max) as f64 + self.offset;
+        self.stats.physical_min = self.stats.physical_min.min(physical);
+        self.stats.physical_max = self.stats.physical_max.max(physical);
+        self.sum += physical;
+        self.sum_squares += physical * physical;
+
+        // 平直段检测：与当前段首值的偏差不超过容差
+        if self.run_length > 0 && (raw - self.run_value).abs() <= self.tolerance {
+            self.run_length += 1;
+        } else {
+            self.close_run();
+            self.run_value = raw;
+            self.run_start = position;
+            self.run_length = 1;
+        }
+    }
+
+    fn close_run(&mut self) {
+        if self.run_length >= self.min_flat {
+            self.stats.flat_lines.push(FlatLine { start: self.run_start, length: self.run_length });
+        }
+        self.run_length = 0;
+    }
+
+    fn finish(mut self) -> SignalStatistics {
+        self.close_run();
+        if self.stats.samples > 0 {
+            let n = self.stats.samples as f64;
+            self.stats.mean = self.sum / n;
+            self.stats.rms = (self.sum_squares / n).sqrt();
+        } else {
+            self.stats.digital_min = 0;
+            self.stats.digital_max = 0;
+            self.stats.physical_min = 0.0;
+            self.stats.physical_max = 0.0;
+        }
+        self.stats
+    }
+}
+
+impl EdfReader {
+    /// Computes summary statistics and quality indicators for every signal
+    ///
+    /// Reads the whole file once, record by record, so memory use does not
+    /// depend on the file size. Reports extremes, mean and RMS, saturation at
+    /// the declared digital limits, stored values outside the declared range,
+    /// and flat-line intervals (see [`StatsOptions`]).
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - `flat_line_seconds` is not a positive finite number
+    /// * `EdfError::Io` - I/O error reading from file
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{EdfReader, StatsOptions};
+    ///
+    /// # edfplus::doctest_utils::create_simple_test_file("intake.edf")?;
+    /// let mut reader = EdfReader::open("intake.edf")?;
+    ///
+    /// for stats in reader.signal_statistics(&StatsOptions::default())? {
+    ///     println!("{}: {:.1}..{:.1}, mean {:.2}, RMS {:.2}", stats.label,
+    ///         stats.physical_min, stats.physical_max, stats.mean, stats.rms);
+    ///     if stats.saturation_ratio() > 0.01 || !stats.flat_lines.is_empty() {
+    ///         println!("  flagged for review");
+    ///     }
+    /// }
+    ///
+    /// # std::fs::remove_file("intake.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn signal_statistics(&mut self, options: &StatsOptions) -> Result<Vec<SignalStatistics>> {
+        if !options.flat_line_seconds.is_finite() || options.flat_line_seconds <= 0.0 {
+            return Err(EdfError::InvalidFormat(format!(
+                "Flat-line duration must be a positive number of seconds, got {}",
+                options.flat_line_seconds
+            )));
+        }
+
+        let record_seconds = self.header().datarecord_duration as f64 / EDFLIB_TIME_DIMENSION as f64;
+        let mut accumulators: Vec<Accumulator> = self.header().signals.iter()
+            .map(|signal| {
+                let rate = signal.samples_per_record as f64 / record_seconds;
+                let gain = signal.bit_value();
+                Accumulator {
+                    stats: SignalStatistics {
+                        label: signal.label.clone(),
+                        samples: 0,
+                        digital_min: i32::MAX,
+                        digital_max: i32::MIN,
+                        physical_min: f64::INFINITY,
+                        physical_max: f64::NEG_INFINITY,
+                        mean: 0.0,
+                        rms: 0.0,
+                        at_digital_min: 0,
+                        at_digital_max: 0,
+                        below_range: 0,
+                        above_range: 0,
+                        flat_lines: Vec::new(),
+                    },
+                    declared: (signal.digital_min, signal.digital_max),
+                    gain,
+                    offset: gain * signal.offset(),
+                    sum: 0.0,
+                    sum_squares: 0.0,
+                    min_flat: ((options.flat_line_seconds * rate).ceil() as u64).max(1),
+                    tolerance: options.flat_line_tolerance.max(0),
+                    run_value: 0,
+                    run_start: 0,
+                    run_length: 0,
+                }
+            })
+            .collect();
+
+        // 读取未限制范围的原始值，之后恢复原设置
+        let clamp_digital = self.clamp_digital;
+        self.clamp_digital = false;
+        let mut result = Ok(());
+        for record in self.records() {
+            match record {
+                Ok(record) => {
+                    for (accumulator, samples) in accumulators.iter_mut().zip(record.signals()) {
+                        samples.iter().for_each(|&raw| accumulator.push(raw));
+                    }
+                }
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        self.clamp_digital = clamp_digital;
+        result?;
+
+        Ok(accumulators.into_iter().map(Accumulator::finish).collect())
+    }
+}
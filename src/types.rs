@@ -1,4 +1,8 @@
-use chrono::{NaiveDate, NaiveTime};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+
+use crate::error::{EdfError, Result};
+use crate::EDFLIB_TIME_DIMENSION;
+use crate::utils::{encode_header_bound, encode_header_number, seconds_to_time, time_to_sample};
 
 /// Supported EDF file types
 /// 
@@ -250,6 +254,511 @@ impl SignalParam {
         let digital = (physical_value / self.bit_value()) - self.offset();
         digital.round() as i32
     }
+
+    /// Starts a builder for a signal with the given label
+    ///
+    /// The builder starts from a generic ±1 unit signal covering the full
+    /// 16-bit digital range at 1 sample per record. Use a modality preset
+    /// such as [`SignalParamBuilder::eeg`] for sensible defaults instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::SignalParam;
+    ///
+    /// let temperature = SignalParam::builder("Temp rectal")
+    ///     .samples_per_record(1)
+    ///     .physical_range(30.0, 42.0)
+    ///     .physical_dimension("degC")
+    ///     .transducer("Thermistor probe")
+    ///     .build()?;
+    ///
+    /// assert_eq!(temperature.digital_max, 32767);
+    /// assert_eq!(temperature.samples_in_file, 0);
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn builder(label: &str) -> SignalParamBuilder {
+        SignalParamBuilder::new(label)
+    }
+
+    /// Creates an EEG signal with default ranges (±500 µV)
+    ///
+    /// Shorthand for `SignalParamBuilder::eeg(label, samples_per_record)`
+    /// without validation; [`EdfWriter::add_signal`](crate::EdfWriter::add_signal)
+    /// validates the result.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::SignalParam;
+    ///
+    /// let fp1 = SignalParam::new_eeg("EEG Fp1", 256);
+    /// assert_eq!(fp1.physical_dimension, "uV");
+    /// assert_eq!(fp1.physical_max, 500.0);
+    /// ```
+    pub fn new_eeg(label: &str, samples_per_record: i32) -> SignalParam {
+        SignalParamBuilder::eeg(label, samples_per_record).signal
+    }
+
+    /// Creates an ECG signal with default ranges (±5 mV)
+    pub fn new_ecg(label: &str, samples_per_record: i32) -> SignalParam {
+        SignalParamBuilder::ecg(label, samples_per_record).signal
+    }
+
+    /// Creates an EMG signal with default ranges (±5000 µV)
+    pub fn new_emg(label: &str, samples_per_record: i32) -> SignalParam {
+        SignalParamBuilder::emg(label, samples_per_record).signal
+    }
+
+    /// Creates an EOG signal with default ranges (±1000 µV)
+    pub fn new_eog(label: &str, samples_per_record: i32) -> SignalParam {
+        SignalParamBuilder::eog(label, samples_per_record).signal
+    }
+
+    /// Creates a respiration signal with default ranges (±100 arbitrary units)
+    pub fn new_respiration(label: &str, samples_per_record: i32) -> SignalParam {
+        SignalParamBuilder::respiration(label, samples_per_record).signal
+    }
+
+    /// Creates an SpO2 signal with default ranges (0-100 %)
+    pub fn new_spo2(label: &str, samples_per_record: i32) -> SignalParam {
+        SignalParamBuilder::spo2(label, samples_per_record).signal
+    }
+
+    /// Checks that the signal can be stored in an EDF+ header
+    ///
+    /// Validates the fixed-width text fields (label 16, transducer 80,
+    /// physical dimension 8, prefilter 80 characters, printable ASCII only),
+    /// that the physical range can be written as 8-character numbers, the
+    /// digital range (must fit in a 16-bit sample and `digital_min` must
+    /// be below `digital_max`), the physical range and the number of samples
+    /// per data record.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - A text field is too long or not printable ASCII,
+    ///   the label is reserved, a physical limit needs more than 8 characters
+    ///   even after rounding, the digital range does not fit in 16 bits,
+    ///   or `samples_per_record` is not positive
+    /// * `EdfError::PhysicalMinEqualsMax` - Invalid physical range
+    /// * `EdfError::DigitalMinEqualsMax` - Invalid digital range
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::SignalParam;
+    ///
+    /// let mut signal = SignalParam::new_eeg("EEG Fp1", 256);
+    /// assert!(signal.validate().is_ok());
+    ///
+    /// // Would be silently truncated to 16 bits
+    /// signal.digital_max = 100_000;
+    /// assert!(signal.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        check_header_text("label", &self.label, SIGNAL_LABEL_LEN)?;
+        check_header_text("transducer", &self.transducer, SIGNAL_TRANSDUCER_LEN)?;
+        check_header_text("physical dimension", &self.physical_dimension, SIGNAL_DIMENSION_LEN)?;
+        check_header_text("prefilter", &self.prefilter, SIGNAL_PREFILTER_LEN)?;
+
+        if self.label.trim_end() == "EDF Annotations" {
+            return Err(EdfError::InvalidFormat(
+                "Signal label 'EDF Annotations' is reserved for annotation signals".to_string()
+            ));
+        }
+
+        if !self.physical_min.is_finite() || !self.physical_max.is_finite() {
+            return Err(EdfError::InvalidFormat(format!(
+                "Signal '{}' physical range must be finite", self.label
+            )));
+        }
+        if self.physical_min == self.physical_max {
+            return Err(EdfError::PhysicalMinEqualsMax);
+        }
+        encode_header_number(self.physical_min, 8)?;
+        encode_header_number(self.physical_max, 8)?;
+        if self.digital_min == self.digital_max {
+            return Err(EdfError::DigitalMinEqualsMax);
+        }
+
+        let digital_range = i16::MIN as i32..=i16::MAX as i32;
+        if !digital_range.contains(&self.digital_min) || !digital_range.contains(&self.digital_max) {
+            return Err(EdfError::InvalidFormat(format!(
+                "Signal '{}' digital range {}..{} does not fit in 16-bit samples ({}..{})",
+                self.label, self.digital_min, self.digital_max, i16::MIN, i16::MAX
+            )));
+        }
+        if self.digital_min > self.digital_max {
+            return Err(EdfError::InvalidFormat(format!(
+                "Signal '{}' digital minimum {} is greater than digital maximum {}",
+                self.label, self.digital_min, self.digital_max
+            )));
+        }
+
+        if self.samples_per_record < 1 {
+            return Err(EdfError::InvalidFormat(format!(
+                "Signal '{}' must have at least 1 sample per record, got {}",
+                self.label, self.samples_per_record
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the quantization step and worst-case rounding error
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::SignalParam;
+    ///
+    /// let signal = SignalParam::new_eeg("EEG Fp1", 256);
+    /// let q = signal.quantization();
+    /// // ±500 uV over 65535 digital steps
+    /// assert!((q.step - 1000.0 / 65535.0).abs() < 1e-12);
+    /// assert_eq!(q.max_error, q.step / 2.0);
+    /// ```
+    pub fn quantization(&self) -> Quantization {
+        let step = self.bit_value().abs();
+        Quantization { step, max_error: step / 2.0 }
+    }
+
+    /// Derives the physical range from a representative sample buffer
+    ///
+    /// Sets `physical_min`/`physical_max` to the smallest range that contains
+    /// every finite sample in `samples` and can be written to the header
+    /// (limits are rounded outward to 8 characters, so nothing is clipped),
+    /// and switches to the full 16-bit digital range for maximum resolution.
+    /// NaN and infinite samples are ignored. A constant buffer gets a range
+    /// of ±1 unit around its value.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - `samples` contains no finite value, or
+    ///   the range cannot be written in 8 characters
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::SignalParam;
+    ///
+    /// let samples = [-87.25, 12.0, 143.6, 3.3];
+    /// let mut signal = SignalParam::new_eeg("EEG Fp1", 256);
+    /// let q = signal.fit_physical_range(&samples)?;
+    ///
+    /// assert_eq!(signal.physical_min, -87.25);
+    /// assert_eq!(signal.physical_max, 143.6);
+    /// assert_eq!((signal.digital_min, signal.digital_max), (-32768, 32767));
+    /// println!("{} uV/bit, worst-case error {} uV", q.step, q.max_error);
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn fit_physical_range(&mut self, samples: &[f64]) -> Result<Quantization> {
+        let (min, max) = samples
+            .iter()
+            .filter(|v| v.is_finite())
+            .fold(None, |acc: Option<(f64, f64)>, &v| match acc {
+                Some((min, max)) => Some((min.min(v), max.max(v))),
+                None => Some((v, v)),
+            })
+            .ok_or_else(|| EdfError::InvalidFormat(format!(
+                "Signal '{}' has no finite samples to derive a physical range from", self.label
+            )))?;
+        let (min, max) = if min == max { (min - 1.0, max + 1.0) } else { (min, max) };
+
+        self.apply_physical_range(min, max)
+    }
+
+    /// Derives the physical range from a target resolution (physical units per bit)
+    ///
+    /// Uses the full 16-bit digital range with digital 0 at physical 0, so a
+    /// step of 0.1 uV/bit gives a range of -3276.8 to 3276.7 uV. When the
+    /// limits need rounding to fit the header they are rounded outward, which
+    /// makes the actual step (returned) slightly coarser than requested.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - `step` is not a positive finite number, or
+    ///   the resulting range cannot be written in 8 characters
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::SignalParam;
+    ///
+    /// let mut signal = SignalParam::new_eeg("EEG Fp1", 256);
+    /// let q = signal.set_resolution(0.1)?;
+    ///
+    /// assert_eq!(signal.physical_min, -3276.8);
+    /// assert_eq!(signal.physical_max, 3276.7);
+    /// assert!((q.step - 0.1).abs() < 1e-12);
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn set_resolution(&mut self, step: f64) -> Result<Quantization> {
+        if !step.is_finite() || step <= 0.0 {
+            return Err(EdfError::InvalidFormat(format!(
+                "Signal '{}' resolution must be a positive number, got {}", self.label, step
+            )));
+        }
+
+        self.apply_physical_range(step * i16::MIN as f64, step * i16::MAX as f64)
+    }
+
+    /// 设置物理范围（向外舍入到头部可表示的值）并使用完整的16位数字范围
+    fn apply_physical_range(&mut self, min: f64, max: f64) -> Result<Quantization> {
+        let physical_min = encode_header_bound(min, 8, false)?;
+        let physical_max = encode_header_bound(max, 8, true)?;
+
+        self.physical_min = physical_min.value;
+        self.physical_max = physical_max.value;
+        self.digital_min = i16::MIN as i32;
+        self.digital_max = i16::MAX as i32;
+        Ok(self.quantization())
+    }
+}
+
+/// Quantization of a signal's physical values into digital samples
+///
+/// Returned by [`SignalParam::quantization`] and the automatic range
+/// selection methods. Physical values inside the signal's range are stored
+/// with an error of at most `max_error`; values outside it are clamped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantization {
+    /// Physical units per digital step (e.g. uV/bit)
+    pub step: f64,
+    /// Worst-case rounding error for in-range values (half a step)
+    pub max_error: f64,
+}
+
+/// Maximum label length in the signal header (16 ASCII characters)
+const SIGNAL_LABEL_LEN: usize = 16;
+/// Maximum transducer type length in the signal header (80 ASCII characters)
+const SIGNAL_TRANSDUCER_LEN: usize = 80;
+/// Maximum physical dimension length in the signal header (8 ASCII characters)
+const SIGNAL_DIMENSION_LEN: usize = 8;
+/// Maximum prefiltering length in the signal header (80 ASCII characters)
+const SIGNAL_PREFILTER_LEN: usize = 80;
+
+/// 检查头部文本字段：长度限制 + 可打印ASCII (32..=126)
+fn check_header_text(field: &str, value: &str, max_len: usize) -> Result<()> {
+    if let Some(c) = value.chars().find(|c| !(' '..='~').contains(c)) {
+        return Err(EdfError::InvalidFormat(format!(
+            "Signal {} '{}' contains non-printable or non-ASCII character {:?}",
+            field, value, c
+        )));
+    }
+    if value.len() > max_len {
+        return Err(EdfError::InvalidFormat(format!(
+            "Signal {} '{}' exceeds {} characters",
+            field, value, max_len
+        )));
+    }
+    Ok(())
+}
+
+/// Builder for [`SignalParam`] with modality presets
+///
+/// Presets fill in a physical range, unit and transducer suited to the
+/// modality and always use the full 16-bit digital range. Every field can
+/// be overridden before calling [`build`](SignalParamBuilder::build), which
+/// validates the result with [`SignalParam::validate`].
+///
+/// | Preset | Physical range | Unit |
+/// |--------|----------------|------|
+/// | [`eeg`](SignalParamBuilder::eeg) | ±500 | uV |
+/// | [`ecg`](SignalParamBuilder::ecg) | ±5 | mV |
+/// | [`emg`](SignalParamBuilder::emg) | ±5000 | uV |
+/// | [`eog`](SignalParamBuilder::eog) | ±1000 | uV |
+/// | [`respiration`](SignalParamBuilder::respiration) | ±100 | a.u. |
+/// | [`spo2`](SignalParamBuilder::spo2) | 0 to 100 | % |
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::{EdfWriter, SignalParamBuilder};
+///
+/// let mut writer = EdfWriter::create("builder_example.edf")?;
+///
+/// writer.add_signal(
+///     SignalParamBuilder::eeg("EEG C3-A2", 256)
+///         .physical_range(-200.0, 200.0)
+///         .prefilter("HP:0.3Hz LP:35Hz")
+///         .build()?
+/// )?;
+/// writer.add_signal(SignalParamBuilder::spo2("SpO2", 1).build()?)?;
+///
+/// // Field lengths are checked when building
+/// assert!(SignalParamBuilder::ecg("ECG lead II (modified chest)", 256).build().is_err());
+///
+/// # std::fs::remove_file("builder_example.edf").ok();
+/// # Ok::<(), edfplus::EdfError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SignalParamBuilder {
+    signal: SignalParam,
+    auto_range: Option<AutoRange>,
+}
+
+/// 延迟到 build() 时应用的自动范围选择
+#[derive(Debug, Clone, Copy)]
+enum AutoRange {
+    Samples(f64, f64),
+    Resolution(f64),
+}
+
+impl SignalParamBuilder {
+    /// Creates a builder for a generic signal (±1 unit, 1 sample per record)
+    pub fn new(label: &str) -> Self {
+        SignalParamBuilder {
+            signal: SignalParam {
+                label: label.to_string(),
+                samples_in_file: 0,
+                physical_max: 1.0,
+                physical_min: -1.0,
+                digital_max: i16::MAX as i32,
+                digital_min: i16::MIN as i32,
+                samples_per_record: 1,
+                physical_dimension: String::new(),
+                prefilter: String::new(),
+                transducer: String::new(),
+            },
+            auto_range: None,
+        }
+    }
+
+    fn preset(label: &str, samples_per_record: i32, min: f64, max: f64, unit: &str, transducer: &str) -> Self {
+        Self::new(label)
+            .samples_per_record(samples_per_record)
+            .physical_range(min, max)
+            .physical_dimension(unit)
+            .transducer(transducer)
+    }
+
+    /// EEG preset: ±500 uV, AgAgCl electrode
+    pub fn eeg(label: &str, samples_per_record: i32) -> Self {
+        Self::preset(label, samples_per_record, -500.0, 500.0, "uV", "AgAgCl electrode")
+    }
+
+    /// ECG preset: ±5 mV, AgAgCl electrode
+    pub fn ecg(label: &str, samples_per_record: i32) -> Self {
+        Self::preset(label, samples_per_record, -5.0, 5.0, "mV", "AgAgCl electrode")
+    }
+
+    /// EMG preset: ±5000 uV, AgAgCl electrode
+    pub fn emg(label: &str, samples_per_record: i32) -> Self {
+        Self::preset(label, samples_per_record, -5000.0, 5000.0, "uV", "AgAgCl electrode")
+    }
+
+    /// EOG preset: ±1000 uV, AgAgCl electrode
+    pub fn eog(label: &str, samples_per_record: i32) -> Self {
+        Self::preset(label, samples_per_record, -1000.0, 1000.0, "uV", "AgAgCl electrode")
+    }
+
+    /// Respiration preset: ±100 arbitrary units, respiratory effort belt
+    pub fn respiration(label: &str, samples_per_record: i32) -> Self {
+        Self::preset(label, samples_per_record, -100.0, 100.0, "a.u.", "Respiratory effort belt")
+    }
+
+    /// SpO2 preset: 0 to 100 %, pulse oximeter
+    pub fn spo2(label: &str, samples_per_record: i32) -> Self {
+        Self::preset(label, samples_per_record, 0.0, 100.0, "%", "Pulse oximeter")
+    }
+
+    /// Sets the number of samples per data record
+    pub fn samples_per_record(mut self, samples_per_record: i32) -> Self {
+        self.signal.samples_per_record = samples_per_record;
+        self
+    }
+
+    /// Sets the physical minimum and maximum
+    pub fn physical_range(mut self, min: f64, max: f64) -> Self {
+        self.signal.physical_min = min;
+        self.signal.physical_max = max;
+        self.auto_range = None;
+        self
+    }
+
+    /// Derives the physical range from representative samples at build time
+    ///
+    /// See [`SignalParam::fit_physical_range`]. Replaces any range or
+    /// resolution set earlier.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::SignalParamBuilder;
+    ///
+    /// let calibration = [0.8, 1.2, -0.4, 2.1];
+    /// let signal = SignalParamBuilder::ecg("ECG", 250)
+    ///     .physical_range_from_samples(&calibration)
+    ///     .build()?;
+    /// assert_eq!((signal.physical_min, signal.physical_max), (-0.4, 2.1));
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn physical_range_from_samples(mut self, samples: &[f64]) -> Self {
+        let range = samples
+            .iter()
+            .filter(|v| v.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| (min.min(v), max.max(v)));
+        self.auto_range = Some(AutoRange::Samples(range.0, range.1));
+        self
+    }
+
+    /// Derives the physical range from a resolution at build time
+    ///
+    /// See [`SignalParam::set_resolution`]. Replaces any range set earlier.
+    pub fn resolution(mut self, step: f64) -> Self {
+        self.auto_range = Some(AutoRange::Resolution(step));
+        self
+    }
+
+    /// Sets the digital minimum and maximum (must fit in 16 bits)
+    pub fn digital_range(mut self, min: i32, max: i32) -> Self {
+        self.signal.digital_min = min;
+        self.signal.digital_max = max;
+        self
+    }
+
+    /// Sets the physical dimension (max 8 characters)
+    pub fn physical_dimension(mut self, dimension: &str) -> Self {
+        self.signal.physical_dimension = dimension.to_string();
+        self
+    }
+
+    /// Sets the prefiltering description (max 80 characters)
+    pub fn prefilter(mut self, prefilter: &str) -> Self {
+        self.signal.prefilter = prefilter.to_string();
+        self
+    }
+
+    /// Sets the transducer type (max 80 characters)
+    pub fn transducer(mut self, transducer: &str) -> Self {
+        self.signal.transducer = transducer.to_string();
+        self
+    }
+
+    /// Validates and returns the signal parameters
+    ///
+    /// Applies any automatic range selection first.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`SignalParam::validate`], plus the errors of
+    /// [`SignalParam::fit_physical_range`] and [`SignalParam::set_resolution`].
+    pub fn build(mut self) -> Result<SignalParam> {
+        match self.auto_range {
+            Some(AutoRange::Samples(min, max)) => {
+                // 无有限样本时 min > max，由 fit_physical_range 报错
+                let samples: &[f64] = if min <= max { &[min, max] } else { &[] };
+                self.signal.fit_physical_range(samples)?;
+            }
+            Some(AutoRange::Resolution(step)) => {
+                self.signal.set_resolution(step)?;
+            }
+            None => {}
+        }
+        self.signal.validate()?;
+        Ok(self.signal)
+    }
 }
 
 /// Annotation or event marker in an EDF+ file
@@ -299,6 +808,51 @@ pub struct Annotation {
     pub description: String,
 }
 
+impl Annotation {
+    /// Checks the description against a wildcard pattern
+    ///
+    /// `*` matches any sequence of characters and `?` a single character;
+    /// everything else must match exactly (case-sensitive).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::Annotation;
+    ///
+    /// let event = Annotation { onset: 0, duration: -1, description: "Stimulus 12".to_string() };
+    /// assert!(event.matches("Stimulus*"));
+    /// assert!(event.matches("Stimulus ??"));
+    /// assert!(!event.matches("Response*"));
+    /// ```
+    pub fn matches(&self, pattern: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let text: Vec<char> = self.description.chars().collect();
+        wildcard_match(&pattern, &text)
+    }
+}
+
+/// 通配符匹配（'*' 任意序列，'?' 单个字符），线性回溯实现
+fn wildcard_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 /// Complete EDF+ file header information
 /// 
 /// Contains all metadata about the recording, including patient information,
@@ -420,6 +974,13 @@ pub struct EdfHeader {
     /// better temporal resolution for annotations.
     pub datarecord_duration: i64,
     
+    /// Whether the file is discontinuous (EDF+D)
+    /// 
+    /// Data records of an EDF+D file may be separated by gaps; their start
+    /// times are available from [`EdfReader::record_onsets`](crate::EdfReader::record_onsets).
+    /// Sample and time conversions on this header assume a continuous file.
+    pub discontinuous: bool,
+    
     /// Total number of annotations/events in the file
     pub annotations_in_file: i64,
     
@@ -474,3 +1035,191 @@ pub struct EdfHeader {
     /// Free text field for recording details, protocols, etc.
     pub recording_additional: String,
 }
+
+impl EdfHeader {
+    /// Sampling frequency of a signal in Hz
+    /// 
+    /// # Errors
+    /// 
+    /// * `EdfError::InvalidSignalIndex` - Signal index is out of bounds
+    /// * `EdfError::InvalidFormat` - The record duration or samples per record is not positive
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::EdfReader;
+    /// 
+    /// # edfplus::doctest_utils::create_simple_test_file("rate.edf")?;
+    /// let reader = EdfReader::open("rate.edf")?;
+    /// assert_eq!(reader.header().sampling_frequency(0)?, 256.0);
+    /// 
+    /// # std::fs::remove_file("rate.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn sampling_frequency(&self, signal: usize) -> Result<f64> {
+        let samples_per_record = self.samples_per_record_of(signal)?;
+        Ok(samples_per_record as f64 * EDFLIB_TIME_DIMENSION as f64 / self.datarecord_duration as f64)
+    }
+    
+    /// Time of a sample in seconds since the first sample of the file
+    /// 
+    /// Like annotation onsets, this does not include `starttime_subsecond`;
+    /// use [`sample_to_datetime`](Self::sample_to_datetime) for wall-clock time.
+    /// 
+    /// The conversions between samples and times on `EdfHeader` assume that
+    /// data records follow each other without gaps. For a discontinuous
+    /// (EDF+D) file they return `EdfError::DiscontinuousFile`; use
+    /// [`EdfReader::seconds_to_sample`](crate::EdfReader::seconds_to_sample) and
+    /// [`EdfReader::sample_times`](crate::EdfReader::sample_times) instead,
+    /// which read the record start times from the file.
+    pub fn sample_to_seconds(&self, signal: usize, sample: i64) -> Result<f64> {
+        Ok(self.sample_to_time(signal, sample)? as f64 / EDFLIB_TIME_DIMENSION as f64)
+    }
+    
+    /// Index of the sample nearest to a time in seconds since the first sample
+    /// 
+    /// The result is not limited to the samples present in the file.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::EdfReader;
+    /// 
+    /// # edfplus::doctest_utils::create_simple_test_file("nearest.edf")?;
+    /// let mut reader = EdfReader::open("nearest.edf")?;
+    /// 
+    /// // Jump to 2.5 s into the recording
+    /// let sample = reader.header().seconds_to_sample(0, 2.5)?;
+    /// reader.seek(0, sample)?;
+    /// assert_eq!(sample, 640);
+    /// 
+    /// # std::fs::remove_file("nearest.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn seconds_to_sample(&self, signal: usize, seconds: f64) -> Result<i64> {
+        let time = seconds_to_time(seconds)?;
+        self.time_to_sample(signal, time, true)
+    }
+    
+    /// Index of the last sample at or before a time in seconds since the first sample
+    pub fn seconds_to_sample_floor(&self, signal: usize, seconds: f64) -> Result<i64> {
+        let time = seconds_to_time(seconds)?;
+        self.time_to_sample(signal, time, false)
+    }
+    
+    /// Date and time of the first sample, including `starttime_subsecond`
+    pub fn start_datetime(&self) -> NaiveDateTime {
+        self.start_date.and_time(self.start_time) + TimeDelta::nanoseconds(self.starttime_subsecond * 100)
+    }
+    
+    /// Absolute date and time of a sample
+    /// 
+    /// # Errors
+    /// 
+    /// * `EdfError::InvalidSignalIndex` - Signal index is out of bounds
+    /// * `EdfError::InvalidFormat` - The signal has no sampling rate, or the
+    ///   sample lies outside the range of `NaiveDateTime`
+    /// * `EdfError::DiscontinuousFile` - The file is EDF+D
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::EdfReader;
+    /// 
+    /// # edfplus::doctest_utils::create_simple_test_file("clock.edf")?;
+    /// let reader = EdfReader::open("clock.edf")?;
+    /// let header = reader.header();
+    /// 
+    /// let at = header.sample_to_datetime(0, 256 * 90)?;
+    /// assert_eq!(at - header.start_datetime(), chrono::TimeDelta::seconds(90));
+    /// assert_eq!(header.datetime_to_sample(0, &at)?, 256 * 90);
+    /// 
+    /// # std::fs::remove_file("clock.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn sample_to_datetime(&self, signal: usize, sample: i64) -> Result<NaiveDateTime> {
+        let samples_per_record = self.contiguous_samples_per_record(signal)?;
+        // 以纳秒精度计算，避免浮点误差
+        let nanos = sample as i128 * self.datarecord_duration as i128 * 100 / samples_per_record as i128;
+        i64::try_from(nanos).ok()
+            .and_then(|nanos| self.start_datetime().checked_add_signed(TimeDelta::nanoseconds(nanos)))
+            .ok_or_else(|| EdfError::InvalidFormat(format!(
+                "Sample {} of signal {} is outside the representable date range", sample, signal
+            )))
+    }
+    
+    /// Index of the sample nearest to an absolute date and time
+    pub fn datetime_to_sample(&self, signal: usize, datetime: &NaiveDateTime) -> Result<i64> {
+        let elapsed = (*datetime - self.start_datetime())
+            .num_nanoseconds()
+            .ok_or_else(|| EdfError::InvalidFormat(format!("Date and time {} is too far from the recording start", datetime)))?;
+        // 纳秒转换为100纳秒单位（四舍五入）
+        let time = (elapsed as i128 + 50).div_euclid(100) as i64;
+        self.time_to_sample(signal, time, true)
+    }
+    
+    /// Times in seconds since the first sample of `count` consecutive samples
+    /// starting at `start`
+    /// 
+    /// Pairs with the sample reading methods: pass the position before the
+    /// read and the number of samples returned.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::EdfReader;
+    /// 
+    /// # edfplus::doctest_utils::create_simple_test_file("timestamps.edf")?;
+    /// let mut reader = EdfReader::open("timestamps.edf")?;
+    /// reader.seek(0, 128)?;
+    /// 
+    /// let position = reader.tell(0)?;
+    /// let samples = reader.read_physical_samples(0, 128)?;
+    /// let times = reader.header().sample_times(0, position, samples.len())?;
+    /// 
+    /// assert_eq!(times[0], 0.5);
+    /// assert_eq!(times[64], 0.75);
+    /// 
+    /// # std::fs::remove_file("timestamps.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn sample_times(&self, signal: usize, start: i64, count: usize) -> Result<Vec<f64>> {
+        let samples_per_record = self.contiguous_samples_per_record(signal)? as f64;
+        let record_seconds = self.datarecord_duration as f64 / EDFLIB_TIME_DIMENSION as f64;
+        Ok((0..count as i64)
+            .map(|i| (start + i) as f64 * record_seconds / samples_per_record)
+            .collect())
+    }
+    
+    /// 校验信号索引与时间参数，返回每记录样本数
+    fn samples_per_record_of(&self, signal: usize) -> Result<i64> {
+        let signal_param = self.signals.get(signal).ok_or(EdfError::InvalidSignalIndex(signal))?;
+        if signal_param.samples_per_record <= 0 || self.datarecord_duration <= 0 {
+            return Err(EdfError::InvalidFormat(format!(
+                "Signal {} has no sampling rate ({} samples per {} record duration)",
+                signal, signal_param.samples_per_record, self.datarecord_duration
+            )));
+        }
+        Ok(signal_param.samples_per_record as i64)
+    }
+    
+    /// 样本与时间换算要求数据记录之间没有间隙
+    fn contiguous_samples_per_record(&self, signal: usize) -> Result<i64> {
+        if self.discontinuous {
+            return Err(EdfError::DiscontinuousFile);
+        }
+        self.samples_per_record_of(signal)
+    }
+    
+    /// 样本序号对应的时间（100纳秒单位，向下取整）
+    fn sample_to_time(&self, signal: usize, sample: i64) -> Result<i64> {
+        let samples_per_record = self.contiguous_samples_per_record(signal)?;
+        Ok((sample as i128 * self.datarecord_duration as i128).div_euclid(samples_per_record as i128) as i64)
+    }
+    
+    /// 时间（100纳秒单位）对应的样本序号，四舍五入或向下取整
+    fn time_to_sample(&self, signal: usize, time: i64, nearest: bool) -> Result<i64> {
+        let samples_per_record = self.contiguous_samples_per_record(signal)?;
+        Ok(time_to_sample(time, samples_per_record, self.datarecord_duration, nearest))
+    }
+}
@@ -12,7 +12,7 @@ pub fn is_integer_number(s: &str) -> bool {
     }
     
     // 简单的整数检查
-    s.chars().next().map_or(false, |first| first == '+' || first == '-' || first.is_ascii_digit()) &&
+    s.chars().next().is_some_and(|first| first == '+' || first == '-' || first.is_ascii_digit()) &&
     s.chars().skip(if s.starts_with('+') || s.starts_with('-') { 1 } else { 0 })
         .all(|c| c.is_ascii_digit() || c == ' ') &&
     s.chars().any(|c| c.is_ascii_digit())
@@ -42,47 +42,38 @@ pub fn parse_edf_time(s: &str) -> Result<i64> {
     }
     
     // 处理符号
-    let (negative, s) = if s.starts_with('-') {
-        (true, &s[1..])
-    } else if s.starts_with('+') {
-        (false, &s[1..])
+    let (negative, s) = if let Some(rest) = s.strip_prefix('-') {
+        (true, rest)
+    } else if let Some(rest) = s.strip_prefix('+') {
+        (false, rest)
     } else {
         (false, s)
     };
     
-    let mut value = 0i64;
+    let (integer_part, decimal_part) = s.split_once('.').unwrap_or((s, ""));
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if integer_part.is_empty() && decimal_part.is_empty() || !is_digits(integer_part) || !is_digits(decimal_part) {
+        return Err(EdfError::InvalidFormat(format!("Invalid time string {:?}", s)));
+    }
+    let overflow = || EdfError::InvalidFormat(format!("Time {:?} is out of range", s));
     
-    if let Some(dot_pos) = s.find('.') {
-        // 有小数部分
-        let integer_part = &s[..dot_pos];
-        let decimal_part = &s[dot_pos + 1..];
-        
-        // 解析整数部分
-        if !integer_part.is_empty() {
-            value += integer_part.parse::<i64>()
-                .map_err(|_| EdfError::InvalidFormat("Invalid integer part".to_string()))?
-                * crate::EDFLIB_TIME_DIMENSION;
-        }
-        
-        // 解析小数部分（最多7位精度）
-        if !decimal_part.is_empty() {
-            let decimal_str = if decimal_part.len() > 7 {
-                &decimal_part[..7]
-            } else {
-                decimal_part
-            };
-            
-            let decimal_value = decimal_str.parse::<i64>()
-                .map_err(|_| EdfError::InvalidFormat("Invalid decimal part".to_string()))?;
-            
-            let scale = 10i64.pow(7 - decimal_str.len() as u32);
-            value += decimal_value * scale;
-        }
+    // 解析整数部分
+    let mut value = if integer_part.is_empty() {
+        0
     } else {
-        // 只有整数部分
-        value = s.parse::<i64>()
-            .map_err(|_| EdfError::InvalidFormat("Invalid integer".to_string()))?
-            * crate::EDFLIB_TIME_DIMENSION;
+        integer_part.parse::<i64>()
+            .ok()
+            .and_then(|seconds| seconds.checked_mul(crate::EDFLIB_TIME_DIMENSION))
+            .ok_or_else(overflow)?
+    };
+    
+    // 解析小数部分（最多7位精度，均为ASCII数字，可按字节截取）
+    if !decimal_part.is_empty() {
+        let decimal_str = &decimal_part[..decimal_part.len().min(7)];
+        let decimal_value: i64 = decimal_str.parse()
+            .map_err(|_| EdfError::InvalidFormat("Invalid decimal part".to_string()))?;
+        let scale = 10i64.pow(7 - decimal_str.len() as u32);
+        value = value.checked_add(decimal_value * scale).ok_or_else(overflow)?;
     }
     
     if negative {
@@ -92,6 +83,137 @@ pub fn parse_edf_time(s: &str) -> Result<i64> {
     Ok(value)
 }
 
+/// 与上一个记录末尾相差不超过此值的记录视为连续（1 ms）
+pub(crate) const CONTIGUOUS_TOLERANCE: i64 = crate::EDFLIB_TIME_DIMENSION / 1000;
+
+/// 秒转换为100纳秒单位（四舍五入）
+pub(crate) fn seconds_to_time(seconds: f64) -> Result<i64> {
+    let time = (seconds * crate::EDFLIB_TIME_DIMENSION as f64).round();
+    if !time.is_finite() || time.abs() >= i64::MAX as f64 {
+        return Err(EdfError::InvalidFormat(format!("Invalid time {} s", seconds)));
+    }
+    Ok(time as i64)
+}
+
+/// 时间（100纳秒单位）对应的样本序号，四舍五入或向下取整
+pub(crate) fn time_to_sample(time: i64, samples_per_record: i64, record_duration: i64, nearest: bool) -> i64 {
+    if record_duration <= 0 {
+        return 0;
+    }
+    let scaled = time as i128 * samples_per_record as i128;
+    let duration = record_duration as i128;
+    let rounding = if nearest { duration / 2 } else { 0 };
+    (scaled + rounding).div_euclid(duration) as i64
+}
+
+/// A number encoded for a fixed-width ASCII header field
+///
+/// Produced by [`encode_header_number`]. `text` is what goes into the header
+/// (left-aligned, without padding) and `value` is what a reader will parse
+/// back from it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderNumber {
+    /// ASCII representation, at most `width` characters
+    pub text: String,
+    /// Value represented by `text`
+    pub value: f64,
+}
+
+impl HeaderNumber {
+    /// Absolute difference between `original` and the encoded value
+    pub fn rounding_error(&self, original: f64) -> f64 {
+        (self.value - original).abs()
+    }
+}
+
+/// 将数字编码为最多 `width` 个字符的EDF头部字段
+///
+/// Encodes a number for an EDF header field of `width` characters (8 for
+/// most numeric fields, 4 for the number of signals).
+///
+/// The EDF specification only allows plain decimal notation, so the value is
+/// rounded to the largest number of decimals that still fits; integers are
+/// written exactly. Trailing zeros are removed, so `100.0` becomes `"100"`.
+///
+/// # Errors
+///
+/// * `EdfError::InvalidFormat` - The value is not finite or its integer part
+///   alone needs more than `width` characters
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::utils::encode_header_number;
+///
+/// let encoded = encode_header_number(-3276.8123, 8)?;
+/// assert_eq!(encoded.text, "-3276.81");
+/// assert!((encoded.rounding_error(-3276.8123) - 0.0023).abs() < 1e-9);
+///
+/// assert_eq!(encode_header_number(1.0 / 3.0, 8)?.text, "0.333333");
+/// assert_eq!(encode_header_number(200.0, 8)?.text, "200");
+/// assert!(encode_header_number(123_456_789.0, 8).is_err());
+/// # Ok::<(), edfplus::EdfError>(())
+/// ```
+pub fn encode_header_number(value: f64, width: usize) -> Result<HeaderNumber> {
+    if !value.is_finite() {
+        return Err(EdfError::InvalidFormat(format!(
+            "Header value {} is not a finite number", value
+        )));
+    }
+
+    // 从最高精度开始，找到第一个能放进字段宽度的表示
+    for decimals in (0..width).rev() {
+        let mut text = format!("{:.*}", decimals, value);
+        if text.contains('.') {
+            let trimmed_len = text.trim_end_matches('0').trim_end_matches('.').len();
+            text.truncate(trimmed_len);
+        }
+        if text == "-0" {
+            text = "0".to_string();
+        }
+        if text.len() <= width {
+            let parsed = text.parse::<f64>().unwrap_or(value);
+            return Ok(HeaderNumber { text, value: parsed });
+        }
+    }
+
+    Err(EdfError::InvalidFormat(format!(
+        "Header value {} cannot be represented in {} characters", value, width
+    )))
+}
+
+/// 编码头部数值，并向外舍入（`round_up` 为 true 时结果 >= value，否则 <= value）
+///
+/// Used for range limits, where rounding towards the inside of the range
+/// would clip samples at the edges.
+pub(crate) fn encode_header_bound(value: f64, width: usize, round_up: bool) -> Result<HeaderNumber> {
+    let encoded = encode_header_number(value, width)?;
+    // 容忍浮点运算噪声（例如 0.1 * 32767 = 3276.7000000000003）
+    let tolerance = value.abs() * 1e-12;
+    let outside = if round_up {
+        encoded.value >= value - tolerance
+    } else {
+        encoded.value <= value + tolerance
+    };
+    if outside {
+        return Ok(encoded);
+    }
+
+    // 按最后一位小数的步长向外移动一步
+    let decimals = encoded.text.find('.').map_or(0, |dot| encoded.text.len() - dot - 1);
+    let step = 10f64.powi(-(decimals as i32));
+    let nudged = if round_up { encoded.value + step } else { encoded.value - step };
+    let encoded = encode_header_number(nudged, width)?;
+    let outside = if round_up { encoded.value >= value } else { encoded.value <= value };
+    if outside {
+        Ok(encoded)
+    } else {
+        Err(EdfError::InvalidFormat(format!(
+            "Header value {} cannot be rounded outward in {} characters", value, width
+        )))
+    }
+}
+
 /// 移除字符串前后的空格
 pub fn trim_padding_spaces(s: &mut String) {
     let trimmed = s.trim().to_string();
@@ -140,5 +262,65 @@ mod tests {
         assert_eq!(parse_edf_time("1.5").unwrap(), 15_000_000);
         assert_eq!(parse_edf_time("-2.5").unwrap(), -25_000_000);
         assert_eq!(parse_edf_time("+0.0000001").unwrap(), 1);
+        assert_eq!(parse_edf_time(".25").unwrap(), 2_500_000);
+        assert_eq!(parse_edf_time("3.").unwrap(), 30_000_000);
+        assert_eq!(parse_edf_time("1.123456789").unwrap(), 11_234_567);
+
+        // 畸形输入返回错误而不是 panic
+        assert!(parse_edf_time(".").is_err());
+        assert!(parse_edf_time("+").is_err());
+        assert!(parse_edf_time("1.2.3").is_err());
+        assert!(parse_edf_time("1.123456\u{fffd}").is_err());
+        assert!(parse_edf_time("99999999999999999").is_err());
+        assert!(parse_edf_time("--5").is_err());
+    }
+
+    #[test]
+    fn test_encode_header_number() {
+        // 精确表示
+        assert_eq!(encode_header_number(0.0, 8).unwrap().text, "0");
+        assert_eq!(encode_header_number(-0.0, 8).unwrap().text, "0");
+        assert_eq!(encode_header_number(-32768.0, 8).unwrap().text, "-32768");
+        assert_eq!(encode_header_number(0.5, 8).unwrap().text, "0.5");
+        assert_eq!(encode_header_number(99999999.0, 8).unwrap().text, "99999999");
+
+        // 需要舍入
+        let encoded = encode_header_number(-3276.8123, 8).unwrap();
+        assert_eq!(encoded.text, "-3276.81");
+        assert_eq!(encoded.value, -3276.81);
+        let encoded = encode_header_number(0.123456789, 8).unwrap();
+        assert_eq!(encoded.text, "0.123457");
+        let encoded = encode_header_number(12345678.9, 8).unwrap();
+        assert_eq!(encoded.text, "12345679");
+        let encoded = encode_header_number(0.00000001, 8).unwrap();
+        assert_eq!(encoded.text, "0");
+
+        // 4字符字段
+        assert_eq!(encode_header_number(4096.0, 4).unwrap().text, "4096");
+        assert!(encode_header_number(10000.0, 4).is_err());
+
+        // 无法表示
+        assert!(encode_header_number(99999999.6, 8).is_err());
+        assert!(encode_header_number(-12345678.0, 8).is_err());
+        assert!(encode_header_number(f64::NAN, 8).is_err());
+        assert!(encode_header_number(f64::INFINITY, 8).is_err());
+    }
+
+    #[test]
+    fn test_encode_header_bound() {
+        // 最近值已经在外侧
+        assert_eq!(encode_header_bound(-3276.8123, 8, false).unwrap().text, "-3276.82");
+        assert_eq!(encode_header_bound(3276.8123, 8, true).unwrap().text, "3276.813");
+        assert_eq!(encode_header_bound(-3276.8163, 8, false).unwrap().text, "-3276.82");
+        assert_eq!(encode_header_bound(3276.8128, 8, true).unwrap().text, "3276.813");
+
+        // 精确值不变
+        assert_eq!(encode_header_bound(100.0, 8, true).unwrap().text, "100");
+        assert_eq!(encode_header_bound(-0.5, 8, false).unwrap().text, "-0.5");
+        assert_eq!(encode_header_bound(0.1 * 32767.0, 8, true).unwrap().text, "3276.7");
+
+        // 进位改变位数
+        assert_eq!(encode_header_bound(9999999.5, 8, true).unwrap().text, "10000000");
+        assert!(encode_header_bound(99999999.5, 8, true).is_err());
     }
 }
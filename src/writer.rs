@@ -1,10 +1,12 @@
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::Path;
-use chrono::{NaiveDate, NaiveTime, Datelike, Timelike};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Datelike, Timelike};
 
-use crate::types::SignalParam;
+use crate::types::{Quantization, SignalParam};
+use crate::filter::{FilterChain, SignalFilter};
 use crate::error::{EdfError, Result};
+use crate::utils::encode_header_number;
 use crate::EDFLIB_TIME_DIMENSION;
 
 /// Maximum number of annotation channels (matches edflib)
@@ -188,6 +190,7 @@ pub struct EdfWriter {
     admin_code: String,
     technician: String,
     equipment: String,
+    #[allow(dead_code)] // 暂未写入记录字段
     recording_additional: String,
     
     // 注释存储
@@ -198,6 +201,145 @@ pub struct EdfWriter {
     
     // 多注释通道支持 (遵循edflib设计)
     nr_annot_chns: usize,                    // 注释通道数量 (默认1)
+
+    // 头部数值字段的舍入记录
+    header_rounding: Vec<HeaderRounding>,
+
+    // 样本处理策略与统计
+    sample_policy: SamplePolicy,
+    signal_policies: Vec<Option<SamplePolicy>>,
+    sample_stats: Vec<SampleStats>,
+
+    // 写入前对物理值应用的滤波器，及在第一个记录时按采样率设计的滤波状态
+    signal_filters: Vec<Option<FilterChain>>,
+    filter_states: Vec<Option<SignalFilter>>,
+
+    // 按通道缓冲的流式样本（push_samples）
+    stream_buffers: Vec<Vec<f64>>,
+    last_pushed: Vec<Option<f64>>,
+    padding_policy: PaddingPolicy,
+
+    // 下一个数据记录的开始时间，以及已写入记录覆盖到的时间（EDF+D 支持）
+    record_onset: i64,
+    annotations_until: i64,
+    discontinuous: bool,
+
+    // 编码数据记录的缓冲，在记录之间复用
+    record_buffer: Vec<u8>,
+}
+
+/// A numeric header value that was rounded to fit its 8-character field
+///
+/// EDF headers store numbers as at most 8 ASCII characters. When a signal's
+/// physical range or the data record duration needs more digits, the writer
+/// rounds it to the closest representable value and uses that value for all
+/// further conversions, so the file stays self-consistent. Each such change
+/// is recorded and can be inspected with [`EdfWriter::header_rounding`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderRounding {
+    /// Header field name (`"physical_min"`, `"physical_max"` or `"datarecord_duration"`)
+    pub field: &'static str,
+    /// Index of the signal the field belongs to (`None` for main header fields)
+    pub signal: Option<usize>,
+    /// Value requested by the caller
+    pub requested: f64,
+    /// Value actually written to the header
+    pub written: f64,
+    /// Header text for `written`
+    pub text: String,
+}
+
+impl HeaderRounding {
+    /// Absolute difference between the requested and written values
+    pub fn error(&self) -> f64 {
+        (self.written - self.requested).abs()
+    }
+}
+
+/// How [`EdfWriter::write_samples`] handles values outside the physical range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClipPolicy {
+    /// Clamp to `physical_min`/`physical_max` and count the sample as clipped
+    #[default]
+    Clamp,
+    /// Reject the whole data record with `EdfError::InvalidSample`
+    Error,
+}
+
+/// How [`EdfWriter::write_samples`] handles NaN and infinite values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvalidSamplePolicy {
+    /// Reject the whole data record with `EdfError::InvalidSample`
+    #[default]
+    Error,
+    /// Write this digital value instead (clamped to the signal's digital range)
+    /// and count the sample as invalid
+    Marker(i32),
+}
+
+/// Sample handling policy for a writer or a single signal
+///
+/// The default clamps out-of-range values, as earlier versions did silently,
+/// and rejects NaN/infinite values.
+///
+/// # Examples
+///
+/// ```rust
+/// use edfplus::{ClipPolicy, InvalidSamplePolicy, SamplePolicy};
+///
+/// // Strict acquisition: any clipping is an error
+/// let strict = SamplePolicy { clip: ClipPolicy::Error, ..Default::default() };
+///
+/// // Mark sensor dropouts with the lowest digital value
+/// let marked = SamplePolicy {
+///     invalid: InvalidSamplePolicy::Marker(-32768),
+///     ..Default::default()
+/// };
+/// assert_ne!(strict, marked);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SamplePolicy {
+    /// Handling of finite values outside the physical range
+    pub clip: ClipPolicy,
+    /// Handling of NaN and infinite values
+    pub invalid: InvalidSamplePolicy,
+}
+
+/// Per-signal sample counters collected while writing
+///
+/// Returned by [`EdfWriter::finalize`] and available during writing through
+/// [`EdfWriter::sample_stats`]. Records rejected with an error are not counted.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SampleStats {
+    /// Signal label
+    pub label: String,
+    /// Number of samples written
+    pub samples: u64,
+    /// Samples clamped to the physical range
+    pub clipped: u64,
+    /// NaN or infinite samples replaced by the marker value
+    pub invalid: u64,
+}
+
+/// How [`EdfWriter::finalize`] completes a partial data record left by
+/// [`EdfWriter::push_samples`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PaddingPolicy {
+    /// Drop the buffered samples that do not fill a complete record
+    #[default]
+    Discard,
+    /// Pad each channel with this physical value
+    Value(f64),
+    /// Pad each channel with its last pushed sample (channels that never
+    /// received data are padded with 0, clamped to their physical range)
+    RepeatLast,
+}
+
+impl SampleStats {
+    /// Returns true if no sample was clipped or replaced
+    pub fn is_clean(&self) -> bool {
+        self.clipped == 0 && self.invalid == 0
+    }
 }
 
 impl EdfWriter {
@@ -282,6 +424,19 @@ impl EdfWriter {
             annotations: Vec::new(),
             starttime_subsecond: 0,
             nr_annot_chns: 1,  // 默认1个注释通道
+            header_rounding: Vec::new(),
+            sample_policy: SamplePolicy::default(),
+            signal_policies: Vec::new(),
+            sample_stats: Vec::new(),
+            signal_filters: Vec::new(),
+            filter_states: Vec::new(),
+            stream_buffers: Vec::new(),
+            last_pushed: Vec::new(),
+            padding_policy: PaddingPolicy::default(),
+            record_onset: 0,
+            annotations_until: 0,
+            discontinuous: false,
+            record_buffer: Vec::new(),
         })
     }
     
@@ -297,16 +452,22 @@ impl EdfWriter {
     /// 
     /// # Errors
     /// 
-    /// * `EdfError::InvalidFormat` - Trying to add signal after header is written
+    /// * `EdfError::InvalidFormat` - Trying to add signal after header is written,
+    ///   or a field that cannot be stored in the header (see below)
     /// * `EdfError::PhysicalMinEqualsMax` - Invalid physical range
     /// * `EdfError::DigitalMinEqualsMax` - Invalid digital range
     /// 
     /// # Signal Parameter Requirements
     /// 
+    /// The signal is checked with [`SignalParam::validate`]:
     /// - `physical_min` must be different from `physical_max`
-    /// - `digital_min` must be different from `digital_max`
-    /// - `samples_per_record` should match the intended sampling rate
-    /// - `label` should be descriptive and follow EDF+ conventions
+    /// - `digital_min` must be below `digital_max`, both within -32768..=32767
+    /// - `samples_per_record` must be at least 1 and match the intended sampling rate
+    /// - `label` (16), `transducer` (80), `physical_dimension` (8) and `prefilter` (80)
+    ///   must fit their header fields and contain printable ASCII only
+    /// 
+    /// Use [`SignalParam::new_eeg`] and friends or [`SignalParamBuilder`](crate::SignalParamBuilder)
+    /// to avoid spelling out every field.
     /// 
     /// # Examples
     /// 
@@ -371,22 +532,213 @@ impl EdfWriter {
     /// # std::fs::remove_file("mixed_rates.edf").ok();
     /// # Ok::<(), edfplus::EdfError>(())
     /// ```
-    pub fn add_signal(&mut self, signal: SignalParam) -> Result<()> {
+    pub fn add_signal(&mut self, mut signal: SignalParam) -> Result<()> {
         if self.header_written {
             return Err(EdfError::InvalidFormat("Cannot add signal after writing header".to_string()));
         }
         
-        // 验证信号参数
-        if signal.physical_min == signal.physical_max {
+        // 验证信号参数（字段长度、ASCII、16位数字范围）
+        signal.validate()?;
+        
+        // 物理范围舍入到8字符头部字段可表示的值，后续转换使用同样的值
+        let signal_idx = self.signals.len();
+        let physical_min = encode_header_number(signal.physical_min, 8)?;
+        let physical_max = encode_header_number(signal.physical_max, 8)?;
+        if physical_min.value == physical_max.value {
             return Err(EdfError::PhysicalMinEqualsMax);
         }
-        if signal.digital_min == signal.digital_max {
-            return Err(EdfError::DigitalMinEqualsMax);
+        for (field, requested, encoded) in [
+            ("physical_min", &mut signal.physical_min, physical_min),
+            ("physical_max", &mut signal.physical_max, physical_max),
+        ] {
+            if encoded.value != *requested {
+                self.header_rounding.push(HeaderRounding {
+                    field,
+                    signal: Some(signal_idx),
+                    requested: *requested,
+                    written: encoded.value,
+                    text: encoded.text,
+                });
+                *requested = encoded.value;
+            }
         }
         
+        self.sample_stats.push(SampleStats { label: signal.label.clone(), ..Default::default() });
+        self.signal_policies.push(None);
+        self.signal_filters.push(None);
+        self.stream_buffers.push(Vec::new());
+        self.last_pushed.push(None);
         self.signals.push(signal);
         Ok(())
     }
+
+    /// Returns the header values that had to be rounded to fit 8 characters
+    /// 
+    /// Physical limits are rounded in [`add_signal`](Self::add_signal) and the
+    /// record duration in [`set_datarecord_duration`](Self::set_datarecord_duration).
+    /// Values that could be stored exactly are not listed.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::{EdfWriter, SignalParam};
+    /// 
+    /// let mut writer = EdfWriter::create("rounding.edf")?;
+    /// 
+    /// let mut signal = SignalParam::new_eeg("EEG Fp1", 256);
+    /// signal.physical_min = -3276.8123;
+    /// writer.add_signal(signal)?;
+    /// writer.set_datarecord_duration(1.0 / 3.0)?;
+    /// 
+    /// for rounding in writer.header_rounding() {
+    ///     println!("{} {:?}: {} -> {} (error {:.2e})",
+    ///         rounding.field, rounding.signal, rounding.requested, rounding.text, rounding.error());
+    /// }
+    /// assert_eq!(writer.header_rounding().len(), 2);
+    /// assert_eq!(writer.header_rounding()[0].text, "-3276.81");
+    /// assert_eq!(writer.header_rounding()[1].text, "0.333333");
+    /// 
+    /// # std::fs::remove_file("rounding.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn header_rounding(&self) -> &[HeaderRounding] {
+        &self.header_rounding
+    }
+
+    /// Returns the quantization of each signal, in the order they were added
+    ///
+    /// Reflects the ranges actually written to the header, i.e. after any
+    /// rounding reported by [`header_rounding`](Self::header_rounding).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{EdfWriter, SignalParam};
+    ///
+    /// let mut writer = EdfWriter::create("quantization.edf")?;
+    /// let mut signal = SignalParam::new_eeg("EEG Fp1", 256);
+    /// signal.set_resolution(0.1)?;
+    /// writer.add_signal(signal)?;
+    ///
+    /// let q = writer.quantization()[0];
+    /// assert!((q.step - 0.1).abs() < 1e-12);
+    /// # drop(writer);
+    /// # std::fs::remove_file("quantization.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn quantization(&self) -> Vec<Quantization> {
+        self.signals.iter().map(SignalParam::quantization).collect()
+    }
+
+    /// Sets the sample handling policy for all signals
+    ///
+    /// Signals with their own policy (see
+    /// [`set_signal_sample_policy`](Self::set_signal_sample_policy)) keep it.
+    /// Can be changed at any time; it applies to the following records.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{ClipPolicy, EdfError, EdfWriter, SamplePolicy, SignalParam};
+    ///
+    /// let mut writer = EdfWriter::create("strict.edf")?;
+    /// writer.add_signal(SignalParam::new_ecg("ECG", 4))?;
+    /// writer.set_sample_policy(SamplePolicy { clip: ClipPolicy::Error, ..Default::default() });
+    ///
+    /// // 12 mV is outside the ±5 mV ECG range
+    /// let result = writer.write_samples(&[vec![0.0, 1.0, 12.0, 0.0]]);
+    /// assert!(matches!(result, Err(EdfError::InvalidSample(_))));
+    ///
+    /// # drop(writer);
+    /// # std::fs::remove_file("strict.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn set_sample_policy(&mut self, policy: SamplePolicy) {
+        self.sample_policy = policy;
+    }
+
+    /// Sets the sample handling policy for one signal
+    ///
+    /// Pass `None` to fall back to the writer-wide policy.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidSignalIndex` - No signal with this index was added
+    pub fn set_signal_sample_policy(&mut self, signal_idx: usize, policy: Option<SamplePolicy>) -> Result<()> {
+        let slot = self.signal_policies
+            .get_mut(signal_idx)
+            .ok_or(EdfError::InvalidSignalIndex(signal_idx))?;
+        *slot = policy;
+        Ok(())
+    }
+
+    /// Filters the physical samples of a signal before they are written
+    ///
+    /// Applies to [`write_samples`](Self::write_samples),
+    /// [`write_records`](Self::write_records) and
+    /// [`push_samples`](Self::push_samples); the filter state carries over
+    /// from one data record to the next. The chain's EDF+ notation (e.g.
+    /// `HP:0.5Hz N:50Hz`) is appended to the signal's `prefilter` field, and
+    /// a second call on the same signal adds its filters after the first.
+    /// Digital samples cannot be written to a file with filtered signals.
+    ///
+    /// # Errors
+    ///
+    /// * `EdfError::InvalidFormat` - The header has been written, the chain is
+    ///   zero-phase, it cannot be designed for the signal's sampling rate, or
+    ///   the prefilter text would exceed 80 characters
+    /// * `EdfError::InvalidSignalIndex` - No signal with this index was added
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use edfplus::{EdfReader, EdfWriter, FilterChain, SignalParam};
+    ///
+    /// let mut writer = EdfWriter::create("filtered.edf")?;
+    /// writer.add_signal(SignalParam::new_eeg("EEG Cz", 256))?;
+    /// writer.set_filter(0, &FilterChain::new().highpass(0.5, 2).notch(50.0, 30.0))?;
+    /// writer.write_samples(&[vec![10.0; 256]])?;
+    /// writer.finalize()?;
+    ///
+    /// let reader = EdfReader::open("filtered.edf")?;
+    /// assert_eq!(reader.header().signals[0].prefilter, "HP:0.5Hz N:50Hz");
+    ///
+    /// # std::fs::remove_file("filtered.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn set_filter(&mut self, signal_idx: usize, filter: &FilterChain) -> Result<()> {
+        if self.header_written {
+            return Err(EdfError::InvalidFormat("Cannot set a filter after writing header".to_string()));
+        }
+        if filter.is_zero_phase() {
+            return Err(EdfError::InvalidFormat(
+                "Zero-phase filters need the whole signal and cannot be applied while writing".to_string()
+            ));
+        }
+        let signal = self.signals.get(signal_idx).ok_or(EdfError::InvalidSignalIndex(signal_idx))?;
+        let record_seconds = self.datarecord_duration as f64 / EDFLIB_TIME_DIMENSION as f64;
+        filter.design(signal.samples_per_record as f64 / record_seconds)?;
+
+        // 已有的预滤波说明保留在前（"None" 视为空）
+        let mut updated = signal.clone();
+        let previous = updated.prefilter.trim();
+        updated.prefilter = if previous.is_empty() || previous.eq_ignore_ascii_case("none") {
+            filter.to_string()
+        } else {
+            format!("{} {}", previous, filter)
+        };
+        updated.validate()?;
+
+        self.signals[signal_idx] = updated;
+        let chain = &mut self.signal_filters[signal_idx];
+        *chain = Some(chain.take().map_or_else(|| filter.clone(), |chain| chain.then(filter)));
+        Ok(())
+    }
+
+    /// Returns the sample counters of each signal written so far
+    pub fn sample_stats(&self) -> &[SampleStats] {
+        &self.sample_stats
+    }
     
     /// Sets patient information for the EDF+ file
     /// 
@@ -495,8 +847,16 @@ impl EdfWriter {
     /// 
     /// # Errors
     /// 
-    /// * `EdfError::InvalidFormat` - Trying to modify after header written
-    /// * `EdfError::InvalidArgument` - Duration <= 0 or too large
+    /// * `EdfError::InvalidFormat` - Trying to modify after header written,
+    ///   or duration <= 0 or too large
+    /// 
+    /// # Precision
+    /// 
+    /// The duration is stored as an 8-character number in the header, e.g.
+    /// 1/3 second becomes `0.333333`. The writer rounds to that value and
+    /// uses it for all time-keeping, so annotations and record onsets match
+    /// what readers compute; the change is reported by
+    /// [`header_rounding`](Self::header_rounding).
     /// 
     /// # Common Values
     /// 
@@ -542,8 +902,27 @@ impl EdfWriter {
             return Err(EdfError::InvalidFormat("Data record duration must be between 0 and 3600 seconds".to_string()));
         }
         
-        // Convert seconds to EDFLIB_TIME_DIMENSION units (100 nanoseconds)
-        self.datarecord_duration = (duration_seconds * EDFLIB_TIME_DIMENSION as f64) as i64;
+        // 舍入到头部字段可表示的值，再转换为100纳秒单位
+        let encoded = encode_header_number(duration_seconds, 8)?;
+        let datarecord_duration = (encoded.value * EDFLIB_TIME_DIMENSION as f64).round() as i64;
+        if datarecord_duration <= 0 {
+            return Err(EdfError::InvalidFormat(format!(
+                "Data record duration {} rounds to zero", duration_seconds
+            )));
+        }
+        
+        self.header_rounding.retain(|r| r.field != "datarecord_duration");
+        if encoded.value != duration_seconds {
+            self.header_rounding.push(HeaderRounding {
+                field: "datarecord_duration",
+                signal: None,
+                requested: duration_seconds,
+                written: encoded.value,
+                text: encoded.text,
+            });
+        }
+        
+        self.datarecord_duration = datarecord_duration;
         Ok(())
     }
 
@@ -637,7 +1016,9 @@ impl EdfWriter {
         let info_bytes = info.as_bytes();
         let copy_len = (80 - 22).min(info_bytes.len());
         recording_field[22..22 + copy_len].copy_from_slice(&info_bytes[..copy_len]);
-        assert!(validate_recording_field(&recording_field), "Recording field contains invalid characters");
+        if !validate_recording_field(&recording_field) {
+            return Err(EdfError::InvalidFormat("Recording field contains invalid characters".to_string()));
+        }
         main_header[88..168].copy_from_slice(&recording_field);
         check_recording_field(true, false, &main_header)?;
 
@@ -652,24 +1033,20 @@ impl EdfWriter {
         main_header[176..184].copy_from_slice(time_str.as_bytes());
         
         // 头部大小 (8字节)
-        let header_size_str = format!("{:<8}", header_size);
-        main_header[184..192].copy_from_slice(header_size_str.as_bytes());
+        main_header[184..192].copy_from_slice(&header_field(header_size as f64, 8)?);
         
         // EDF+标识 (44字节)
-        main_header[192..197].copy_from_slice(b"EDF+C");
+        main_header[192..197].copy_from_slice(if self.discontinuous { b"EDF+D" } else { b"EDF+C" });
         
         // 数据记录数 (8字节)
-        let datarecords_str = format!("{:<8}", total_datarecords);
-        main_header[236..244].copy_from_slice(datarecords_str.as_bytes());
+        main_header[236..244].copy_from_slice(&header_field(total_datarecords as f64, 8)?);
         
         // 数据记录持续时间 (8字节)
         let duration_seconds = self.datarecord_duration as f64 / EDFLIB_TIME_DIMENSION as f64;
-        let duration_str = format!("{:<8}", duration_seconds);
-        main_header[244..252].copy_from_slice(duration_str.as_bytes());
+        main_header[244..252].copy_from_slice(&header_field(duration_seconds, 8)?);
         
         // 信号数 (4字节)
-        let signals_str = format!("{:<4}", total_signals);
-        main_header[252..256].copy_from_slice(signals_str.as_bytes());
+        main_header[252..256].copy_from_slice(&header_field(total_signals as f64, 4)?);
         
         self.file.write_all(&main_header)?;
         
@@ -704,13 +1081,27 @@ impl EdfWriter {
     /// * `samples` - Vector of sample vectors, one per signal channel
     ///   - Must contain exactly the same number of vectors as signals added
     ///   - Each vector must contain exactly `samples_per_record` samples
+    ///   - `Vec<f64>`, borrowed `&[f64]` and `f32` data are all accepted, so
+    ///     `&[&eeg[..], &ecg[..]]` avoids copying into new vectors
+    /// 
+    /// To write several records per call use [`write_records`](Self::write_records),
+    /// for raw digital values [`write_digital_samples`](Self::write_digital_samples).
     /// 
     /// # Errors
     /// 
     /// * `EdfError::InvalidFormat` - Wrong number of sample vectors or samples per vector
+    /// * `EdfError::InvalidSample` - A sample was rejected by the [`SamplePolicy`];
+    ///   nothing from this record is written
     /// * `EdfError::FileWriteError` - I/O error during writing
     /// * `EdfError::NotReady` - File headers not written yet
     /// 
+    /// # Out-of-range and Invalid Values
+    /// 
+    /// By default values outside the physical range are clamped and NaN or
+    /// infinite values are rejected. Use [`set_sample_policy`](Self::set_sample_policy)
+    /// to change this; clipped and replaced samples are counted per signal
+    /// (see [`sample_stats`](Self::sample_stats)).
+    /// 
     /// # Sample Organization
     /// 
     /// The `samples` parameter must be organized as:
@@ -836,7 +1227,7 @@ impl EdfWriter {
     ///     physical_max: 10.0, physical_min: -10.0,
     ///     digital_max: 32767, digital_min: -32768,
     ///     samples_per_record: 25,   // 25 Hz sampling rate
-    ///     physical_dimension: "arbitrary".to_string(),
+    ///     physical_dimension: "a.u.".to_string(),
     ///     prefilter: "LP:10Hz".to_string(),
     ///     transducer: "Strain gauge".to_string(),
     /// })?;
@@ -869,153 +1260,704 @@ impl EdfWriter {
     /// # std::fs::remove_file("mixed_rates.edf").ok();
     /// # Ok::<(), edfplus::EdfError>(())
     /// ```
-    pub fn write_samples(&mut self, samples: &[Vec<f64>]) -> Result<()> {
-        if samples.len() != self.signals.len() {
-            return Err(EdfError::InvalidFormat("Sample count must match signal count".to_string()));
+    pub fn write_samples<T, S>(&mut self, samples: &[S]) -> Result<()>
+    where
+        T: Copy + Into<f64>,
+        S: AsRef<[T]>,
+    {
+        self.ensure_stream_empty()?;
+        self.write_record(samples)
+    }
+    
+    /// Appends samples of one signal and writes every data record that is complete
+    /// 
+    /// Streaming alternative to [`write_samples`](Self::write_samples) for
+    /// acquisition sources that deliver variable-size packets per channel.
+    /// Each signal is buffered independently; as soon as every signal has at
+    /// least `samples_per_record` samples buffered, a data record is written.
+    /// A trailing partial record is completed at [`finalize`](Self::finalize)
+    /// according to the [`PaddingPolicy`].
+    /// 
+    /// Don't mix with `write_samples` while samples are buffered.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `signal_idx` - Index of the signal (order in which it was added)
+    /// * `samples` - Physical values, any length
+    /// 
+    /// # Returns
+    /// 
+    /// Number of data records written by this call.
+    /// 
+    /// # Errors
+    /// 
+    /// * `EdfError::InvalidSignalIndex` - No signal with this index was added
+    /// * `EdfError::InvalidSample` - A completed record was rejected by the
+    ///   [`SamplePolicy`]; that record is dropped from the buffers
+    /// * `EdfError::Io` - I/O error during writing
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::{EdfWriter, PaddingPolicy, SignalParam};
+    /// 
+    /// let mut writer = EdfWriter::create("packets.edf")?;
+    /// writer.add_signal(SignalParam::new_eeg("EEG Fp1", 256))?;
+    /// writer.add_signal(SignalParam::new_respiration("Resp", 25))?;
+    /// writer.set_padding_policy(PaddingPolicy::RepeatLast);
+    /// 
+    /// // Packets of arbitrary size arrive per channel
+    /// assert_eq!(writer.push_samples(0, &[1.0; 300])?, 0);
+    /// assert_eq!(writer.push_samples(1, &[0.5; 30])?, 1);  // first record complete
+    /// assert_eq!(writer.buffered_samples(), vec![44, 5]);
+    /// 
+    /// // The partial second record is padded to full length
+    /// writer.finalize()?;
+    /// 
+    /// # std::fs::remove_file("packets.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn push_samples(&mut self, signal_idx: usize, samples: &[f64]) -> Result<usize> {
+        let buffer = self.stream_buffers
+            .get_mut(signal_idx)
+            .ok_or(EdfError::InvalidSignalIndex(signal_idx))?;
+        buffer.extend_from_slice(samples);
+        if let Some(&last) = samples.last() {
+            self.last_pushed[signal_idx] = Some(last);
         }
         
-        // 验证每个信号的样本数
-        for (i, signal_samples) in samples.iter().enumerate() {
-            let expected_samples = self.signals[i].samples_per_record as usize;
-            if signal_samples.len() != expected_samples {
-                return Err(EdfError::InvalidFormat(
-                    format!("Signal {} expected {} samples per record, got {}", 
-                           i, expected_samples, signal_samples.len())
-                ));
-            }
+        self.write_buffered_records()
+    }
+    
+    /// Returns the number of samples buffered per signal by [`push_samples`](Self::push_samples)
+    pub fn buffered_samples(&self) -> Vec<usize> {
+        self.stream_buffers.iter().map(Vec::len).collect()
+    }
+    
+    /// Starts the next data record at `onset_seconds` after the file start
+    /// 
+    /// Data records normally follow each other without gaps. Moving the next
+    /// record later leaves a gap in the recording: the file is then written
+    /// as EDF+D (discontinuous), and every record carries its onset in the
+    /// time-keeping TAL. Annotations whose onset falls in a gap are stored in
+    /// the first record after it.
+    /// 
+    /// # Errors
+    /// 
+    /// * `EdfError::InvalidFormat` - The onset is negative, not finite, or
+    ///   before the end of the previous record, or samples from
+    ///   [`push_samples`](Self::push_samples) are still buffered
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::{EdfReader, EdfWriter, SignalParam};
+    /// 
+    /// let mut writer = EdfWriter::create("gap.edf")?;
+    /// writer.add_signal(SignalParam::new_eeg("EEG Fp1", 256))?;
+    /// writer.write_samples(&[vec![0.0; 256]])?;
+    /// 
+    /// // The amplifier was disconnected for 9 seconds
+    /// writer.set_next_record_onset(10.0)?;
+    /// writer.write_samples(&[vec![0.0; 256]])?;
+    /// writer.finalize()?;
+    /// 
+    /// let reader = EdfReader::open("gap.edf")?;
+    /// assert!(reader.header().discontinuous);
+    /// assert_eq!(reader.record_onsets(), &[0, 100_000_000]);
+    /// 
+    /// # std::fs::remove_file("gap.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn set_next_record_onset(&mut self, onset_seconds: f64) -> Result<()> {
+        if !onset_seconds.is_finite() || onset_seconds < 0.0 {
+            return Err(EdfError::InvalidFormat(format!(
+                "Data record onset must be a non-negative number of seconds, got {}", onset_seconds
+            )));
         }
-        
-        // 如果还没写头部，先写头部
-        if !self.header_written {
-            self.write_header(1)?; // 临时使用1，会在finalize时更新
+        if self.stream_buffers.iter().any(|buffer| !buffer.is_empty()) {
+            return Err(EdfError::InvalidFormat(
+                "Cannot move the next data record while pushed samples are buffered".to_string()
+            ));
         }
         
-        // 按照edflib的顺序写入数据：信号数据 + 注释信号
+        let onset = (onset_seconds * EDFLIB_TIME_DIMENSION as f64).round() as i64;
+        if onset < self.record_onset {
+            return Err(EdfError::InvalidFormat(format!(
+                "Data record onset {} s is before the end of the previous record at {} s",
+                onset_seconds, self.record_onset as f64 / EDFLIB_TIME_DIMENSION as f64
+            )));
+        }
+        if onset > self.record_onset {
+            self.discontinuous = true;
+            self.record_onset = onset;
+        }
+        Ok(())
+    }
+    
+    /// Sets how `finalize` completes a partial record from [`push_samples`](Self::push_samples)
+    pub fn set_padding_policy(&mut self, policy: PaddingPolicy) {
+        self.padding_policy = policy;
+    }
     
-        // 写入所有信号的样本数据
-        for signal_idx in 0..self.signals.len() {
-            let signal = &self.signals[signal_idx];
-            let signal_samples = &samples[signal_idx];
+    /// 写入所有已缓冲完整的数据记录
+    fn write_buffered_records(&mut self) -> Result<usize> {
+        let record_lens = self.record_lens();
+        let mut written = 0;
+        
+        while !record_lens.is_empty()
+            && self.stream_buffers.iter().zip(&record_lens).all(|(buffer, &len)| buffer.len() >= len)
+        {
+            let buffers = std::mem::take(&mut self.stream_buffers);
+            let record: Vec<&[f64]> = buffers.iter().zip(&record_lens)
+                .map(|(buffer, &len)| &buffer[..len])
+                .collect();
+            let result = self.write_record(&record);
+            self.stream_buffers = buffers;
             
-            for &physical_value in signal_samples {
-                let digital_value = signal.to_digital(physical_value);
-                
-                // 应用范围限制
-                let clamped_value = digital_value
-                    .max(signal.digital_min)
-                    .min(signal.digital_max);
-                
-                // 写入为16位小端序
-                let bytes = (clamped_value as i16).to_le_bytes();
-                self.file.write_all(&bytes)?;
+            // 出错时同样丢弃该记录，避免后续调用反复失败
+            for (buffer, &len) in self.stream_buffers.iter_mut().zip(&record_lens) {
+                buffer.drain(..len);
             }
+            result?;
+            written += 1;
         }
         
-        // 写入注释信号的TAL数据 - 支持多个注释通道
-        for channel_idx in 0..self.nr_annot_chns {
-            let annotation_data = self.generate_annotation_tal_for_channel(self.samples_written, channel_idx)?;
-            self.file.write_all(&annotation_data)?;
+        Ok(written)
+    }
+    
+    /// 按填充策略补齐最后一个不完整的流式数据记录
+    fn write_partial_record(&mut self) -> Result<()> {
+        if self.stream_buffers.iter().all(Vec::is_empty) {
+            return Ok(());
         }
         
-        self.samples_written += 1;
+        if self.padding_policy == PaddingPolicy::Discard {
+            self.stream_buffers.iter_mut().for_each(Vec::clear);
+            return Ok(());
+        }
+        
+        // 所有通道补齐到相同的记录数
+        let records = self.signals.iter().zip(&self.stream_buffers)
+            .map(|(signal, buffer)| buffer.len().div_ceil(signal.samples_per_record as usize))
+            .max()
+            .unwrap_or(0);
+        for (i, signal) in self.signals.iter().enumerate() {
+            let pad_value = match self.padding_policy {
+                PaddingPolicy::Value(value) => value,
+                _ => self.last_pushed[i]
+                    .unwrap_or_else(|| 0.0f64.clamp(signal.physical_min, signal.physical_max)),
+            };
+            self.stream_buffers[i].resize(records * signal.samples_per_record as usize, pad_value);
+        }
+        
+        self.write_buffered_records()?;
         Ok(())
     }
     
-    /// Finalizes the EDF+ file and closes it
+    /// Writes raw digital values for all signals to the current data record
     /// 
-    /// This method must be called to complete the file writing process.
-    /// It flushes any remaining data to disk and properly closes the file.
-    /// After calling this method, the writer is consumed and cannot be used again.
+    /// Digital counterpart of [`write_samples`](Self::write_samples) and
+    /// [`EdfReader::read_digital_samples`](crate::EdfReader::read_digital_samples):
+    /// values are stored as given, without a physical round trip. Accepts
+    /// `i16` or `i32` counts, as owned vectors or borrowed slices.
+    /// 
+    /// Values outside `digital_min..=digital_max` are handled by the
+    /// [`ClipPolicy`] of the signal, like out-of-range physical values.
     /// 
     /// # Errors
     /// 
-    /// * `EdfError::FileWriteError` - I/O error during file finalization
+    /// * `EdfError::InvalidFormat` - Wrong number of signals or samples per signal,
+    ///   or samples from [`push_samples`](Self::push_samples) are still buffered
+    /// * `EdfError::InvalidSample` - A value was rejected by the [`ClipPolicy`]
+    /// * `EdfError::Io` - I/O error during writing
     /// 
-    /// # File Integrity
+    /// # Examples
     /// 
-    /// Failing to call `finalize()` may result in:
-    /// - Incomplete file headers
-    /// - Missing data records
-    /// - Corrupted file structure
+    /// ```rust
+    /// use edfplus::{EdfReader, EdfWriter, SignalParam};
     /// 
-    /// Always call `finalize()` when finished writing data.
+    /// let mut writer = EdfWriter::create("digital.edf")?;
+    /// writer.add_signal(SignalParam::new_eeg("EEG Fp1", 4))?;
+    /// 
+    /// // Calibrated counts straight from the amplifier
+    /// let counts: [i16; 4] = [-120, 0, 75, 32767];
+    /// writer.write_digital_samples(&[&counts[..]])?;
+    /// writer.finalize()?;
+    /// 
+    /// let mut reader = EdfReader::open("digital.edf")?;
+    /// assert_eq!(reader.read_digital_samples(0, 4)?, vec![-120, 0, 75, 32767]);
+    /// 
+    /// # std::fs::remove_file("digital.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn write_digital_samples<T, S>(&mut self, samples: &[S]) -> Result<()>
+    where
+        T: Copy + Into<i32>,
+        S: AsRef<[T]>,
+    {
+        self.ensure_stream_empty()?;
+        self.write_digital_record(samples)
+    }
+    
+    /// Writes several consecutive data records of physical values in one call
+    /// 
+    /// Each signal's slice holds the samples of all records back to back, so its
+    /// length must be the same multiple of `samples_per_record` for every signal.
+    /// Accepts `f64` or `f32` data, owned or borrowed.
+    /// 
+    /// # Returns
+    /// 
+    /// Number of data records written.
+    /// 
+    /// # Errors
+    /// 
+    /// Same as [`write_samples`](Self::write_samples). The shape is checked
+    /// before anything is written; if a sample is rejected, the records before
+    /// the one containing it have already been written.
     /// 
     /// # Examples
     /// 
     /// ```rust
     /// use edfplus::{EdfWriter, SignalParam};
     /// 
-    /// let mut writer = EdfWriter::create("final_test.edf")?;
-    /// 
-    /// // Add signal and write data...
-    /// writer.add_signal(SignalParam {
-    ///     label: "Test Signal".to_string(),
-    ///     samples_in_file: 0,
-    ///     physical_max: 1.0, physical_min: -1.0,
-    ///     digital_max: 32767, digital_min: -32768,
-    ///     samples_per_record: 10,
-    ///     physical_dimension: "V".to_string(),
-    ///     prefilter: "None".to_string(),
-    ///     transducer: "Test".to_string(),
-    /// })?;
-    /// 
-    /// let samples = vec![0.1, 0.2, 0.3, 0.4, 0.5, -0.1, -0.2, -0.3, -0.4, -0.5];
-    /// writer.write_samples(&[samples])?;
+    /// let mut writer = EdfWriter::create("records.edf")?;
+    /// writer.add_signal(SignalParam::new_eeg("EEG Fp1", 256))?;
+    /// writer.add_signal(SignalParam::new_respiration("Resp", 25))?;
     /// 
-    /// // Always finalize to ensure file integrity
+    /// // 10 seconds of single precision data
+    /// let eeg = vec![0.0f32; 2560];
+    /// let resp = vec![0.0f32; 250];
+    /// assert_eq!(writer.write_records(&[&eeg[..], &resp[..]])?, 10);
     /// writer.finalize()?;
     /// 
-    /// # // Cleanup (hidden from docs)
-    /// # std::fs::remove_file("final_test.edf").ok();
+    /// # std::fs::remove_file("records.edf").ok();
     /// # Ok::<(), edfplus::EdfError>(())
     /// ```
+    pub fn write_records<T, S>(&mut self, samples: &[S]) -> Result<usize>
+    where
+        T: Copy + Into<f64>,
+        S: AsRef<[T]>,
+    {
+        self.ensure_stream_empty()?;
+        let records = self.record_count_of(samples)?;
+        let record_lens = self.record_lens();
+        let mut record = Vec::with_capacity(samples.len());
+        for index in 0..records {
+            record.clear();
+            record.extend(record_slices(samples, &record_lens, index));
+            self.write_record(&record)?;
+        }
+        Ok(records)
+    }
+    
+    /// Writes several consecutive data records of digital values in one call
     /// 
-    /// ## Error handling during finalization
+    /// Digital counterpart of [`write_records`](Self::write_records).
     /// 
-    /// ```rust
-    /// use edfplus::{EdfWriter, SignalParam};
+    /// # Returns
     /// 
-    /// fn test_finalize() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut writer = EdfWriter::create("error_test.edf")?;
-    ///     // ... add signals and write data ...
-    ///     
-    ///     match writer.finalize() {
-    ///         Ok(()) => println!("File successfully completed"),
-    ///         Err(e) => {
-    ///             eprintln!("Error finalizing file: {}", e);
-    ///             // File may be corrupted
-    ///         }
-    ///     }
-    ///     
-    ///     # // Cleanup (hidden from docs)
-    ///     # std::fs::remove_file("error_test.edf").ok();
-    ///     Ok(())
-    /// }
+    /// Number of data records written.
     /// 
-    /// # test_finalize().unwrap();
-    /// ```
-    pub fn finalize(mut self) -> Result<()> {
-        // 如果有数据写入但头部记录数不正确，需要更新头部
-        if self.header_written && self.samples_written > 1 {
+    /// # Errors
+    /// 
+    /// Same as [`write_digital_samples`](Self::write_digital_samples).
+    pub fn write_digital_records<T, S>(&mut self, samples: &[S]) -> Result<usize>
+    where
+        T: Copy + Into<i32>,
+        S: AsRef<[T]>,
+    {
+        self.ensure_stream_empty()?;
+        let records = self.record_count_of(samples)?;
+        let record_lens = self.record_lens();
+        let mut record = Vec::with_capacity(samples.len());
+        for index in 0..records {
+            record.clear();
+            record.extend(record_slices(samples, &record_lens, index));
+            self.write_digital_record(&record)?;
+        }
+        Ok(records)
+    }
+    
+    /// 缓冲中有流式样本时不允许直接写入记录（会打乱样本顺序）
+    fn ensure_stream_empty(&self) -> Result<()> {
+        if self.stream_buffers.iter().any(|buffer| !buffer.is_empty()) {
+            return Err(EdfError::InvalidFormat(
+                "Cannot write a data record while samples from push_samples are buffered".to_string()
+            ));
+        }
+        Ok(())
+    }
+    
+    /// 计算多记录写入中的记录数，各信号必须一致
+    fn record_count_of<T, S: AsRef<[T]>>(&self, samples: &[S]) -> Result<usize> {
+        if samples.len() != self.signals.len() {
+            return Err(EdfError::InvalidFormat("Sample count must match signal count".to_string()));
+        }
+        
+        let mut records = None;
+        for (i, (signal, signal_samples)) in self.signals.iter().zip(samples).enumerate() {
+            let len = signal_samples.as_ref().len();
+            let per_record = signal.samples_per_record as usize;
+            if len % per_record != 0 || records.is_some_and(|n| n != len / per_record) {
+                return Err(EdfError::InvalidFormat(format!(
+                    "Signal {} has {} samples, which is not {} records of {} samples",
+                    i, len, records.map_or("a whole number of".to_string(), |n: usize| n.to_string()), per_record
+                )));
+            }
+            records = Some(len / per_record);
+        }
+        Ok(records.unwrap_or(0))
+    }
+    
+    /// 每个信号一个数据记录中的样本数
+    fn record_lens(&self) -> Vec<usize> {
+        self.signals.iter()
+            .map(|signal| signal.samples_per_record as usize)
+            .collect()
+    }
+    
+    /// 验证单个数据记录中每个信号的样本数
+    fn check_record_shape<T, S: AsRef<[T]>>(&self, samples: &[S]) -> Result<()> {
+        if samples.len() != self.signals.len() {
+            return Err(EdfError::InvalidFormat("Sample count must match signal count".to_string()));
+        }
+        
+        for (i, signal_samples) in samples.iter().enumerate() {
+            let signal_samples = signal_samples.as_ref();
+            let expected_samples = self.signals[i].samples_per_record as usize;
+            if signal_samples.len() != expected_samples {
+                return Err(EdfError::InvalidFormat(
+                    format!("Signal {} expected {} samples per record, got {}", 
+                           i, expected_samples, signal_samples.len())
+                ));
+            }
+        }
+        Ok(())
+    }
+    
+    /// 写入一个物理值数据记录（信号数据 + 注释）
+    fn write_record<T, S>(&mut self, samples: &[S]) -> Result<()>
+    where
+        T: Copy + Into<f64>,
+        S: AsRef<[T]>,
+    {
+        self.check_record_shape(samples)?;
+        
+        // 滤波器状态在记录成功写入后才更新；未设置滤波器时不复制状态
+        let filtering = self.signal_filters.iter().any(Option::is_some);
+        if filtering && self.filter_states.len() != self.signals.len() {
+            self.filter_states = self.design_filters()?;
+        }
+        let mut filter_states = if filtering { self.filter_states.clone() } else { Vec::new() };
+        let filtered: Vec<Option<Vec<f64>>> = filter_states.iter_mut().zip(samples)
+            .map(|(filter, signal_samples)| filter.as_mut().map(|filter| {
+                let mut values: Vec<f64> = signal_samples.as_ref().iter().map(|&sample| sample.into()).collect();
+                filter.filter(&mut values);
+                values
+            }))
+            .collect();
+        
+        // 先转换整个数据记录，出错时不写入任何数据
+        let mut record = std::mem::take(&mut self.record_buffer);
+        record.clear();
+        let result = self.encode_record(samples, &filtered, &mut record)
+            .and_then(|counts| self.commit_record(&record, counts));
+        self.record_buffer = record;
+        result?;
+        if filtering {
+            self.filter_states = filter_states;
+        }
+        Ok(())
+    }
+    
+    /// 将一个物理值数据记录编码为16位小端序样本，返回各信号的样本统计
+    fn encode_record<T, S>(
+        &self,
+        samples: &[S],
+        filtered: &[Option<Vec<f64>>],
+        record: &mut Vec<u8>,
+    ) -> Result<Vec<(u64, u64, u64)>>
+    where
+        T: Copy + Into<f64>,
+        S: AsRef<[T]>,
+    {
+        let mut counts = Vec::with_capacity(samples.len());
+        for (i, (signal, signal_samples)) in self.signals.iter().zip(samples).enumerate() {
+            let signal_samples = signal_samples.as_ref();
+            let policy = self.signal_policies[i].unwrap_or(self.sample_policy);
+            let (mut clipped, mut invalid) = (0u64, 0u64);
+            let filtered = filtered.get(i).and_then(Option::as_ref);
+            
+            for (j, &sample) in signal_samples.iter().enumerate() {
+                let physical_value: f64 = match filtered {
+                    Some(values) => values[j],
+                    None => sample.into(),
+                };
+                let digital_value = if !physical_value.is_finite() {
+                    match policy.invalid {
+                        InvalidSamplePolicy::Error => {
+                            return Err(EdfError::InvalidSample(format!(
+                                "Signal {} ('{}') sample {} of record {} is {}",
+                                i, signal.label, j, self.samples_written, physical_value
+                            )));
+                        }
+                        InvalidSamplePolicy::Marker(marker) => {
+                            invalid += 1;
+                            marker
+                        }
+                    }
+                } else {
+                    let digital_value = signal.to_digital(physical_value);
+                    if digital_value < signal.digital_min || digital_value > signal.digital_max {
+                        if policy.clip == ClipPolicy::Error {
+                            return Err(EdfError::InvalidSample(format!(
+                                "Signal {} ('{}') sample {} of record {} is {} {}, outside [{}, {}]",
+                                i, signal.label, j, self.samples_written, physical_value,
+                                signal.physical_dimension, signal.physical_min, signal.physical_max
+                            )));
+                        }
+                        clipped += 1;
+                    }
+                    digital_value
+                };
+                
+                // 应用范围限制，写入为16位小端序
+                let clamped_value = digital_value
+                    .max(signal.digital_min)
+                    .min(signal.digital_max);
+                record.extend_from_slice(&(clamped_value as i16).to_le_bytes());
+            }
+            counts.push((signal_samples.len() as u64, clipped, invalid));
+        }
+        Ok(counts)
+    }
+    
+    /// 按当前数据记录时长为每个信号设计滤波器
+    fn design_filters(&self) -> Result<Vec<Option<SignalFilter>>> {
+        let record_seconds = self.datarecord_duration as f64 / EDFLIB_TIME_DIMENSION as f64;
+        self.signals.iter().zip(&self.signal_filters)
+            .map(|(signal, chain)| {
+                chain.as_ref()
+                    .map(|chain| chain.design(signal.samples_per_record as f64 / record_seconds))
+                    .transpose()
+            })
+            .collect()
+    }
+    
+    /// 写入一个数字值数据记录（信号数据 + 注释）
+    fn write_digital_record<T, S>(&mut self, samples: &[S]) -> Result<()>
+    where
+        T: Copy + Into<i32>,
+        S: AsRef<[T]>,
+    {
+        self.check_record_shape(samples)?;
+        if self.signal_filters.iter().any(Option::is_some) {
+            return Err(EdfError::InvalidFormat(
+                "Filtered signals need physical samples; use write_samples".to_string()
+            ));
+        }
+        
+        let mut record = std::mem::take(&mut self.record_buffer);
+        record.clear();
+        let result = self.encode_digital_record(samples, &mut record)
+            .and_then(|counts| self.commit_record(&record, counts));
+        self.record_buffer = record;
+        result
+    }
+    
+    /// 写入从另一个文件复制的原始数据记录（各信号的16位小端序样本，不做范围检查）
+    pub(crate) fn write_raw_record(&mut self, record: &[u8]) -> Result<()> {
+        self.ensure_stream_empty()?;
+        if self.signal_filters.iter().any(Option::is_some) {
+            return Err(EdfError::InvalidFormat(
+                "Filtered signals need physical samples; use write_samples".to_string()
+            ));
+        }
+        let expected: usize = self.signals.iter().map(|s| s.samples_per_record as usize * 2).sum();
+        if record.len() != expected {
+            return Err(EdfError::InvalidFormat(format!(
+                "Raw data record has {} bytes, expected {}", record.len(), expected
+            )));
+        }
+        let counts = self.signals.iter().map(|s| (s.samples_per_record as u64, 0, 0)).collect();
+        self.commit_record(record, counts)
+    }
+    
+    /// 将一个数字值数据记录编码为16位小端序样本，返回各信号的样本统计
+    fn encode_digital_record<T, S>(&self, samples: &[S], record: &mut Vec<u8>) -> Result<Vec<(u64, u64, u64)>>
+    where
+        T: Copy + Into<i32>,
+        S: AsRef<[T]>,
+    {
+        let mut counts = Vec::with_capacity(samples.len());
+        for (i, (signal, signal_samples)) in self.signals.iter().zip(samples).enumerate() {
+            let signal_samples = signal_samples.as_ref();
+            let policy = self.signal_policies[i].unwrap_or(self.sample_policy);
+            let mut clipped = 0u64;
+            
+            for (j, &sample) in signal_samples.iter().enumerate() {
+                let digital_value: i32 = sample.into();
+                if digital_value < signal.digital_min || digital_value > signal.digital_max {
+                    if policy.clip == ClipPolicy::Error {
+                        return Err(EdfError::InvalidSample(format!(
+                            "Signal {} ('{}') sample {} of record {} is digital {}, outside [{}, {}]",
+                            i, signal.label, j, self.samples_written, digital_value,
+                            signal.digital_min, signal.digital_max
+                        )));
+                    }
+                    clipped += 1;
+                }
+                
+                let clamped_value = digital_value
+                    .max(signal.digital_min)
+                    .min(signal.digital_max);
+                record.extend_from_slice(&(clamped_value as i16).to_le_bytes());
+            }
+            counts.push((signal_samples.len() as u64, clipped, 0));
+        }
+        Ok(counts)
+    }
+    
+    /// 写入已编码的信号数据及本记录的注释，并更新统计
+    fn commit_record(&mut self, record: &[u8], counts: Vec<(u64, u64, u64)>) -> Result<()> {
+        // 如果还没写头部，先写头部
+        if !self.header_written {
+            self.write_header(1)?; // 临时使用1，会在finalize时更新
+        }
+        
+        // 按照edflib的顺序写入数据：信号数据 + 注释信号
+        self.file.write_all(record)?;
+        for (stats, (samples, clipped, invalid)) in self.sample_stats.iter_mut().zip(counts) {
+            stats.samples += samples;
+            stats.clipped += clipped;
+            stats.invalid += invalid;
+        }
+        
+        // 写入注释信号的TAL数据 - 支持多个注释通道
+        // 间隙中的注释写入间隙之后的第一个记录
+        let record_end = self.record_onset + self.datarecord_duration;
+        for channel_idx in 0..self.nr_annot_chns {
+            let annotation_data = self.generate_annotation_tal_for_channel(
+                self.annotations_until..record_end, channel_idx,
+            )?;
+            self.file.write_all(&annotation_data)?;
+        }
+        
+        self.samples_written += 1;
+        self.annotations_until = record_end;
+        self.record_onset = record_end;
+        Ok(())
+    }
+    
+    /// Finalizes the EDF+ file and closes it
+    /// 
+    /// This method must be called to complete the file writing process.
+    /// It writes any samples still buffered by [`push_samples`](Self::push_samples)
+    /// (see [`PaddingPolicy`]), flushes remaining data to disk and properly
+    /// closes the file.
+    /// After calling this method, the writer is consumed and cannot be used again.
+    /// 
+    /// # Returns
+    /// 
+    /// The [`SampleStats`] of each signal, in the order the signals were added,
+    /// so acquisitions with clipped or invalid samples can be flagged.
+    /// 
+    /// # Errors
+    /// 
+    /// * `EdfError::FileWriteError` - I/O error during file finalization
+    /// 
+    /// # File Integrity
+    /// 
+    /// Failing to call `finalize()` may result in:
+    /// - Incomplete file headers
+    /// - Missing data records
+    /// - Corrupted file structure
+    /// 
+    /// Always call `finalize()` when finished writing data.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use edfplus::{EdfWriter, SignalParam};
+    /// 
+    /// let mut writer = EdfWriter::create("final_test.edf")?;
+    /// 
+    /// // Add signal and write data...
+    /// writer.add_signal(SignalParam {
+    ///     label: "Test Signal".to_string(),
+    ///     samples_in_file: 0,
+    ///     physical_max: 1.0, physical_min: -1.0,
+    ///     digital_max: 32767, digital_min: -32768,
+    ///     samples_per_record: 10,
+    ///     physical_dimension: "V".to_string(),
+    ///     prefilter: "None".to_string(),
+    ///     transducer: "Test".to_string(),
+    /// })?;
+    /// 
+    /// let samples = vec![0.1, 0.2, 0.3, 0.4, 0.5, -0.1, -0.2, -0.3, -0.4, -0.5];
+    /// writer.write_samples(&[samples])?;
+    /// 
+    /// // Always finalize to ensure file integrity
+    /// writer.finalize()?;
+    /// 
+    /// # // Cleanup (hidden from docs)
+    /// # std::fs::remove_file("final_test.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    /// 
+    /// ## Error handling during finalization
+    /// 
+    /// ```rust
+    /// use edfplus::{EdfWriter, SignalParam};
+    /// 
+    /// fn test_finalize() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut writer = EdfWriter::create("error_test.edf")?;
+    ///     // ... add signals and write data ...
+    ///     
+    ///     match writer.finalize() {
+    ///         Ok(_) => println!("File successfully completed"),
+    ///         Err(e) => {
+    ///             eprintln!("Error finalizing file: {}", e);
+    ///             // File may be corrupted
+    ///         }
+    ///     }
+    ///     
+    ///     # // Cleanup (hidden from docs)
+    ///     # std::fs::remove_file("error_test.edf").ok();
+    ///     Ok(())
+    /// }
+    /// 
+    /// # test_finalize().unwrap();
+    /// ```
+    pub fn finalize(mut self) -> Result<Vec<SampleStats>> {
+        // 补齐流式写入剩余的不完整记录
+        self.write_partial_record()?;
+        
+        // 如果有数据写入但头部记录数或文件类型不正确，需要更新头部
+        if self.header_written && (self.samples_written > 1 || self.discontinuous) {
             use std::io::{Seek, SeekFrom};
             
             // 刷新缓冲区以确保所有数据都写入了
             self.file.flush()?;
             
-            // 获取内部文件引用并seek到数据记录数位置 (236-244字节)
-            let mut file = self.file.into_inner().map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+            // 获取内部文件引用，更新文件类型 (192-197字节)
+            let mut file = self.file.into_inner().map_err(std::io::Error::other)?;
+            file.seek(SeekFrom::Start(192))?;
+            file.write_all(if self.discontinuous { b"EDF+D" } else { b"EDF+C" })?;
+            
+            // seek到数据记录数位置 (236-244字节)
             file.seek(SeekFrom::Start(236))?;
             
             // 更新数据记录数
-            let datarecords_str = format!("{:<8}", self.samples_written);
-            file.write_all(datarecords_str.as_bytes())?;
+            file.write_all(&header_field(self.samples_written as f64, 8)?)?;
             
             // 确保数据写入磁盘
             file.flush()?;
         } else {
             self.file.flush()?;
         }
-        Ok(())
+        Ok(self.sample_stats)
     }
     
     /// Adds an annotation/event to the EDF+ file
@@ -1241,9 +2183,9 @@ impl EdfWriter {
         }
         
         // Convert to internal time units (100 nanoseconds)
-        let onset = (onset_seconds * EDFLIB_TIME_DIMENSION as f64) as i64;
+        let onset = (onset_seconds * EDFLIB_TIME_DIMENSION as f64).round() as i64;
         let duration = duration_seconds
-            .map(|d| (d * EDFLIB_TIME_DIMENSION as f64) as i64)
+            .map(|d| (d * EDFLIB_TIME_DIMENSION as f64).round() as i64)
             .unwrap_or(-1);
         
         // Create and store annotation
@@ -1295,7 +2237,8 @@ impl EdfWriter {
     /// 
     /// # Arguments
     /// 
-    /// * `data_record_index` - 数据记录索引
+    /// * `window` - 写入本记录的注释的时间范围，结束于记录末尾；
+    ///   开始于上一个记录的末尾（EDF+D 中可能早于本记录的开始）
     /// * `channel_idx` - 注释通道索引 (0 到 nr_annot_chns-1)
     /// 
     /// # Channel Distribution Strategy
@@ -1307,32 +2250,17 @@ impl EdfWriter {
     /// # Returns
     /// 
     /// 返回120字节的TAL数据，严格符合EDF+标准格式
-    fn generate_annotation_tal_for_channel(&self, data_record_index: usize, channel_idx: usize) -> Result<Vec<u8>> {
+    fn generate_annotation_tal_for_channel(&self, window: std::ops::Range<i64>, channel_idx: usize) -> Result<Vec<u8>> {
         let mut tal_data = Vec::with_capacity(EDFLIB_ANNOTATION_BYTES);
         
-        // 数据记录的时间范围
-        let data_record_time_start = data_record_index as f64 * (self.datarecord_duration as f64 / EDFLIB_TIME_DIMENSION as f64);
-        let data_record_time_end = (data_record_index + 1) as f64 * (self.datarecord_duration as f64 / EDFLIB_TIME_DIMENSION as f64);
-        
         // 第一个注释通道处理时间戳记录（遵循edflib设计）
         if channel_idx == 0 {
             // 时间戳注释，格式: "+<onset>\x14\x14\x00"
             tal_data.push(b'+');
             
-            // 添加子秒精度支持
-            if data_record_index == 0 && self.starttime_subsecond > 0 {
-                // 第一个记录包含子秒开始时间
-                let time_with_subsecond = data_record_time_start + (self.starttime_subsecond as f64 / EDFLIB_TIME_DIMENSION as f64);
-                let time_str = format!("{:.7}", time_with_subsecond).trim_end_matches('0').trim_end_matches('.').to_string();
-                tal_data.extend_from_slice(time_str.as_bytes());
-            } else {
-                let time_str = if data_record_time_start.fract() == 0.0 {
-                    format!("{}", data_record_time_start as i64)
-                } else {
-                    format!("{:.6}", data_record_time_start).trim_end_matches('0').trim_end_matches('.').to_string()
-                };
-                tal_data.extend_from_slice(time_str.as_bytes());
-            }
+            // 每个记录的时间戳都包含子秒开始时间（相对于头部的整秒开始时间）
+            let record_time = self.record_onset + self.starttime_subsecond;
+            tal_data.extend_from_slice(format_tal_time(record_time).as_bytes());
             
             tal_data.push(0x14); // ASCII 20 - start of annotation
             tal_data.push(0x14); // ASCII 20 - end of annotation (empty)
@@ -1342,10 +2270,8 @@ impl EdfWriter {
         // 查找属于当前数据记录和注释通道的注释
         let mut record_annotations = Vec::new();
         for (annot_idx, annotation) in self.annotations.iter().enumerate() {
-            let annotation_time = annotation.onset as f64 / EDFLIB_TIME_DIMENSION as f64;
-            
             // 检查注释是否属于当前数据记录
-            if annotation_time >= data_record_time_start && annotation_time < data_record_time_end {
+            if window.contains(&annotation.onset) {
                 // 按照edflib策略分配注释到通道
                 let target_channel = if self.nr_annot_chns == 1 {
                     0 // 单通道模式，所有注释都在通道0
@@ -1377,15 +2303,14 @@ impl EdfWriter {
         
         // 添加分配给当前通道的注释
         for annotation in record_annotations {
-            let annotation_time = annotation.onset as f64 / EDFLIB_TIME_DIMENSION as f64;
+            // TAL中的时间相对于头部的整秒开始时间，需要加上子秒偏移
+            let time_str = format_tal_time(annotation.onset + self.starttime_subsecond);
             
             // 计算基本注释结构所需的最小空间
-            let time_str = format!("{:.7}", annotation_time).trim_end_matches('0').trim_end_matches('.').to_string();
             let mut min_needed_space = 1 + time_str.len() + 2 + 1; // +, time, \x14, \x14 (不包括描述)
             
             if annotation.duration >= 0 {
-                let duration_str = format!("{:.7}", annotation.duration as f64 / EDFLIB_TIME_DIMENSION as f64)
-                    .trim_end_matches('0').trim_end_matches('.').to_string();
+                let duration_str = format_tal_time(annotation.duration);
                 min_needed_space += 1 + duration_str.len(); // \x15 + duration
             }
             
@@ -1401,8 +2326,7 @@ impl EdfWriter {
             // 添加持续时间（如果指定）
             if annotation.duration >= 0 {
                 tal_data.push(0x15); // ASCII 21 - duration separator
-                let duration_str = format!("{:.7}", annotation.duration as f64 / EDFLIB_TIME_DIMENSION as f64)
-                    .trim_end_matches('0').trim_end_matches('.').to_string();
+                let duration_str = format_tal_time(annotation.duration);
                 tal_data.extend_from_slice(duration_str.as_bytes());
             }
             
@@ -1424,13 +2348,57 @@ impl EdfWriter {
         Ok(tal_data)
     }
 
+    /// Sets the start date and time of the recording
+    /// 
+    /// Fractions of a second are stored as the sub-second start time (see
+    /// [`set_subsecond_starttime`](Self::set_subsecond_starttime)) with
+    /// 100-nanosecond resolution. Defaults to 1985-01-01 00:00:00.
+    /// 
+    /// # Errors
+    /// 
+    /// * `EdfError::InvalidFormat` - Trying to modify after header written, or
+    ///   the year is outside 1985..=2084 (the header stores two-digit years)
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use chrono::NaiveDate;
+    /// use edfplus::EdfWriter;
+    /// 
+    /// let mut writer = EdfWriter::create("dated.edf")?;
+    /// let start = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap()
+    ///     .and_hms_milli_opt(22, 30, 0, 250).unwrap();
+    /// writer.set_start_datetime(start)?;
+    /// 
+    /// # std::fs::remove_file("dated.edf").ok();
+    /// # Ok::<(), edfplus::EdfError>(())
+    /// ```
+    pub fn set_start_datetime(&mut self, datetime: NaiveDateTime) -> Result<()> {
+        if self.header_written {
+            return Err(EdfError::InvalidFormat("Cannot modify start date and time after writing header".to_string()));
+        }
+        
+        if !(1985..=2084).contains(&datetime.year()) {
+            return Err(EdfError::InvalidFormat(format!(
+                "Start year {} cannot be stored in an EDF header (1985-2084)", datetime.year()
+            )));
+        }
+        
+        self.start_date = datetime.date();
+        self.start_time = datetime.time().with_nanosecond(0).unwrap_or(datetime.time());
+        // 闰秒表示的纳秒值会超过1秒，限制在有效范围内
+        let nanos = datetime.time().nanosecond().min(999_999_999) as i64;
+        self.starttime_subsecond = nanos / 100;
+        Ok(())
+    }
+
     // 添加subsecond开始时间支持
     pub fn set_subsecond_starttime(&mut self, subsecond: i64) -> Result<()> {
         if self.header_written {
             return Err(EdfError::InvalidFormat("Cannot modify subsecond start time after writing header".to_string()));
         }
         
-        if subsecond < 0 || subsecond >= EDFLIB_TIME_DIMENSION {
+        if !(0..EDFLIB_TIME_DIMENSION).contains(&subsecond) {
             return Err(EdfError::InvalidFormat("Subsecond must be between 0 and 9999999".to_string()));
         }
         
@@ -1476,26 +2444,22 @@ impl EdfWriter {
         
         // 4. 物理最小值 (8字节 × 信号数)
         for signal in &all_signals {
-            let phys_min_str = format!("{:<8}", signal.physical_min);
-            self.file.write_all(phys_min_str.as_bytes())?;
+            self.file.write_all(&header_field(signal.physical_min, 8)?)?;
         }
         
         // 5. 物理最大值 (8字节 × 信号数)
         for signal in &all_signals {
-            let phys_max_str = format!("{:<8}", signal.physical_max);
-            self.file.write_all(phys_max_str.as_bytes())?;
+            self.file.write_all(&header_field(signal.physical_max, 8)?)?;
         }
         
         // 6. 数字最小值 (8字节 × 信号数)
         for signal in &all_signals {
-            let dig_min_str = format!("{:<8}", signal.digital_min);
-            self.file.write_all(dig_min_str.as_bytes())?;
+            self.file.write_all(&header_field(signal.digital_min as f64, 8)?)?;
         }
         
         // 7. 数字最大值 (8字节 × 信号数)
         for signal in &all_signals {
-            let dig_max_str = format!("{:<8}", signal.digital_max);
-            self.file.write_all(dig_max_str.as_bytes())?;
+            self.file.write_all(&header_field(signal.digital_max as f64, 8)?)?;
         }
         
         // 8. 预滤波 (80字节 × 信号数)
@@ -1509,8 +2473,7 @@ impl EdfWriter {
         
         // 9. 每记录样本数 (8字节 × 信号数)
         for signal in &all_signals {
-            let samples_str = format!("{:<8}", signal.samples_per_record);
-            self.file.write_all(samples_str.as_bytes())?;
+            self.file.write_all(&header_field(signal.samples_per_record as f64, 8)?)?;
         }
         
         // 10. 保留字段 (32字节 × 信号数)
@@ -1568,6 +2531,173 @@ impl EdfWriter {
 
 }
 
+/// 多记录数据中第 `record` 个记录的各信号切片
+fn record_slices<'a, T: 'a, S: AsRef<[T]>>(
+    samples: &'a [S],
+    record_lens: &'a [usize],
+    record: usize,
+) -> impl Iterator<Item = &'a [T]> + 'a {
+    samples.iter().zip(record_lens)
+        .map(move |(signal_samples, &len)| &signal_samples.as_ref()[record * len..(record + 1) * len])
+}
+
+/// 将100纳秒单位的非负时间格式化为TAL时间文本（不含符号，去掉多余的零）
+fn format_tal_time(time: i64) -> String {
+    let seconds = time / EDFLIB_TIME_DIMENSION;
+    let fraction = time % EDFLIB_TIME_DIMENSION;
+    if fraction == 0 {
+        seconds.to_string()
+    } else {
+        let fraction = format!("{:07}", fraction);
+        format!("{}.{}", seconds, fraction.trim_end_matches('0'))
+    }
+}
+
+// 工具函数：编码数值头部字段，左对齐并用空格填充到固定宽度
+fn header_field(value: f64, width: usize) -> Result<Vec<u8>> {
+    let encoded = encode_header_number(value, width)?;
+    let mut field = vec![b' '; width];
+    field[..encoded.text.len()].copy_from_slice(encoded.text.as_bytes());
+    Ok(field)
+}
+
+// 工具函数：将字符串转换为 7-bit ASCII，非 ASCII 替换为 '_'
+fn to_ascii(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_ascii() { c } else { '_' })
+        .collect()
+}
+
+fn validate_recording_field(field: &[u8]) -> bool {
+    if field.len() != 80 { return false; }
+    for &b in field {
+        if !(32..=126).contains(&b) { return false; } // 非 ASCII
+    }
+    true
+}
+
+fn check_recording_field(edfplus: bool, bdfplus: bool, header: &[u8]) -> Result<()> {
+    if !edfplus && !bdfplus {
+        return Ok(());
+    }
+
+    if header.len() < 88 + 80 {
+        return Err(EdfError::InvalidFormat("Header too short".to_string()));
+    }
+    // 以下按字节位置检查，要求全部为可打印ASCII
+    if !validate_recording_field(&header[88..88 + 80]) {
+        return Err(EdfError::InvalidFormat("Recording field contains invalid characters".to_string()));
+    }
+
+    let scratchpad_128 = &header[88..88 + 80];
+    let scratchpad_str = std::str::from_utf8(scratchpad_128)
+        .map_err(|_| EdfError::InvalidFormat("Recording field is not valid UTF-8".to_string()))?;
+
+    let mut error = false;
+
+    // 前 10 字节必须是 "Startdate "
+    if !scratchpad_str.starts_with("Startdate ") {
+        return Err(EdfError::InvalidFormat(
+            "Recording field must start with 'Startdate '".to_string(),
+        ));
+    }
+
+    let plus_startdate_offset = 10;
+    let p;
+
+    if scratchpad_str.as_bytes()[plus_startdate_offset] == b'X' {
+        if scratchpad_str.as_bytes()[plus_startdate_offset + 1] != b' ' {
+            error = true;
+            println!("Error: Expected space after 'X' in Startdate field, plus_startdate_offset + 1");
+        }
+        if scratchpad_str.as_bytes()[plus_startdate_offset + 2] == b' ' {
+            error = true;
+            println!("Error: Expected space after 'X' in Startdate field, plus_startdate_offset + 2");
+        }
+        p = plus_startdate_offset + 2;
+    } else {
+        // 日期 dd-MMM-yy
+        if scratchpad_str.as_bytes()[21] != b' ' || scratchpad_str.as_bytes()[22] == b' ' {
+            error = true;
+            println!("{} {} {}", b' ', scratchpad_str.as_bytes()[21], scratchpad_str.as_bytes()[22]);
+            println!("Error: Invalid date format in Startdate field, 21");
+        }
+        p = 22;
+
+        let scratchpad_64 = &scratchpad_str[plus_startdate_offset..plus_startdate_offset + 11];
+        let bytes_64 = scratchpad_64.as_bytes();
+
+        if bytes_64[2] != b'-' || bytes_64[6] != b'-' {
+            error = true;
+            println!("Error: Invalid date format in Startdate field, 2 or 6");
+        }
+
+        // 天两位
+        if !bytes_64[0].is_ascii_digit() || !bytes_64[1].is_ascii_digit() {
+            error = true;
+            println!("Error: Invalid date format in Startdate field, 0 or 1");
+        }
+        // 年两位， // || !bytes_64[9].is_ascii_digit() || !bytes_64[10].is_ascii_digit()
+        if !bytes_64[7].is_ascii_digit() || !bytes_64[8].is_ascii_digit()   || !bytes_64[9].is_ascii_digit() || !bytes_64[10].is_ascii_digit()
+        {
+            error = true;
+            // print value
+            println!("{} {} {} {}", bytes_64[7], bytes_64[8], bytes_64[9], bytes_64[10]);
+            println!("Error: Invalid date format in Startdate field, 7 to 10");
+        }
+
+        // 天有效性
+        let day: u32 = scratchpad_64[0..2].parse().unwrap_or(0);
+        if !(1..=31).contains(&day) {
+            error = true;
+            println!("Error: Invalid day in Startdate field");
+        }
+
+        // 月份检查
+        let month_str = &scratchpad_64[3..6];
+        if !matches!(
+            month_str,
+            "JAN" | "FEB" | "MAR" | "APR" | "MAY" | "JUN" | "JUL" | "AUG" | "SEP" | "OCT" | "NOV" | "DEC"
+        ) {
+            error = true;
+            println!("Error: Invalid month in Startdate field");
+        }
+    }
+
+    // 检查空格规则
+    let scratchpad_bytes = scratchpad_str.as_bytes();
+    let mut n = 0;
+    for i in p..80 {
+        if i > 78 {
+            error = true;
+            println!("Error: Invalid space in Startdate field, i: {}", i);
+            break;
+        }
+        if scratchpad_bytes[i] == b' ' {
+            n += 1;
+            if scratchpad_bytes[i + 1] == b' ' {
+                error = true;
+                println!("Error: Invalid space in Startdate field, i: {}, i+1", i);
+                break;
+            }
+        }
+        if n > 1 {
+            break;
+        }
+    }
+
+    if error {
+        let msg = if edfplus {
+            format!("Error, file is marked as EDF+ but recording field does not comply to the EDF+ standard:\n\"{}\"", scratchpad_str)
+        } else {
+            format!("Error, file is marked as BDF+ but recording field does not comply to the BDF+ standard:\n\"{}\"", scratchpad_str)
+        };
+        return Err(EdfError::InvalidFormat(msg));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1672,8 +2802,8 @@ mod tests {
         writer.add_signal(create_test_signal()).unwrap();
         
         // Test TAL generation for multiple channels
-        let tal_0 = writer.generate_annotation_tal_for_channel(0, 0).unwrap();
-        let tal_1 = writer.generate_annotation_tal_for_channel(1, 0).unwrap();
+        let tal_0 = writer.generate_annotation_tal_for_channel(0..10_000_000, 0).unwrap();
+        let tal_1 = writer.generate_annotation_tal_for_channel(10_000_000..20_000_000, 0).unwrap();
         
         // Both channels should have some content
         assert!(!tal_0.is_empty());
@@ -1740,152 +2870,3 @@ mod tests {
         cleanup_test_file(filename);
     }
 }
-
-// 工具函数：将字符串转换为 7-bit ASCII，非 ASCII 替换为 '_'
-fn to_ascii(s: &str) -> String {
-    s.chars()
-        .map(|c| if c.is_ascii() { c } else { '_' })
-        .collect()
-}
-
-fn validate_recording_field(field: &[u8]) -> bool {
-    if field.len() != 80 { return false; }
-    for &b in field {
-        if b < 32 || b > 126 { return false; } // 非 ASCII
-    }
-    true
-}
-
-fn check_recording_field(edfplus: bool, bdfplus: bool, header: &[u8]) -> Result<()> {
-    println!("Header: {:?}", header);
-
-    if !edfplus && !bdfplus {
-        return Ok(());
-    }
-
-    if header.len() < 88 + 80 {
-        return Err(EdfError::InvalidFormat("Header too short".to_string()));
-    }
-
-    let scratchpad_128 = &header[88..88 + 80];
-    let scratchpad_str = std::str::from_utf8(scratchpad_128)
-        .map_err(|_| EdfError::InvalidFormat("Recording field is not valid UTF-8".to_string()))?;
-
-    let mut error = false;
-
-    // 前 10 字节必须是 "Startdate "
-    if !scratchpad_str.starts_with("Startdate ") {
-        error = true;
-        return Err(EdfError::InvalidFormat(
-            "Recording field must start with 'Startdate '".to_string(),
-        ));
-    }
-
-    let plus_startdate_offset = 10;
-    let mut p = 0;
-
-    if scratchpad_str.as_bytes()[plus_startdate_offset] == b'X' {
-        if scratchpad_str.as_bytes()[plus_startdate_offset + 1] != b' ' {
-            error = true;
-            println!("Error: Expected space after 'X' in Startdate field, plus_startdate_offset + 1");
-        }
-        if scratchpad_str.as_bytes()[plus_startdate_offset + 2] == b' ' {
-            error = true;
-            println!("Error: Expected space after 'X' in Startdate field, plus_startdate_offset + 2");
-        }
-        p = plus_startdate_offset + 2;
-    } else {
-        // 日期 dd-MMM-yy
-        if scratchpad_str.as_bytes()[21] != b' ' || scratchpad_str.as_bytes()[22] == b' ' {
-            error = true;
-            println!("{} {} {}", b' ', scratchpad_str.as_bytes()[21], scratchpad_str.as_bytes()[22]);
-            println!("Error: Invalid date format in Startdate field, 21");
-        }
-        p = 22;
-
-        let scratchpad_64 = &scratchpad_str[plus_startdate_offset..plus_startdate_offset + 11];
-        let bytes_64 = scratchpad_64.as_bytes();
-
-        if bytes_64[2] != b'-' || bytes_64[6] != b'-' {
-            error = true;
-            println!("Error: Invalid date format in Startdate field, 2 or 6");
-        }
-
-        // 天两位
-        if !bytes_64[0].is_ascii_digit() || !bytes_64[1].is_ascii_digit() {
-            error = true;
-            println!("Error: Invalid date format in Startdate field, 0 or 1");
-        }
-        // 年两位， // || !bytes_64[9].is_ascii_digit() || !bytes_64[10].is_ascii_digit()
-        if !bytes_64[7].is_ascii_digit() || !bytes_64[8].is_ascii_digit()   || !bytes_64[9].is_ascii_digit() || !bytes_64[10].is_ascii_digit()
-        {
-            error = true;
-            // print value
-            println!("{} {} {} {}", bytes_64[7], bytes_64[8], bytes_64[9], bytes_64[10]);
-            println!("Error: Invalid date format in Startdate field, 7 to 10");
-        }
-
-        // 天有效性
-        let day: u32 = std::str::from_utf8(&bytes_64[0..2])
-            .unwrap()
-            .parse()
-            .unwrap_or(0);
-        if day < 1 || day > 31 {
-            error = true;
-            println!("Error: Invalid day in Startdate field");
-        }
-
-        // 月份检查
-        let month_str = &scratchpad_64[3..6];
-        let month = match month_str {
-            "JAN" => 1,
-            "FEB" => 2,
-            "MAR" => 3,
-            "APR" => 4,
-            "MAY" => 5,
-            "JUN" => 6,
-            "JUL" => 7,
-            "AUG" => 8,
-            "SEP" => 9,
-            "OCT" => 10,
-            "NOV" => 11,
-            "DEC" => 12,
-            _ => { error = true;
-                println!("Error: Invalid month in Startdate field");
-                 0 }
-        };
-    }
-
-    // 检查空格规则
-    let scratchpad_bytes = scratchpad_str.as_bytes();
-    let mut n = 0;
-    for i in p..80 {
-        if i > 78 {
-            error = true;
-            println!("Error: Invalid space in Startdate field, i: {}", i);
-            break;
-        }
-        if scratchpad_bytes[i] == b' ' {
-            n += 1;
-            if scratchpad_bytes[i + 1] == b' ' {
-                error = true;
-                println!("Error: Invalid space in Startdate field, i: {}, i+1", i);
-                break;
-            }
-        }
-        if n > 1 {
-            break;
-        }
-    }
-
-    if error {
-        let msg = if edfplus {
-            format!("Error, file is marked as EDF+ but recording field does not comply to the EDF+ standard:\n\"{}\"", scratchpad_str)
-        } else {
-            format!("Error, file is marked as BDF+ but recording field does not comply to the BDF+ standard:\n\"{}\"", scratchpad_str)
-        };
-        return Err(EdfError::InvalidFormat(msg));
-    }
-
-    Ok(())
-}
@@ -67,13 +67,11 @@ fn test_basic_annotation_write_read() {
         assert_eq!(annotations.len(), 5);
         
         // 验证具体注释内容
-        let expected_annotations = vec![
-            (0.0, None, "Recording Start"),
+        let expected_annotations = [(0.0, None, "Recording Start"),
             (1.5, Some(2.0), "Sleep Stage N1"),
             (3.5, None, "Eye Movement"),
             (5.2, Some(0.5), "Artifact"),
-            (7.8, None, "K-Complex"),
-        ];
+            (7.8, None, "K-Complex")];
         
         for (i, (expected_onset, expected_duration, expected_desc)) in expected_annotations.iter().enumerate() {
             let annotation = &annotations[i];
@@ -131,7 +129,7 @@ fn test_annotation_time_precision() {
         writer.add_annotation(0.0001, None, "Microsecond Event").unwrap();      // 0.1ms
         writer.add_annotation(0.1234567, None, "High Precision").unwrap();      // 123.4567ms
         writer.add_annotation(1.9999999, Some(0.0000001), "Nanosecond Duration").unwrap(); // 100ns duration
-        writer.add_annotation(3.141592653589793, None, "Pi Seconds").unwrap();  // π秒
+        writer.add_annotation(std::f64::consts::PI, None, "Pi Seconds").unwrap();  // π秒
         
         // 写入5秒的数据
         for second in 0..5 {
@@ -155,12 +153,10 @@ fn test_annotation_time_precision() {
         assert_eq!(annotations.len(), 4);
         
         // 验证高精度时间（EDF+内部使用100纳秒单位）
-        let precision_tests = vec![
-            (0.0001, "Microsecond Event"),
+        let precision_tests = [(0.0001, "Microsecond Event"),
             (0.1234567, "High Precision"),
             (1.9999999, "Nanosecond Duration"),
-            (3.141592653589793, "Pi Seconds"),
-        ];
+            (std::f64::consts::PI, "Pi Seconds")];
         
         for (i, (expected_time, expected_desc)) in precision_tests.iter().enumerate() {
             let annotation = &annotations[i];
@@ -593,7 +589,7 @@ fn test_edf_header_fields_comprehensive() {
             digital_max: 32767,
             digital_min: -32768,
             samples_per_record: 1,   // 1 Hz
-            physical_dimension: "°C".to_string(),
+            physical_dimension: "degC".to_string(),
             prefilter: "".to_string(),
             transducer: "Thermistor probe".to_string(),
         };
@@ -679,14 +675,12 @@ fn test_edf_header_fields_comprehensive() {
         println!("  Annotations read: {} (expected: 6)", annotations.len());
         
         // 验证注释内容
-        let expected_annotations = vec![
-            "Recording start",
+        let expected_annotations = ["Recording start",
             "Test event 1", 
             "Marker point",
             "Test event 2",
             "End marker",
-            "Recording end"
-        ];
+            "Recording end"];
         
         for (i, expected_desc) in expected_annotations.iter().enumerate() {
             assert_eq!(annotations[i].description, *expected_desc,
@@ -742,7 +736,7 @@ fn test_edf_header_fields_comprehensive() {
         
         assert_eq!(header.signals[2].label, "Temperature");
         assert_eq!(header.signals[2].samples_per_record, 1);
-        assert_eq!(header.signals[2].physical_dimension, "°C");
+        assert_eq!(header.signals[2].physical_dimension, "degC");
         
         // 验证注释详细信息
         println!("\n📋 Annotation Details:");
@@ -937,7 +931,7 @@ fn test_header_fields_maximum_annotations() {
                    i, annotation.description);
             
             // 验证时间在合理范围内
-            assert!(actual_onset >= 0.0 && actual_onset < 30.0,
+            assert!((0.0..30.0).contains(&actual_onset),
                    "Annotation {} time should be in [0,30): {:.3}s", 
                    i, actual_onset);
         }
@@ -1139,7 +1133,7 @@ fn test_multiple_annotations_per_record() {
         println!("\n📄 Test Summary:");
         println!("  • Single record can store {} annotations in 120 bytes", record_0_annotations.len());
         println!("  • Average space per annotation: ~{:.1} bytes", 
-                if record_0_annotations.len() > 0 { 
+                if !record_0_annotations.is_empty() { 
                     estimated_tal_usage as f64 / record_0_annotations.len() as f64 
                 } else { 0.0 });
         
@@ -1250,3 +1244,142 @@ fn test_tal_buffer_stress_test() {
     
     cleanup_test_file(filename);
 }
+
+#[test]
+fn test_data_record_iterator() {
+    let filename = "test_record_iterator.edf";
+
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.add_signal(create_test_signal()).unwrap();
+        writer.add_signal(SignalParam::new_respiration("Resp", 25)).unwrap();
+        writer.set_number_of_annotation_signals(2).unwrap();
+        writer.set_datarecord_duration(0.5).unwrap();
+        writer.add_annotation(0.1, None, "Start").unwrap();
+        writer.add_annotation(1.2, Some(0.3), "Arousal").unwrap();
+
+        for record in 0..4 {
+            let eeg = vec![record as f64 * 10.0; 256];
+            let resp = vec![-(record as f64); 25];
+            writer.write_samples(&[eeg, resp]).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = EdfReader::open(filename).unwrap();
+    let eeg = reader.header().signals[0].clone();
+    let resp = reader.header().signals[1].clone();
+
+    let records: Vec<_> = reader.records().collect::<Result<_, _>>().unwrap();
+    assert_eq!(records.len(), 4);
+
+    let mut descriptions = Vec::new();
+    for (i, record) in records.iter().enumerate() {
+        assert_eq!(record.index, i);
+        assert_eq!(record.onset, i as i64 * 5_000_000);
+        assert_eq!(record.signal_count(), 2);
+        assert_eq!(record.signal(0).len(), 256);
+        assert_eq!(record.signal(1).len(), 25);
+        assert!((eeg.to_physical(record.signal(0)[0]) - i as f64 * 10.0).abs() < 0.01);
+        assert!((resp.to_physical(record.signal(1)[24]) + i as f64).abs() < 0.01);
+        assert_eq!(record.annotations.len(), 2);
+        for channel in &record.annotations {
+            descriptions.extend(channel.iter().map(|a| (i, a.description.clone())));
+        }
+    }
+    assert_eq!(descriptions, vec![(0, "Start".to_string()), (2, "Arousal".to_string())]);
+    assert_eq!(records[2].annotations.iter().flatten().next().unwrap().duration, 3_000_000);
+
+    // 迭代不影响按信号读取的位置
+    assert_eq!(reader.tell(0).unwrap(), 0);
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_subsecond_start_round_trip() {
+    let filename = "test_subsecond_start_round_trip.edf";
+    
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.add_signal(SignalParam::new_eeg("EEG Fp1", 100)).unwrap();
+        // 记录从 0.25 秒开始
+        writer.set_subsecond_starttime(2_500_000).unwrap();
+        writer.add_annotation(0.1, Some(0.3), "Early").unwrap();
+        writer.add_annotation(1.7, None, "Late").unwrap();
+        for _ in 0..3 {
+            writer.write_samples(&[vec![0.0; 100]]).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+    
+    // 每个记录的时间戳和注释时间都相对于头部的整秒开始时间
+    let bytes = fs::read(filename).unwrap();
+    let contains = |text: &[u8]| bytes.windows(text.len()).any(|window| window == text);
+    assert!(contains(b"+0.25\x14\x14\x00"));
+    assert!(contains(b"+1.25\x14\x14\x00"));
+    assert!(contains(b"+2.25\x14\x14\x00"));
+    assert!(contains(b"+0.35\x150.3\x14Early\x14"));
+    assert!(contains(b"+1.95\x14Late\x14"));
+    
+    let mut reader = EdfReader::open(filename).unwrap();
+    assert_eq!(reader.header().starttime_subsecond, 2_500_000);
+    let annotations: Vec<(i64, i64, &str)> = reader.annotations().iter()
+        .map(|a| (a.onset, a.duration, a.description.as_str()))
+        .collect();
+    assert_eq!(annotations, vec![(1_000_000, 3_000_000, "Early"), (17_000_000, -1, "Late")]);
+    
+    let onsets: Vec<i64> = reader.records().map(|record| record.unwrap().onset).collect();
+    assert_eq!(onsets, vec![0, 10_000_000, 20_000_000]);
+    
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_annotation_time_rounding() {
+    let filename = "test_annotation_time_rounding.edf";
+    
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.add_signal(SignalParam::new_eeg("EEG Fp1", 100)).unwrap();
+        // 0.57 * 10^7 在浮点运算中略小于 5_700_000，截断会丢失 100 纳秒
+        writer.add_annotation(0.57, Some(0.57), "Spindle").unwrap();
+        writer.write_samples(&[vec![0.0; 100]]).unwrap();
+        writer.finalize().unwrap();
+    }
+    
+    let reader = EdfReader::open(filename).unwrap();
+    let annotation = &reader.annotations()[0];
+    assert_eq!(annotation.onset, 5_700_000);
+    assert_eq!(annotation.duration, 5_700_000);
+    assert_eq!(annotation.description, "Spindle");
+    
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_subsecond_start_ignores_later_annotation_signals() {
+    let filename = "test_subsecond_start_later_annotation_signals.edf";
+    
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.set_number_of_annotation_signals(2).unwrap();
+        writer.add_signal(SignalParam::new_eeg("EEG Fp1", 100)).unwrap();
+        // 第二个注释信号的第一个TAL以带小数的起始时间开头
+        writer.add_annotation(0.0, None, "First").unwrap();
+        writer.add_annotation(0.75, None, "Second").unwrap();
+        writer.write_samples(&[vec![0.0; 100]]).unwrap();
+        writer.finalize().unwrap();
+    }
+    
+    let bytes = fs::read(filename).unwrap();
+    let second_tal = b"+0.75\x14Second\x14";
+    assert!(bytes.windows(second_tal.len()).any(|window| window == second_tal));
+    
+    let reader = EdfReader::open(filename).unwrap();
+    assert_eq!(reader.header().starttime_subsecond, 0);
+    let onsets: Vec<i64> = reader.annotations().iter().map(|a| a.onset).collect();
+    assert_eq!(onsets, vec![0, 7_500_000]);
+    
+    cleanup_test_file(filename);
+}
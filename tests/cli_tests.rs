@@ -0,0 +1,308 @@
+use edfplus::{EdfReader, EdfWriter, FilterChain, SignalParam};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+fn edfplus(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_edfplus")).args(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn create_signal(label: &str, samples_per_record: i32) -> SignalParam {
+    SignalParam {
+        label: label.to_string(),
+        samples_in_file: 0,
+        physical_max: 100.0,
+        physical_min: -100.0,
+        digital_max: 32767,
+        digital_min: -32768,
+        samples_per_record,
+        physical_dimension: "uV".to_string(),
+        prefilter: "HP:0.1Hz".to_string(),
+        transducer: "AgAgCl".to_string(),
+    }
+}
+
+// 10秒，两个 10 Hz 信号，样本值为样本序号
+fn create_cli_test_file(filename: &str) {
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.set_patient_info("P042", "F", "02-AUG-1971", "Jane_Doe").unwrap();
+    writer.add_signal(create_signal("EEG Fp1", 10)).unwrap();
+    writer.add_signal(create_signal("EEG Fp2", 10)).unwrap();
+    writer.add_annotation(1.5, None, "Lights off").unwrap();
+    writer.add_annotation(4.0, Some(2.0), "Arousal").unwrap();
+    writer.add_annotation(8.25, None, "Lights on").unwrap();
+    for record in 0..10 {
+        let fp1: Vec<i32> = (0..10).map(|i| record * 10 + i).collect();
+        let fp2: Vec<i32> = fp1.iter().map(|v| -v).collect();
+        writer.write_digital_samples(&[fp1, fp2]).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_cli_info_and_annotations() {
+    let filename = "test_cli_info.edf";
+    create_cli_test_file(filename);
+
+    let output = edfplus(&["info", filename]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    assert!(text.contains("EEG Fp1"));
+    assert!(text.contains("10 data records"));
+    assert!(text.contains("Lights off"));
+
+    let output = edfplus(&["info", filename, "--json"]);
+    assert!(output.status.success());
+    let json = stdout(&output);
+    assert!(json.starts_with('{'));
+    assert!(json.contains("\"code\":\"P042\""));
+    assert!(json.contains("\"sampling_frequency\":10"));
+    assert!(json.contains("\"count\":3"));
+
+    let output = edfplus(&["annotations", filename]);
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "onset\tduration\tdescription");
+    assert_eq!(lines[1], "1.5\t\tLights off");
+    assert_eq!(lines[2], "4\t2\tArousal");
+
+    let output = edfplus(&["annotations", filename, "--json"]);
+    assert!(stdout(&output).contains("{\"onset\":8.25,\"duration\":null,\"description\":\"Lights on\"}"));
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_cli_dump_window() {
+    let filename = "test_cli_dump.edf";
+    create_cli_test_file(filename);
+
+    let output = edfplus(&["dump", filename, "--signal", "EEG Fp2", "--signal", "0",
+        "--start", "2", "--duration", "0.5", "--digital"]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0], "time\tEEG Fp2\tEEG Fp1");
+    assert_eq!(lines[1], "2\t-20\t20");
+    assert_eq!(lines[5], "2.4\t-24\t24");
+
+    let output = edfplus(&["dump", filename, "--signal", "1", "--start", "9.8", "--json", "--digital"]);
+    let json = stdout(&output);
+    assert!(json.contains("\"start\":9.8"));
+    assert!(json.contains("\"samples\":[-98,-99]"));
+
+    // 错误的参数
+    let output = edfplus(&["dump", filename, "--signal", "EMG"]);
+    assert_eq!(output.status.code(), Some(2));
+    let output = edfplus(&["dump", filename, "--end", "1", "--duration", "1"]);
+    assert_eq!(output.status.code(), Some(2));
+    let output = edfplus(&["dump", "missing_cli_file.edf"]);
+    assert_eq!(output.status.code(), Some(1));
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_cli_dump_whole_recording_in_chunks() {
+    let filename = "test_cli_dump_chunks.edf";
+    // 70 秒 1000 Hz，超过一次读取的样本数
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.add_signal(create_signal("EEG Fp1", 1000)).unwrap();
+    for record in 0..70 {
+        let samples: Vec<i32> = (0..1000).map(|i| (record * 1000 + i) % 30000).collect();
+        writer.write_digital_samples(&[samples]).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let output = edfplus(&["dump", filename, "--digital"]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 70_001);
+    assert_eq!(lines[65_536], "65.535\t5535");
+    assert_eq!(lines[65_537], "65.536\t5536");
+    assert_eq!(lines[70_000], "69.999\t9999");
+
+    let output = edfplus(&["dump", filename, "--digital", "--json"]);
+    let json = stdout(&output);
+    assert!(json.starts_with("{\"start\":0,\"sampling_frequency\":1000,\"digital\":true,\"signals\":[{\"label\":\"EEG Fp1\""));
+    assert!(json.contains(",29999,0,1,"));
+    assert!(json.ends_with(",9998,9999]}]}\n"));
+    assert_eq!(json.matches(',').count(), 69_999 + 5);
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_cli_validate() {
+    let filename = "test_cli_validate.edf";
+    let broken = "test_cli_validate_broken.edf";
+    create_cli_test_file(filename);
+
+    let output = edfplus(&["validate", filename]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("OK"));
+
+    // 物理最小值改为等于物理最大值（两个信号加一个注释信号）
+    let mut bytes = fs::read(filename).unwrap();
+    let field = 256 + 3 * 104;
+    bytes[field..field + 8].copy_from_slice(b"100     ");
+    fs::write(broken, &bytes).unwrap();
+
+    let output = edfplus(&["validate", broken, "--json"]);
+    assert_eq!(output.status.code(), Some(1));
+    let json = stdout(&output);
+    assert!(json.contains("\"valid\":false"));
+    assert!(json.contains("\"field\":\"physical minimum\""));
+    assert!(json.contains("\"signal\":0"));
+    assert!(json.contains(&format!("\"bytes\":[{},{}]", field, field + 8)));
+
+    // 截断的文件
+    let bytes = fs::read(filename).unwrap();
+    fs::write(broken, &bytes[..bytes.len() - 100]).unwrap();
+    let output = edfplus(&["validate", broken]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).contains("INVALID"));
+
+    // 格式错误的TAL
+    let mut bytes = fs::read(filename).unwrap();
+    let stamp = bytes.windows(4).position(|w| w == b"+1\x14\x14").unwrap();
+    bytes[stamp + 1] = b'x';
+    fs::write(broken, &bytes).unwrap();
+    let output = edfplus(&["validate", broken, "--json"]);
+    assert_eq!(output.status.code(), Some(1));
+    let json = stdout(&output);
+    assert!(json.contains(&format!("\"record\":1,\"offset\":{}", stamp)), "{}", json);
+
+    cleanup_test_file(filename);
+    cleanup_test_file(broken);
+}
+
+#[test]
+fn test_cli_cut() {
+    let filename = "test_cli_cut.edf";
+    let output_file = "test_cli_cut_out.edf";
+    create_cli_test_file(filename);
+
+    let output = edfplus(&["cut", filename, output_file, "--start", "3.5", "--end", "6.2"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // 扩展到完整记录：3..7 秒
+    let reader = EdfReader::open(output_file).unwrap();
+    let header = reader.header();
+    assert_eq!(header.datarecords_in_file, 4);
+    assert_eq!(header.patient_code, "P042");
+    assert_eq!(reader.read_digital_at(0, 0, 3).unwrap(), vec![30, 31, 32]);
+    assert_eq!(reader.read_digital_at(1, 39, 1).unwrap(), vec![-69]);
+
+    let annotations = reader.annotations();
+    assert_eq!(annotations.len(), 1);
+    assert_eq!(annotations[0].description, "Arousal");
+    assert_eq!(annotations[0].onset, 10_000_000);
+    assert_eq!(annotations[0].duration, 20_000_000);
+
+    let output = edfplus(&["cut", filename, output_file, "--start", "20"]);
+    assert_eq!(output.status.code(), Some(1));
+
+    // --help 只打印用法，不执行命令
+    cleanup_test_file(output_file);
+    let output = edfplus(&["cut", filename, output_file, "--help"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).starts_with("Usage: edfplus"));
+    assert!(!Path::new(output_file).exists());
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output_file);
+}
+
+#[test]
+fn test_cli_convert() {
+    let filename = "test_cli_convert.edf";
+    let csv = "test_cli_convert.csv";
+    let npy = "test_cli_convert.npy";
+    let annotations = "test_cli_convert.annotations.csv";
+    create_cli_test_file(filename);
+
+    let output = edfplus(&["convert", filename, csv, "--digital"]);
+    assert!(output.status.success());
+    let text = fs::read_to_string(csv).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 101);
+    assert_eq!(lines[0], "time,EEG Fp1,EEG Fp2");
+    assert_eq!(lines[100], "9.9,99,-99");
+    let text = fs::read_to_string(annotations).unwrap();
+    assert_eq!(text.lines().count(), 4);
+
+    // 格式由扩展名或 --format 决定
+    let output = edfplus(&["convert", filename, csv, "--format", "tsv", "--signal", "EEG Fp2"]);
+    assert!(output.status.success());
+    let text = fs::read_to_string(csv).unwrap();
+    assert!(text.starts_with("time\tEEG Fp2 (uV)\n0\t"));
+
+    let output = edfplus(&["convert", filename, npy]);
+    assert!(output.status.success());
+    assert!(fs::read(npy).unwrap().starts_with(b"\x93NUMPY"));
+
+    let output = edfplus(&["convert", filename, "test_cli_convert.xyz"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    // 零相位滤波后的物理值
+    let output = edfplus(&["convert", filename, csv, "--signal", "0", "--highpass", "0.5", "--notch", "3"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let chain = FilterChain::new().highpass(0.5, 2).notch(3.0, 30.0).zero_phase();
+    let filtered = EdfReader::open(filename).unwrap().read_filtered(0, &chain).unwrap();
+    let text = fs::read_to_string(csv).unwrap();
+    let values: Vec<f64> = text.lines().skip(1).map(|line| line.split(',').nth(1).unwrap().parse().unwrap()).collect();
+    assert_eq!(values, filtered);
+
+    let output = edfplus(&["convert", filename, csv, "--lowpass", "0"]);
+    assert_eq!(output.status.code(), Some(2));
+    let output = edfplus(&["convert", filename, csv, "--lowpass", "4", "--digital"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    cleanup_test_file(filename);
+    cleanup_test_file(csv);
+    cleanup_test_file(npy);
+    cleanup_test_file(annotations);
+}
+
+#[test]
+fn test_cli_anonymize() {
+    let filename = "test_cli_anonymize.edf";
+    let output_file = "test_cli_anonymize_out.edf";
+    create_cli_test_file(filename);
+
+    let output = edfplus(&["anonymize", filename, output_file, "--code", "Study 7"]);
+    assert!(output.status.success());
+
+    let original = EdfReader::open(filename).unwrap();
+    let reader = EdfReader::open(output_file).unwrap();
+    let header = reader.header();
+    assert_eq!(header.patient_code, "Study_7");
+    assert_eq!(header.patient_name, "X");
+    assert_eq!(header.birthdate, "X");
+    assert_eq!(header.start_date, chrono::NaiveDate::from_ymd_opt(1985, 1, 1).unwrap());
+    assert_eq!(header.start_time, original.header().start_time);
+    assert_eq!(header.datarecords_in_file, 10);
+    assert_eq!(reader.annotations().len(), 3);
+    assert_eq!(reader.read_digital_at(0, 0, 100).unwrap(), original.read_digital_at(0, 0, 100).unwrap());
+
+    // 原始文件中的姓名不应出现在副本中
+    let bytes = fs::read(output_file).unwrap();
+    assert!(!bytes.windows(8).any(|w| w == b"Jane_Doe"));
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output_file);
+}
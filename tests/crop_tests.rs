@@ -0,0 +1,188 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use edfplus::{AnnotationCrop, CropOptions, EdfError, EdfReader, EdfWriter, SignalParam};
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+fn source_start() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 5, 6).unwrap().and_hms_milli_opt(23, 59, 59, 250).unwrap()
+}
+
+// 25 个 0.4 秒的记录（10 秒），两个不同采样率的信号，数字值为样本序号
+fn create_crop_test_file(filename: &str) {
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.set_patient_info("CROP01", "M", "01-JAN-1970", "Crop_Test").unwrap();
+    writer.set_start_datetime(source_start()).unwrap();
+    writer.set_datarecord_duration(0.4).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG C3", 40)).unwrap();
+    writer.add_signal(SignalParam::new_eeg("Resp", 4)).unwrap();
+    writer.set_number_of_annotation_signals(2).unwrap();
+    writer.add_annotation(0.5, None, "Start").unwrap();
+    writer.add_annotation(2.0, Some(1.0), "Inside").unwrap();
+    writer.add_annotation(0.6, Some(1.2), "Crosses start").unwrap();
+    writer.add_annotation(3.0, Some(2.0), "Crosses end").unwrap();
+    writer.add_annotation(1.6, None, "At window start").unwrap();
+    writer.add_annotation(8.0, None, "Later").unwrap();
+    for record in 0..25 {
+        let eeg: Vec<i32> = (0..40).map(|i| record * 40 + i).collect();
+        let resp: Vec<i32> = (0..4).map(|i| -(record * 4 + i)).collect();
+        writer.write_digital_samples(&[eeg, resp]).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_crop_seconds_window() {
+    let filename = "test_crop_seconds.edf";
+    let output = "test_crop_seconds_out.edf";
+    create_crop_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    // 1.7..3.5 秒扩展到记录 4..9，即 1.6..3.6 秒
+    let summary = reader.crop(output, &CropOptions::seconds(1.7, 3.5)).unwrap();
+    assert_eq!(summary.records, 4..9);
+    assert!((summary.offset_seconds - 1.6).abs() < 1e-9);
+    assert_eq!(summary.annotations_copied, 2);
+    assert_eq!(summary.annotations_trimmed, 2);
+    assert_eq!(summary.annotations_dropped, 2);
+
+    let cropped = EdfReader::open(output).unwrap();
+    let header = cropped.header();
+    assert_eq!(header.datarecords_in_file, 5);
+    assert_eq!(header.datarecord_duration, 4_000_000);
+    assert_eq!(header.patient_code, "CROP01");
+    assert_eq!(header.signals.len(), 2);
+
+    // 开始时间 23:59:59.25 + 1.6 秒跨过午夜
+    let expected = NaiveDate::from_ymd_opt(2024, 5, 7).unwrap().and_hms_milli_opt(0, 0, 0, 850).unwrap();
+    assert_eq!(summary.start, expected);
+    assert_eq!(header.start_datetime(), expected);
+    assert_eq!(header.starttime_subsecond, 8_500_000);
+
+    // 数字值原样复制
+    assert_eq!(cropped.read_digital_at(0, 0, 200).unwrap(), reader.read_digital_at(0, 160, 200).unwrap());
+    assert_eq!(cropped.read_digital_at(1, 0, 20).unwrap(), (16..36).map(|v| -v).collect::<Vec<i32>>());
+    assert_eq!(cropped.read_physical_at(0, 0, 200).unwrap(), reader.read_physical_at(0, 160, 200).unwrap());
+
+    let annotations: Vec<(String, i64, i64)> = cropped.annotations().iter()
+        .map(|a| (a.description.clone(), a.onset, a.duration))
+        .collect();
+    assert!(annotations.contains(&("Inside".to_string(), 4_000_000, 10_000_000)));
+    assert!(annotations.contains(&("At window start".to_string(), 0, -1)));
+    // 跨越边界的注释被截断
+    assert!(annotations.contains(&("Crosses start".to_string(), 0, 2_000_000)));
+    assert!(annotations.contains(&("Crosses end".to_string(), 14_000_000, 6_000_000)));
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output);
+}
+
+#[test]
+fn test_crop_records_and_drop_policy() {
+    let filename = "test_crop_records.edf";
+    let output = "test_crop_records_out.edf";
+    create_crop_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    let options = CropOptions::records(4..9).annotations(AnnotationCrop::Drop);
+    let summary = reader.crop(output, &options).unwrap();
+    assert_eq!(summary.annotations_copied, 2);
+    assert_eq!(summary.annotations_trimmed, 0);
+    assert_eq!(summary.annotations_dropped, 4);
+
+    let cropped = EdfReader::open(output).unwrap();
+    let mut descriptions: Vec<&str> = cropped.annotations().iter().map(|a| a.description.as_str()).collect();
+    descriptions.sort();
+    assert_eq!(descriptions, vec!["At window start", "Inside"]);
+
+    // 窗口延伸到文件末尾之后时截断到最后一个记录
+    let summary = reader.crop(output, &CropOptions::seconds(9.0, 60.0)).unwrap();
+    assert_eq!(summary.records, 22..25);
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output);
+}
+
+#[test]
+fn test_crop_into_prepared_writer() {
+    let filename = "test_crop_into.edf";
+    let output = "test_crop_into_out.edf";
+    create_crop_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    let mut writer = EdfWriter::create(output).unwrap();
+    writer.set_patient_info("SHARED", "X", "X", "X").unwrap();
+    let anonymous = NaiveDate::from_ymd_opt(1985, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    let options = CropOptions::records(0..25).start_datetime(anonymous);
+    let summary = reader.crop_into(writer, &options).unwrap();
+    assert_eq!(summary.start, anonymous);
+
+    let copy = EdfReader::open(output).unwrap();
+    assert_eq!(copy.header().patient_code, "SHARED");
+    assert_eq!(copy.header().start_datetime(), anonymous);
+    assert_eq!(copy.annotations().len(), 6);
+    assert_eq!(copy.read_digital_at(0, 0, 1000).unwrap(), reader.read_digital_at(0, 0, 1000).unwrap());
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output);
+}
+
+#[test]
+fn test_crop_invalid_windows() {
+    let filename = "test_crop_invalid.edf";
+    let output = "test_crop_invalid_out.edf";
+    create_crop_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    for options in [
+        CropOptions::seconds(3.0, 2.0),
+        CropOptions::seconds(-1.0, 2.0),
+        CropOptions::seconds(0.0, f64::NAN),
+        CropOptions::seconds(10.0, 20.0),
+        CropOptions::records(5..5),
+        CropOptions::records(20..26),
+    ] {
+        let result = reader.crop(output, &options);
+        assert!(matches!(result, Err(EdfError::InvalidFormat(_))), "{:?}", options);
+        // 窗口无效时不创建输出文件
+        assert!(!Path::new(output).exists());
+    }
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_crop_copies_out_of_range_samples() {
+    let filename = "test_crop_out_of_range.edf";
+    let output = "test_crop_out_of_range_out.edf";
+    let mut signal = SignalParam::new_eeg("EEG C3", 10);
+    signal.digital_min = -100;
+    signal.digital_max = 100;
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.add_signal(signal).unwrap();
+    for _ in 0..2 {
+        writer.write_digital_samples(&[vec![0i32; 10]]).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    // 在第一个样本处写入超出数字范围的原始值（头部为 3 x 256 字节）
+    let mut bytes = fs::read(filename).unwrap();
+    bytes[768..770].copy_from_slice(&500i16.to_le_bytes());
+    bytes[770..772].copy_from_slice(&(-32768i16).to_le_bytes());
+    fs::write(filename, bytes).unwrap();
+
+    let reader = EdfReader::open(filename).unwrap();
+    reader.crop(output, &CropOptions::records(0..1)).unwrap();
+    let mut cropped = EdfReader::open(output).unwrap();
+    cropped.set_digital_clamping(false);
+    assert_eq!(cropped.read_digital_samples(0, 3).unwrap(), vec![500, -32768, 0]);
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output);
+}
@@ -0,0 +1,121 @@
+use edfplus::{EdfReader, EdfWriter, EpochOptions, PartialEpoch, SignalParam};
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+// 创建 65 秒的测试文件：EEG 100Hz（值为时间秒数），Resp 10Hz
+fn create_epoch_test_file(filename: &str) {
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG C3", 100)).unwrap();
+    writer.add_signal(SignalParam::new_respiration("Resp", 10)).unwrap();
+    writer.add_annotation(31.0, None, "Arousal").unwrap();
+    writer.add_annotation(55.0, Some(10.0), "Apnea").unwrap();
+
+    for second in 0..65 {
+        let eeg: Vec<f64> = (0..100).map(|i| second as f64 + i as f64 / 100.0).collect();
+        writer.write_samples(&[eeg, vec![second as f64; 10]]).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_epochs_full_and_partial() {
+    let filename = "test_epochs_full.edf";
+    create_epoch_test_file(filename);
+    let mut reader = EdfReader::open(filename).unwrap();
+
+    // 默认丢弃不完整的 epoch
+    let epochs: Vec<_> = reader.epochs(&EpochOptions::new(30.0)).unwrap()
+        .collect::<Result<_, _>>().unwrap();
+    assert_eq!(epochs.len(), 2);
+    assert_eq!(epochs[1].start, 300_000_000);
+    assert_eq!(epochs[1].signals[0].len(), 3000);
+    assert_eq!(epochs[1].signals[1].len(), 300);
+    assert!((epochs[1].signals[0][0] - 30.0).abs() < 0.02);
+    assert!((epochs[1].signals[1][299] - 59.0).abs() < 0.01);
+    assert!(epochs[0].annotations.is_empty());
+    let descriptions: Vec<_> = epochs[1].annotations.iter().map(|a| a.description.as_str()).collect();
+    assert_eq!(descriptions, vec!["Arousal", "Apnea"]);
+
+    // 保留不完整的 epoch
+    let epochs: Vec<_> = reader.epochs(&EpochOptions::new(30.0).partial(PartialEpoch::Keep)).unwrap()
+        .collect::<Result<_, _>>().unwrap();
+    assert_eq!(epochs.len(), 3);
+    assert!(epochs[2].partial);
+    assert_eq!(epochs[2].signals[0].len(), 500);
+    assert_eq!(epochs[2].annotations.len(), 1); // Apnea 持续到 65s
+
+    // 填充不完整的 epoch
+    let last = reader.epochs(&EpochOptions::new(30.0).partial(PartialEpoch::Pad(0.0))).unwrap()
+        .last().unwrap().unwrap();
+    assert_eq!(last.signals[0].len(), 3000);
+    assert_eq!(last.signals[0][2999], 0.0);
+    assert!((last.signals[0][499] - 64.99).abs() < 0.02);
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_epochs_overlap_and_selection() {
+    let filename = "test_epochs_overlap.edf";
+    create_epoch_test_file(filename);
+    let mut reader = EdfReader::open(filename).unwrap();
+
+    let options = EpochOptions::new(4.0).overlap(2.5).signals(&[1]);
+    let epochs: Vec<_> = reader.epochs(&options).unwrap().collect::<Result<_, _>>().unwrap();
+    // 起点 0, 1.5, 3, ... 直到 61.5（61.5 + 4 = 65.5 超出）
+    assert_eq!(epochs.len(), 41);
+    for epoch in &epochs {
+        assert_eq!(epoch.signals.len(), 1);
+        assert_eq!(epoch.signals[0].len(), 40);
+        let expected = (epoch.start_seconds() * 10.0).round() / 10.0;
+        assert!((epoch.signals[0][0] - expected.floor()).abs() < 0.01);
+    }
+    assert_eq!(epochs[40].start, 600_000_000);
+
+    assert!(reader.epochs(&EpochOptions::new(0.0)).is_err());
+    assert!(reader.epochs(&EpochOptions::new(4.0).step(-1.0)).is_err());
+    assert!(reader.epochs(&EpochOptions::new(4.0).signals(&[2])).is_err());
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_epochs_discontinuous() {
+    let filename = "test_epochs_discontinuous.edf";
+
+    // EDF+D：记录开始于 0、3、4 秒，EEG 的值等于记录时间（秒）
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.add_signal(SignalParam::new_eeg("EEG C3", 100)).unwrap();
+        for onset in [0.0, 3.0, 4.0] {
+            writer.set_next_record_onset(onset).unwrap();
+            let samples: Vec<f64> = (0..100).map(|i| onset + i as f64 / 100.0).collect();
+            writer.write_samples(&[samples]).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = EdfReader::open(filename).unwrap();
+    assert!(reader.header().discontinuous);
+
+    // 跨越 1..3 秒间隙的 epoch 被跳过
+    let options = EpochOptions::new(1.0).step(0.5);
+    let epochs: Vec<_> = reader.epochs(&options).unwrap().collect::<Result<_, _>>().unwrap();
+    let indices: Vec<usize> = epochs.iter().map(|e| e.index).collect();
+    assert_eq!(indices, vec![0, 6, 7, 8]);
+    for epoch in &epochs {
+        assert_eq!(epoch.signals[0].len(), 100);
+        assert!(!epoch.partial);
+        assert!((epoch.signals[0][0] - epoch.start_seconds()).abs() < 0.01);
+        assert!((epoch.signals[0][99] - epoch.start_seconds() - 0.99).abs() < 0.01);
+    }
+
+    cleanup_test_file(filename);
+}
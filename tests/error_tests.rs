@@ -0,0 +1,135 @@
+use edfplus::{EdfError, EdfReader, EdfWriter, SignalParam};
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+fn write_valid_file(filename: &str) -> Vec<u8> {
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG Cz", 10)).unwrap();
+    for _ in 0..3 {
+        writer.write_samples(&[vec![1.0; 10]]).unwrap();
+    }
+    writer.finalize().unwrap();
+    fs::read(filename).unwrap()
+}
+
+fn open_patched(filename: &str, original: &[u8], offset: usize, patch: &[u8]) -> EdfError {
+    let mut bytes = original.to_vec();
+    bytes[offset..offset + patch.len()].copy_from_slice(patch);
+    fs::write(filename, bytes).unwrap();
+    match EdfReader::open_strict(filename) {
+        Ok(_) => panic!("patch at byte {} was accepted", offset),
+        Err(e) => e,
+    }
+}
+
+#[test]
+fn test_header_errors_carry_location() {
+    let filename = "test_header_error_context.edf";
+    let original = write_valid_file(filename);
+
+    // 主头部字段
+    match open_patched(filename, &original, 252, b"0   ") {
+        EdfError::InvalidHeader { field, signal, bytes, raw, .. } => {
+            assert_eq!(field, "number of signals");
+            assert_eq!(signal, None);
+            assert_eq!(bytes, 252..256);
+            assert_eq!(raw, "0   ");
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+    match open_patched(filename, &original, 168, b"31.02.24") {
+        EdfError::InvalidHeader { field, bytes, raw, .. } => {
+            assert_eq!(field, "startdate");
+            assert_eq!(bytes, 168..176);
+            assert_eq!(raw, "31.02.24");
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+    match open_patched(filename, &original, 184, b"256     ") {
+        EdfError::InvalidHeader { field, .. } => assert_eq!(field, "number of bytes in header"),
+        e => panic!("unexpected error {:?}", e),
+    }
+
+    // 信号头部字段：2个信号（EEG + 注释），物理最大值位于 256 + 2*112
+    let physical_min = original[256 + 2 * 104..256 + 2 * 104 + 8].to_vec();
+    let error = open_patched(filename, &original, 256 + 2 * 112, &physical_min);
+    let message = error.to_string();
+    match error {
+        EdfError::InvalidHeader { field, signal, bytes, raw, .. } => {
+            assert_eq!(field, "physical minimum");
+            assert_eq!(signal, Some(0));
+            assert_eq!(bytes, 464..472);
+            assert_eq!(raw.as_bytes(), &physical_min[..]);
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+    assert!(message.contains("'physical minimum' of signal 0 at bytes 464..472"), "{}", message);
+
+    match open_patched(filename, &original, 256 + 2 * 216 + 8, b"0       ") {
+        EdfError::InvalidHeader { field, signal, bytes, .. } => {
+            assert_eq!(field, "number of samples in each data record");
+            assert_eq!(signal, Some(1));
+            assert_eq!(bytes, 696..704);
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_annotation_errors_carry_record() {
+    let filename = "test_annotation_error_context.edf";
+    let original = write_valid_file(filename);
+
+    // 第3个记录的时间戳 "+2" 改为 "+7"，文件不再连续
+    let stamp = original.windows(4).position(|w| w == b"+2\x14\x14").unwrap();
+    match open_patched(filename, &original, stamp, b"+7") {
+        EdfError::InvalidAnnotation { record, offset, reason } => {
+            assert_eq!(record, 2);
+            assert_eq!(offset, stamp);
+            assert!(reason.contains("expected 2 s"), "{}", reason);
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+
+    // 默认打开方式忽略注释并保留错误
+    let reader = EdfReader::open(filename).unwrap();
+    assert!(reader.annotations().is_empty());
+    assert!(matches!(reader.annotation_error(), Some(EdfError::InvalidAnnotation { record: 2, .. })));
+    assert_eq!(reader.record_onsets()[2], 2 * reader.header().datarecord_duration);
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_malformed_tal_errors_carry_record() {
+    let filename = "test_malformed_tal_context.edf";
+    let original = write_valid_file(filename);
+
+    // 第2个记录的时间戳TAL的onset不是数字
+    let stamp = original.windows(4).position(|w| w == b"+1\x14\x14").unwrap();
+    match open_patched(filename, &original, stamp, b"+x") {
+        EdfError::InvalidAnnotation { record, offset, reason } => {
+            assert_eq!(record, 1);
+            assert_eq!(offset, stamp);
+            assert!(reason.contains("invalid onset 'x'"), "{}", reason);
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+
+    let mut reader = EdfReader::open(filename).unwrap();
+    assert!(matches!(reader.annotation_error(), Some(EdfError::InvalidAnnotation { record: 1, .. })));
+    let records: Vec<_> = reader.records().collect();
+    assert!(records[0].is_ok());
+    assert!(matches!(&records[1], Err(EdfError::InvalidAnnotation { record: 1, offset, .. }) if *offset == stamp));
+
+    cleanup_test_file(filename);
+}
@@ -0,0 +1,349 @@
+use edfplus::{EdfError, EdfReader, EdfWriter, ExportDtype, ExportOptions, FilterChain, SignalParam};
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+// 物理值与数字值一一对应，整数样本无量化误差
+fn lossless_signal(label: &str, samples_per_record: i32) -> SignalParam {
+    let mut signal = SignalParam::new_eeg(label, samples_per_record);
+    signal.physical_min = -32768.0;
+    signal.physical_max = 32767.0;
+    signal
+}
+
+// 4 个 1 秒记录：EEG 4 Hz、Resp 2 Hz、EMG 4 Hz，样本值为递增整数
+fn create_export_test_file(filename: &str) {
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.add_signal(lossless_signal("EEG C3", 4)).unwrap();
+    let mut resp = lossless_signal("Resp, nasal", 2);
+    resp.physical_dimension = String::new();
+    writer.add_signal(resp).unwrap();
+    writer.add_signal(lossless_signal("EMG", 4)).unwrap();
+    writer.add_annotation(0.5, Some(1.0), "Lights off").unwrap();
+    writer.add_annotation(3.0, None, "Arousal, spontaneous").unwrap();
+    for record in 0..4 {
+        let eeg: Vec<f64> = (0..4).map(|i| (record * 4 + i) as f64).collect();
+        let resp: Vec<f64> = (0..2).map(|i| -((record * 2 + i) as f64)).collect();
+        let emg: Vec<f64> = (0..4).map(|i| 100.0 + (record * 4 + i) as f64).collect();
+        writer.write_samples(&[eeg, resp, emg]).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+// 解析 .npy 头部，返回（描述字典，数据起始位置）
+fn parse_npy(bytes: &[u8]) -> (String, usize) {
+    assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+    let length = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    assert_eq!((10 + length) % 64, 0);
+    let dict = String::from_utf8(bytes[10..10 + length].to_vec()).unwrap();
+    assert!(dict.ends_with('\n'));
+    (dict.trim_end().to_string(), 10 + length)
+}
+
+fn f64_values(bytes: &[u8]) -> Vec<f64> {
+    bytes.chunks(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect()
+}
+
+// 从不压缩的 .npz 中按中央目录读取条目
+fn npz_entries(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]) as usize;
+    let u64_at = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap()) as usize;
+
+    let eocd = bytes.len() - 22;
+    assert_eq!(&bytes[eocd..eocd + 4], b"PK\x05\x06");
+    let zip64_eocd = u64_at(eocd - 20 + 8);
+    assert_eq!(&bytes[zip64_eocd..zip64_eocd + 4], b"PK\x06\x06");
+    let count = u64_at(zip64_eocd + 32);
+    let mut at = u64_at(zip64_eocd + 48);
+
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        assert_eq!(&bytes[at..at + 4], b"PK\x01\x02");
+        let name_len = u16_at(at + 28);
+        let extra_len = u16_at(at + 30);
+        let name = String::from_utf8(bytes[at + 46..at + 46 + name_len].to_vec()).unwrap();
+        let extra = at + 46 + name_len;
+        let size = u64_at(extra + 4);
+        let offset = u64_at(extra + 20);
+
+        assert_eq!(&bytes[offset..offset + 4], b"PK\x03\x04");
+        let data = offset + 30 + u16_at(offset + 26) + u16_at(offset + 28);
+        entries.push((name, bytes[data..data + size].to_vec()));
+        at = extra + extra_len;
+    }
+    entries
+}
+
+#[test]
+fn test_export_csv_with_annotations() {
+    let filename = "test_export_csv.edf";
+    let output = "test_export_csv.csv";
+    let annotations = "test_export_csv.annotations.csv";
+    create_export_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    let options = ExportOptions::new().signals(&[2, 0]).window(1.0, 2.5);
+    let summary = reader.export_csv(output, &options).unwrap();
+    assert_eq!(summary.signals, vec!["EMG (uV)", "EEG C3 (uV)"]);
+    assert_eq!(summary.samples, vec![6, 6]);
+    assert_eq!(summary.annotations, 1);
+    assert_eq!(summary.annotations_path.as_deref(), Some(Path::new(annotations)));
+
+    let csv = fs::read_to_string(output).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 7);
+    assert_eq!(lines[0], "time,EMG (uV),EEG C3 (uV)");
+    assert_eq!(lines[1], "1,104,4");
+    assert_eq!(lines[6], "2.25,109,9");
+
+    // 窗口内只有与之重叠的注释
+    let csv = fs::read_to_string(annotations).unwrap();
+    assert_eq!(csv, "onset,duration,description\n0.5,1,Lights off\n");
+
+    // 原始数字值，没有注释文件；无单位的标签中有逗号时加引号
+    cleanup_test_file(annotations);
+    let options = ExportOptions::new().signals(&[1]).dtype(ExportDtype::Int16).annotations(false);
+    let summary = reader.export_csv(output, &options).unwrap();
+    assert_eq!(summary.annotations_path, None);
+    assert!(!Path::new(annotations).exists());
+    let csv = fs::read_to_string(output).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "time,\"Resp, nasal\"");
+    assert_eq!(lines.len(), 9);
+    assert_eq!(lines[8], format!("3.5,{}", reader.read_digital_at(1, 7, 1).unwrap()[0]));
+
+    // 整个文件的注释
+    reader.export_csv(output, &ExportOptions::new().signals(&[0])).unwrap();
+    let csv = fs::read_to_string(annotations).unwrap();
+    assert!(csv.ends_with("3,,\"Arousal, spontaneous\"\n"));
+
+    // 制表符分隔：逗号不再需要引号，注释文件仍以逗号分隔
+    let options = ExportOptions::new().signals(&[1]).delimiter('\t');
+    reader.export_csv(output, &options).unwrap();
+    let tsv = fs::read_to_string(output).unwrap();
+    assert!(tsv.starts_with("time\tResp, nasal\n0\t0\n"));
+    let csv = fs::read_to_string(annotations).unwrap();
+    assert!(csv.ends_with("3,,\"Arousal, spontaneous\"\n"));
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output);
+    cleanup_test_file(annotations);
+}
+
+#[test]
+fn test_export_npy() {
+    let filename = "test_export_npy.edf";
+    let output = "test_export_npy.npy";
+    create_export_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    let options = ExportOptions::new().signals(&[0, 2]).annotations(false);
+    let summary = reader.export_npy(output, &options).unwrap();
+    assert_eq!(summary.signals, vec!["EEG C3", "EMG"]);
+    assert_eq!(summary.samples, vec![16, 16]);
+
+    let bytes = fs::read(output).unwrap();
+    let (dict, data) = parse_npy(&bytes);
+    assert_eq!(dict, "{'descr': '<f8', 'fortran_order': False, 'shape': (2, 16), }");
+    let values = f64_values(&bytes[data..]);
+    assert_eq!(values.len(), 32);
+    assert_eq!(values[..16], reader.read_physical_at(0, 0, 16).unwrap()[..]);
+    assert_eq!(values[16..], reader.read_physical_at(2, 0, 16).unwrap()[..]);
+
+    // float32 与重采样
+    let options = ExportOptions::new().resample(2.0).dtype(ExportDtype::Float32).annotations(false);
+    reader.export_npy(output, &options).unwrap();
+    let bytes = fs::read(output).unwrap();
+    let (dict, data) = parse_npy(&bytes);
+    assert_eq!(dict, "{'descr': '<f4', 'fortran_order': False, 'shape': (3, 8), }");
+    assert_eq!(bytes.len() - data, 3 * 8 * 4);
+
+    // 原始 int16
+    let options = ExportOptions::new().signals(&[1]).dtype(ExportDtype::Int16).annotations(false);
+    reader.export_npy(output, &options).unwrap();
+    let bytes = fs::read(output).unwrap();
+    let (dict, data) = parse_npy(&bytes);
+    assert_eq!(dict, "{'descr': '<i2', 'fortran_order': False, 'shape': (1, 8), }");
+    let values: Vec<i32> = bytes[data..].chunks(2).map(|b| i16::from_le_bytes([b[0], b[1]]) as i32).collect();
+    assert_eq!(values, reader.read_digital_at(1, 0, 8).unwrap());
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output);
+}
+
+#[test]
+fn test_export_npz() {
+    let filename = "test_export_npz.edf";
+    let output = "test_export_npz.npz";
+    create_export_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    let summary = reader.export_npz(output, &ExportOptions::new().signals(&[0, 1, 0])).unwrap();
+    assert_eq!(summary.signals, vec!["EEG C3", "Resp, nasal", "EEG C3_2"]);
+    assert_eq!(summary.samples, vec![16, 8, 16]);
+    assert_eq!(summary.annotations, 2);
+    assert_eq!(summary.annotations_path, None);
+
+    let entries = npz_entries(&fs::read(output).unwrap());
+    let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec![
+        "EEG C3.npy", "Resp, nasal.npy", "EEG C3_2.npy",
+        "annotations_onset.npy", "annotations_duration.npy", "annotations_description.npy",
+    ]);
+
+    // 每个信号保持自己的采样率
+    let (dict, data) = parse_npy(&entries[1].1);
+    assert_eq!(dict, "{'descr': '<f8', 'fortran_order': False, 'shape': (8,), }");
+    assert_eq!(f64_values(&entries[1].1[data..]), reader.read_physical_at(1, 0, 8).unwrap());
+
+    let (_, data) = parse_npy(&entries[3].1);
+    assert_eq!(f64_values(&entries[3].1[data..]), vec![0.5, 3.0]);
+    let (_, data) = parse_npy(&entries[4].1);
+    let durations = f64_values(&entries[4].1[data..]);
+    assert_eq!(durations[0], 1.0);
+    assert!(durations[1].is_nan());
+
+    let (dict, data) = parse_npy(&entries[5].1);
+    assert_eq!(dict, "{'descr': '<U20', 'fortran_order': False, 'shape': (2,), }");
+    let text: String = entries[5].1[data..data + 80]
+        .chunks(4)
+        .map(|b| char::from_u32(u32::from_le_bytes(b.try_into().unwrap())).unwrap())
+        .filter(|&c| c != '\0')
+        .collect();
+    assert_eq!(text, "Lights off");
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output);
+}
+
+#[test]
+fn test_export_int16_keeps_out_of_range_samples() {
+    let filename = "test_export_raw.edf";
+    let output = "test_export_raw.npy";
+    let csv = "test_export_raw.csv";
+    let mut signal = SignalParam::new_eeg("EEG C3", 4);
+    signal.digital_min = -100;
+    signal.digital_max = 100;
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.add_signal(signal).unwrap();
+    writer.write_digital_samples(&[vec![1i32, 2, 3, 4]]).unwrap();
+    writer.finalize().unwrap();
+
+    // 第一个样本写入超出数字范围的原始值（头部为 3 x 256 字节）
+    let mut bytes = fs::read(filename).unwrap();
+    bytes[768..770].copy_from_slice(&500i16.to_le_bytes());
+    fs::write(filename, bytes).unwrap();
+
+    let reader = EdfReader::open(filename).unwrap();
+    let options = ExportOptions::new().dtype(ExportDtype::Int16).annotations(false);
+    reader.export_npy(output, &options).unwrap();
+    let bytes = fs::read(output).unwrap();
+    let (_, data) = parse_npy(&bytes);
+    let values: Vec<i16> = bytes[data..].chunks(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect();
+    assert_eq!(values, vec![500, 2, 3, 4]);
+
+    reader.export_csv(csv, &options).unwrap();
+    let text = fs::read_to_string(csv).unwrap();
+    assert_eq!(text.lines().nth(1), Some("0,500"));
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output);
+    cleanup_test_file(csv);
+}
+
+#[test]
+fn test_export_filtered_signals() {
+    let filename = "test_export_filtered.edf";
+    let output = "test_export_filtered.npy";
+    let csv = "test_export_filtered.csv";
+    create_export_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    let highpass = FilterChain::new().highpass(0.5, 2).zero_phase();
+    let filtered = reader.read_filtered(0, &highpass).unwrap();
+
+    // 整个信号滤波后再截取窗口，未滤波的信号原样导出
+    let options = ExportOptions::new()
+        .signals(&[0, 2])
+        .window(1.0, 3.0)
+        .filter(0, &FilterChain::new().lowpass(1.0, 2))
+        .filter(0, &highpass)
+        .annotations(false);
+    reader.export_npy(output, &options).unwrap();
+    let bytes = fs::read(output).unwrap();
+    let (_, data) = parse_npy(&bytes);
+    let values = f64_values(&bytes[data..]);
+    assert_eq!(values[..8], filtered[4..12]);
+    assert_eq!(values[8..], reader.read_physical_at(2, 4, 8).unwrap()[..]);
+
+    reader.export_csv(csv, &options).unwrap();
+    let text = fs::read_to_string(csv).unwrap();
+    let first_row: Vec<&str> = text.lines().nth(1).unwrap().split(',').collect();
+    assert_eq!(first_row, vec!["1".to_string(), filtered[4].to_string(), "104".to_string()]);
+
+    // 重采样作用于滤波后的值
+    let options = ExportOptions::new().signals(&[0]).resample(4.0).filter(0, &highpass).annotations(false);
+    reader.export_npy(output, &options).unwrap();
+    let bytes = fs::read(output).unwrap();
+    let (_, data) = parse_npy(&bytes);
+    let values = f64_values(&bytes[data..]);
+    assert_eq!(values.len(), 16);
+    for (value, expected) in values.iter().zip(&filtered) {
+        assert!((value - expected).abs() < 1e-9, "{} != {}", value, expected);
+    }
+
+    // 原始值不能滤波；滤波器需要有效的信号和截止频率
+    assert!(matches!(
+        reader.export_npy(output, &ExportOptions::new().dtype(ExportDtype::Int16).filter(0, &highpass)),
+        Err(EdfError::InvalidFormat(_))
+    ));
+    assert!(matches!(
+        reader.export_npy(output, &ExportOptions::new().signals(&[0]).filter(5, &highpass)),
+        Err(EdfError::InvalidSignalIndex(5))
+    ));
+    assert!(matches!(
+        reader.export_npy(output, &ExportOptions::new().filter(0, &FilterChain::new().lowpass(10.0, 2))),
+        Err(EdfError::InvalidFormat(_))
+    ));
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output);
+    cleanup_test_file(csv);
+}
+
+#[test]
+fn test_export_errors() {
+    let filename = "test_export_errors.edf";
+    let output = "test_export_errors.csv";
+    create_export_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    let mixed_rates = ExportOptions::new().signals(&[0, 1]);
+    assert!(matches!(reader.export_csv(output, &mixed_rates), Err(EdfError::InvalidFormat(_))));
+    assert!(matches!(reader.export_npy(output, &mixed_rates), Err(EdfError::InvalidFormat(_))));
+    assert!(matches!(
+        reader.export_csv(output, &ExportOptions::new().signals(&[])),
+        Err(EdfError::InvalidFormat(_))
+    ));
+    assert!(matches!(
+        reader.export_csv(output, &ExportOptions::new().signals(&[3])),
+        Err(EdfError::InvalidSignalIndex(3))
+    ));
+    assert!(matches!(
+        reader.export_csv(output, &ExportOptions::new().signals(&[0]).window(2.0, 1.0)),
+        Err(EdfError::InvalidFormat(_))
+    ));
+    assert!(matches!(
+        reader.export_npz(output, &ExportOptions::new().resample(8.0).dtype(ExportDtype::Int16)),
+        Err(EdfError::InvalidFormat(_))
+    ));
+    assert!(!Path::new(output).exists());
+
+    cleanup_test_file(filename);
+}
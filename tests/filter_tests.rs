@@ -0,0 +1,176 @@
+use edfplus::{EdfError, EdfReader, EdfWriter, FilterChain, SignalParam, SignalParamBuilder};
+use std::f64::consts::PI;
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+fn sine(frequency: f64, amplitude: f64, rate: f64, len: usize) -> Vec<f64> {
+    (0..len).map(|i| amplitude * (2.0 * PI * frequency * i as f64 / rate).sin()).collect()
+}
+
+// 后半段的最大幅度（跳过起始瞬态）
+fn amplitude(samples: &[f64]) -> f64 {
+    samples[samples.len() / 2..].iter().fold(0.0, |max, v| f64::max(max, v.abs()))
+}
+
+fn filtered(chain: &FilterChain, rate: f64, mut samples: Vec<f64>) -> Vec<f64> {
+    chain.design(rate).unwrap().filter(&mut samples);
+    samples
+}
+
+// 10 个 1 秒记录，256 Hz：直流偏移 + 10 Hz + 50 Hz 干扰
+fn create_filter_test_file(filename: &str) {
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG Cz", 256)).unwrap();
+    for record in 0..10 {
+        let samples: Vec<f64> = (0..256)
+            .map(|i| (record * 256 + i) as f64 / 256.0)
+            .map(|t| 100.0 + 40.0 * (2.0 * PI * 10.0 * t).sin() + 20.0 * (2.0 * PI * 50.0 * t).sin())
+            .collect();
+        writer.write_samples(&[samples]).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_butterworth_and_notch_responses() {
+    let rate = 256.0;
+    let lowpass = FilterChain::new().lowpass(30.0, 4);
+    assert!((amplitude(&filtered(&lowpass, rate, sine(5.0, 1.0, rate, 4096))) - 1.0).abs() < 0.01);
+    assert!((amplitude(&filtered(&lowpass, rate, sine(30.0, 1.0, rate, 4096))) - 0.5f64.sqrt()).abs() < 0.01);
+    assert!(amplitude(&filtered(&lowpass, rate, sine(80.0, 1.0, rate, 4096))) < 0.01);
+
+    // 奇数阶同样在截止频率处衰减 3 dB
+    let highpass = FilterChain::new().highpass(1.0, 3);
+    assert!((amplitude(&filtered(&highpass, rate, sine(1.0, 1.0, rate, 8192))) - 0.5f64.sqrt()).abs() < 0.01);
+    assert!((amplitude(&filtered(&highpass, rate, sine(10.0, 1.0, rate, 4096))) - 1.0).abs() < 0.01);
+
+    // 高通滤波从稳态开始，直流偏移没有起始瞬态
+    let offset: Vec<f64> = sine(10.0, 1.0, rate, 1024).iter().map(|v| v + 500.0).collect();
+    let without_offset = filtered(&FilterChain::new().highpass(0.5, 2), rate, offset);
+    assert!(without_offset.iter().all(|v| v.abs() < 2.0));
+
+    let notch = FilterChain::new().notch(50.0, 30.0);
+    assert!(amplitude(&filtered(&notch, rate, sine(50.0, 1.0, rate, 4096))) < 0.01);
+    assert!((amplitude(&filtered(&notch, rate, sine(10.0, 1.0, rate, 4096))) - 1.0).abs() < 0.01);
+}
+
+#[test]
+fn test_chunked_filtering_and_zero_phase() {
+    let rate = 200.0;
+    let chain = FilterChain::new().highpass(0.5, 2).lowpass(20.0, 5).notch(60.0, 25.0);
+    let input: Vec<f64> = (0..2000).map(|i| ((i * 7919) % 200) as f64 - 100.0).collect();
+    let whole = filtered(&chain, rate, input.clone());
+
+    // 任意分块的结果与一次处理相同
+    let mut filter = chain.design(rate).unwrap();
+    let mut chunked = input.clone();
+    for chunk in chunked.chunks_mut(37) {
+        filter.filter(chunk);
+    }
+    assert_eq!(chunked, whole);
+
+    // 零相位滤波没有延迟
+    let signal = sine(2.0, 1.0, rate, 2000);
+    let lowpass = FilterChain::new().lowpass(10.0, 4);
+    let causal = filtered(&lowpass, rate, signal.clone());
+    let mut zero_phase = signal.clone();
+    lowpass.clone().zero_phase().design(rate).unwrap().filter_zero_phase(&mut zero_phase);
+    let max_error = |samples: &[f64]| (500..1500).map(|i| (samples[i] - signal[i]).abs()).fold(0.0, f64::max);
+    assert!(max_error(&zero_phase) < 0.01, "zero-phase error {}", max_error(&zero_phase));
+    assert!(max_error(&causal) > 0.1, "causal error {}", max_error(&causal));
+}
+
+#[test]
+fn test_reader_filters() {
+    let filename = "test_filter_reader.edf";
+    create_filter_test_file(filename);
+
+    let chain = FilterChain::new().highpass(1.0, 2).notch(50.0, 30.0);
+    let mut reader = EdfReader::open(filename).unwrap();
+    let raw = reader.read_physical_at(0, 0, 2560).unwrap();
+    let expected = reader.read_filtered(0, &chain).unwrap();
+    assert_eq!(expected, filtered(&chain, 256.0, raw.clone()));
+    // 去除直流和 50 Hz 后只剩 10 Hz 成分
+    assert!((amplitude(&expected) - 40.0).abs() < 1.0);
+
+    // 游标读取跨块保持滤波状态
+    reader.set_filter(0, Some(&chain)).unwrap();
+    let mut streamed = reader.read_physical_samples(0, 1000).unwrap();
+    let mut block = vec![0.0f32; 1000];
+    let read = reader.read_physical_f32_into(0, &mut block).unwrap();
+    streamed.extend(block[..read].iter().map(|&v| v as f64));
+    streamed.extend(reader.read_physical_samples(0, 1000).unwrap());
+    assert_eq!(streamed.len(), 2560);
+    for (i, (a, e)) in streamed.iter().zip(&expected).enumerate() {
+        let tolerance = if (1000..2000).contains(&i) { 1e-4 } else { 1e-9 };
+        assert!((a - e).abs() < tolerance, "sample {}: {} != {}", i, a, e);
+    }
+
+    // 定位后重新开始滤波；位置读取和数字读取不受影响
+    reader.rewind(0).unwrap();
+    assert_eq!(reader.read_physical_samples(0, 100).unwrap(), expected[..100]);
+    assert_eq!(reader.read_physical_at(0, 0, 100).unwrap(), raw[..100]);
+    reader.set_filter(0, None).unwrap();
+    reader.rewind(0).unwrap();
+    assert_eq!(reader.read_physical_samples(0, 100).unwrap(), raw[..100]);
+
+    assert!(matches!(reader.set_filter(0, Some(&chain.clone().zero_phase())), Err(EdfError::InvalidFormat(_))));
+    assert!(matches!(reader.set_filter(0, Some(&FilterChain::new().lowpass(200.0, 2))), Err(EdfError::InvalidFormat(_))));
+    assert!(matches!(reader.set_filter(0, Some(&FilterChain::new().lowpass(20.0, 9))), Err(EdfError::InvalidFormat(_))));
+    assert!(matches!(reader.set_filter(1, Some(&chain)), Err(EdfError::InvalidSignalIndex(1))));
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_writer_filters_and_prefilter() {
+    let filename = "test_filter_writer.edf";
+    let rate = 256.0;
+    let input: Vec<f64> = (0..1280)
+        .map(|i| 20.0 + 30.0 * (2.0 * PI * 8.0 * i as f64 / rate).sin() + 10.0 * (2.0 * PI * 60.0 * i as f64 / rate).sin())
+        .collect();
+
+    let mut writer = EdfWriter::create(filename).unwrap();
+    let acquisition = SignalParamBuilder::eeg("EEG O1", 256).prefilter("HP:0.1Hz").build().unwrap();
+    writer.add_signal(acquisition).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG O2", 256)).unwrap();
+    writer.set_filter(0, &FilterChain::new().lowpass(40.0, 4)).unwrap();
+    writer.set_filter(0, &FilterChain::new().notch(60.0, 30.0)).unwrap();
+    assert!(matches!(writer.set_filter(1, &FilterChain::new().lowpass(40.0, 4).zero_phase()), Err(EdfError::InvalidFormat(_))));
+    assert!(matches!(writer.set_filter(2, &FilterChain::new().lowpass(40.0, 4)), Err(EdfError::InvalidSignalIndex(2))));
+    // 预滤波字段最多 80 个字符
+    let long = (1..=20).fold(FilterChain::new(), |chain, i| chain.notch(i as f64, 5.0));
+    assert!(matches!(writer.set_filter(1, &long), Err(EdfError::InvalidFormat(_))));
+
+    // 滤波状态跨越数据记录和写入方式
+    writer.write_samples(&[&input[..256], &input[..256]]).unwrap();
+    writer.write_records(&[&input[256..768], &input[256..768]]).unwrap();
+    writer.push_samples(0, &input[768..]).unwrap();
+    writer.push_samples(1, &input[768..]).unwrap();
+    assert!(matches!(writer.write_digital_samples(&[vec![0; 256], vec![0; 256]]), Err(EdfError::InvalidFormat(_))));
+    assert!(matches!(writer.set_filter(1, &FilterChain::new().lowpass(40.0, 4)), Err(EdfError::InvalidFormat(_))));
+    writer.finalize().unwrap();
+
+    let reader = EdfReader::open(filename).unwrap();
+    let signals = &reader.header().signals;
+    assert_eq!(signals[0].prefilter, "HP:0.1Hz LP:40Hz N:60Hz");
+    assert_eq!(signals[1].prefilter, "");
+
+    let expected = filtered(&FilterChain::new().lowpass(40.0, 4).notch(60.0, 30.0), rate, input.clone());
+    let resolution = (signals[0].physical_max - signals[0].physical_min) / 65535.0;
+    let written = reader.read_physical_at(0, 0, 1280).unwrap();
+    for (a, e) in written.iter().zip(&expected) {
+        assert!((a - e).abs() <= resolution, "{} != {}", a, e);
+    }
+    let unfiltered = reader.read_physical_at(1, 0, 1280).unwrap();
+    assert!((unfiltered[100] - input[100]).abs() <= resolution);
+
+    cleanup_test_file(filename);
+}
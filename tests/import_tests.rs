@@ -0,0 +1,159 @@
+use chrono::NaiveDate;
+use edfplus::{
+    import_binary, import_csv, EdfError, EdfReader, ImportOptions, ImportSignal, PaddingPolicy,
+    RawSampleType,
+};
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+#[test]
+fn test_import_csv_default_columns() {
+    let input = "test_import_default.csv";
+    let output = "test_import_default.edf";
+    let mut csv = String::from("\"Resp; nasal\";Temperature (very long name);event\n");
+    for i in 0..20 {
+        let marker = match i {
+            3 | 4 => "apnea",
+            12 => "0",
+            15 => "\"arousal; mild\"",
+            _ => "",
+        };
+        csv.push_str(&format!("{};36.5;{}\n", i as f64 * 0.5 - 3.0, marker));
+        if i == 9 {
+            csv.push('\n');
+        }
+    }
+    fs::write(input, csv).unwrap();
+
+    let start = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap().and_hms_opt(23, 59, 30).unwrap();
+    let options = ImportOptions::new(10.0).delimiter(';').marker_column(2).start(start);
+    let summary = import_csv(input, output, &options).unwrap();
+    assert_eq!((summary.rows, summary.records, summary.annotations), (20, 2, 2));
+    assert_eq!(summary.signals[0].label, "Resp; nasal");
+    assert_eq!(summary.signals[1].label, "Temperature (ver");
+    // 自动范围包含所有值，常数信号为 ±1
+    assert_eq!((summary.signals[0].physical_min, summary.signals[0].physical_max), (-3.0, 6.5));
+    assert_eq!((summary.signals[1].physical_min, summary.signals[1].physical_max), (35.5, 37.5));
+    assert!(summary.stats.iter().all(|stats| stats.is_clean() && stats.samples == 20));
+
+    let reader = EdfReader::open(output).unwrap();
+    assert_eq!(reader.header().start_datetime(), start);
+    assert_eq!(reader.header().signals[0].samples_per_record, 10);
+    let resp = reader.read_physical_at(0, 0, 20).unwrap();
+    let step = reader.header().signals[0].quantization().step;
+    for (i, value) in resp.iter().enumerate() {
+        assert!((value - (i as f64 * 0.5 - 3.0)).abs() <= step);
+    }
+
+    // 持续的标记只产生一个注释，"0" 不是标记
+    let annotations: Vec<(i64, &str)> = reader.annotations().iter()
+        .map(|a| (a.onset, a.description.as_str()))
+        .collect();
+    assert_eq!(annotations, vec![(3_000_000, "apnea"), (15_000_000, "arousal; mild")]);
+
+    cleanup_test_file(input);
+    cleanup_test_file(output);
+}
+
+#[test]
+fn test_import_binary_fixed_range() {
+    let input = "test_import_binary.bin";
+    let output = "test_import_binary.edf";
+    let mut dump = Vec::new();
+    for i in 0..230 {
+        for value in [i as f32 * 0.1, -(i as f32), 2.0 * i as f32] {
+            dump.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    fs::write(input, &dump).unwrap();
+
+    // 每个记录 0.5 秒 50 个样本，最后不完整的记录用最后一个值填充
+    let options = ImportOptions::new(100.0)
+        .record_duration(0.5)
+        .signal(ImportSignal::new(2, "Force").unit("N").physical_range(0.0, 1000.0).transducer("Load cell"))
+        .signal(ImportSignal::new(0, "Angle").unit("deg").physical_range(-90.0, 90.0))
+        .padding(PaddingPolicy::RepeatLast);
+    let summary = import_binary(input, output, RawSampleType::Float32, 3, &options).unwrap();
+    assert_eq!((summary.rows, summary.records), (230, 5));
+    assert_eq!(summary.annotations, 0);
+
+    let reader = EdfReader::open(output).unwrap();
+    let header = reader.header();
+    assert_eq!(header.datarecord_duration, 5_000_000);
+    assert_eq!(header.signals[0].label, "Force");
+    assert_eq!(header.signals[0].transducer, "Load cell");
+    assert_eq!(header.signals[1].physical_dimension, "deg");
+    let angle = reader.read_physical_at(1, 220, 30).unwrap();
+    for (i, value) in angle.iter().enumerate() {
+        assert!((value - (220 + i.min(9)) as f64 * 0.1).abs() < 0.01);
+    }
+
+    // 缺省时丢弃不完整的记录
+    let summary = import_binary(input, output, RawSampleType::Float32, 3, &options.clone().padding(PaddingPolicy::Discard)).unwrap();
+    assert_eq!((summary.rows, summary.records), (230, 4));
+    assert_eq!(summary.stats[0].samples, 200);
+
+    cleanup_test_file(input);
+    cleanup_test_file(output);
+}
+
+#[test]
+fn test_import_dense_markers() {
+    let input = "test_import_markers.bin";
+    let output = "test_import_markers.edf";
+    let mut dump = Vec::new();
+    for i in 0..64i16 {
+        dump.extend_from_slice(&i.to_le_bytes());
+        dump.extend_from_slice(&(i % 8 + 1).to_le_bytes());
+    }
+    fs::write(input, &dump).unwrap();
+
+    // 同一个记录中的 32 个标记需要多个注释信号
+    let options = ImportOptions::new(32.0).marker_column(1);
+    let summary = import_binary(input, output, RawSampleType::Int16, 2, &options).unwrap();
+    assert_eq!(summary.signals.len(), 1);
+    assert_eq!(summary.signals[0].label, "Ch1");
+    assert_eq!(summary.annotations, 64);
+
+    let reader = EdfReader::open(output).unwrap();
+    assert_eq!(reader.annotations().len(), 64);
+    assert_eq!(reader.annotations()[9].description, "2");
+    assert_eq!(reader.annotations()[9].onset, 9 * 10_000_000 / 32);
+
+    cleanup_test_file(input);
+    cleanup_test_file(output);
+}
+
+#[test]
+fn test_import_errors() {
+    let input = "test_import_errors.csv";
+    let output = "test_import_errors.edf";
+    fs::write(input, "a,b\n1,2\n3,x\n").unwrap();
+
+    let result = import_csv(input, output, &ImportOptions::new(2.0));
+    match result {
+        Err(EdfError::InvalidFormat(message)) => assert!(message.contains("Line 3, column 1")),
+        other => panic!("expected a parse error, got {:?}", other.map(|s| s.rows)),
+    }
+    let options = ImportOptions::new(2.0).signal(ImportSignal::new(2, "C"));
+    assert!(matches!(import_csv(input, output, &options), Err(EdfError::InvalidFormat(_))));
+    let options = ImportOptions::new(2.5).signal(ImportSignal::new(0, "A").physical_range(0.0, 10.0));
+    assert!(matches!(import_csv(input, output, &options), Err(EdfError::InvalidFormat(_))));
+
+    // 二进制输入必须以完整的行结束
+    fs::write(input, [0u8; 7]).unwrap();
+    assert!(matches!(
+        import_binary(input, output, RawSampleType::Int16, 2, &ImportOptions::new(1.0)),
+        Err(EdfError::InvalidFormat(_))
+    ));
+
+    cleanup_test_file(input);
+    cleanup_test_file(output);
+}
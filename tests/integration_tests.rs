@@ -1,4 +1,4 @@
-use edfplus::{EdfReader, EdfWriter, SignalParam};
+use edfplus::{EdfError, EdfReader, EdfWriter, SignalParam};
 use std::fs;
 use std::path::Path;
 
@@ -373,7 +373,7 @@ fn test_digital_vs_physical_values() {
         writer.add_signal(signal).unwrap();
         
         // 写入已知的物理值
-        let known_values = vec![
+        let known_values = [
             0.0,      // 零点
             100.0,    // 正最大值
             -100.0,   // 负最大值
@@ -412,7 +412,7 @@ fn test_digital_vs_physical_values() {
         
         
         // 验证已知值的转换
-        let known_values = vec![0.0, 100.0, -100.0, 50.0, -50.0];
+        let known_values = [0.0, 100.0, -100.0, 50.0, -50.0];
         
         for (i, &expected_physical) in known_values.iter().enumerate() {
             let actual_physical = physical_samples[i];
@@ -437,7 +437,7 @@ fn test_digital_vs_physical_values() {
         } else {
             println!("Skipping digital/physical comparison due to length mismatch");
             // 至少验证物理数据是合理的
-            let known_values = vec![0.0, 100.0, -100.0, 50.0, -50.0];
+            let known_values = [0.0, 100.0, -100.0, 50.0, -50.0];
             for (i, &expected_physical) in known_values.iter().enumerate() {
                 let actual_physical = physical_samples[i];
                 let tolerance = 0.01;
@@ -450,3 +450,247 @@ fn test_digital_vs_physical_values() {
     
     cleanup_test_file(filename);
 }
+
+#[test]
+fn test_header_numbers_fit_eight_characters() {
+    let filename = "test_header_numbers.edf";
+    
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.set_patient_info("NUM001", "X", "X", "Header Numbers").unwrap();
+        writer.set_datarecord_duration(1.0 / 3.0).unwrap();
+        
+        let mut signal = create_test_eeg_signal();
+        signal.physical_min = -3276.8123;
+        signal.physical_max = 3276.8123;
+        signal.samples_per_record = 100;
+        writer.add_signal(signal).unwrap();
+        
+        let mut ecg = create_test_ecg_signal();
+        ecg.samples_per_record = 100;
+        writer.add_signal(ecg).unwrap();
+        
+        // 舍入记录：两个物理范围 + 记录时长
+        let rounding = writer.header_rounding();
+        assert_eq!(rounding.len(), 3);
+        assert_eq!(rounding[0].field, "datarecord_duration");
+        assert_eq!(rounding[0].signal, None);
+        assert_eq!(rounding[0].text, "0.333333");
+        assert_eq!(rounding[1].field, "physical_min");
+        assert_eq!(rounding[1].signal, Some(0));
+        assert_eq!(rounding[1].text, "-3276.81");
+        assert!((rounding[1].error() - 0.0023).abs() < 1e-9);
+        assert_eq!(rounding[2].field, "physical_max");
+        assert_eq!(rounding[2].text, "3276.812");
+        
+        // 无法表示的值应报错
+        let mut huge = create_test_eeg_signal();
+        huge.physical_max = 1.0e9;
+        assert!(writer.add_signal(huge).is_err());
+        
+        for _ in 0..6 {
+            writer.write_samples(&[vec![1000.0; 100], vec![1.0; 100]]).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+    
+    // 头部未被破坏，后续字段可以正确解析
+    let mut reader = EdfReader::open(filename).unwrap();
+    let header = reader.header();
+    assert_eq!(header.datarecord_duration, 3_333_330);
+    assert_eq!(header.datarecords_in_file, 6);
+    assert_eq!(header.signals.len(), 2);
+    assert_eq!(header.signals[0].physical_min, -3276.81);
+    assert_eq!(header.signals[0].physical_max, 3276.812);
+    assert_eq!(header.signals[0].samples_per_record, 100);
+    assert_eq!(header.signals[1].label, "ECG Lead II");
+    assert_eq!(header.signals[1].physical_dimension, "mV");
+    
+    let eeg = reader.read_physical_samples(0, 600).unwrap();
+    assert_eq!(eeg.len(), 600);
+    let resolution = reader.header().signals[0].bit_value();
+    assert!(eeg.iter().all(|v| (v - 1000.0).abs() <= resolution));
+    let ecg = reader.read_physical_samples(1, 600).unwrap();
+    assert!(ecg.iter().all(|v| (v - 1.0).abs() < 0.001));
+    
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_digital_borrowed_and_multi_record_writes() {
+    let filename = "test_digital_multi_record.edf";
+
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.add_signal(SignalParam::new_eeg("EEG Fp1", 8)).unwrap();
+        writer.add_signal(SignalParam::new_respiration("Resp", 2)).unwrap();
+
+        // 借用切片 + f32
+        let eeg: Vec<f32> = (0..8).map(|i| i as f32 * 10.0).collect();
+        let resp = [1.5f32, -1.5];
+        writer.write_samples(&[&eeg[..], &resp[..]]).unwrap();
+
+        // 原始数字值，i16 和 i32 都可以
+        let counts_eeg: Vec<i16> = (0..8).map(|i| i * 1000 - 4000).collect();
+        writer.write_digital_samples(&[&counts_eeg[..], &[7i16, -7][..]]).unwrap();
+        writer.write_digital_samples(&[vec![40000i32; 8], vec![0i32; 2]]).unwrap();
+
+        // 多记录写入
+        let eeg: Vec<f64> = (0..24).map(|i| i as f64).collect();
+        let resp: Vec<f64> = (0..6).map(|i| -(i as f64)).collect();
+        assert_eq!(writer.write_records(&[&eeg[..], &resp[..]]).unwrap(), 3);
+        let digital_eeg: Vec<i32> = (0..16).collect();
+        assert_eq!(writer.write_digital_records(&[digital_eeg, vec![5, 6, 7, 8]]).unwrap(), 2);
+
+        // 各信号记录数不一致
+        assert!(writer.write_records(&[&eeg[..16], &resp[..]]).is_err());
+        assert!(writer.write_records(&[&eeg[..9], &resp[..2]]).is_err());
+
+        let stats = writer.finalize().unwrap();
+        assert_eq!(stats[0].samples, 8 * 8);
+        assert_eq!(stats[0].clipped, 8); // 超出范围的数字值被截断
+    }
+
+    let mut reader = EdfReader::open(filename).unwrap();
+    assert_eq!(reader.header().datarecords_in_file, 8);
+
+    let eeg = reader.read_physical_samples(0, 8).unwrap();
+    for (i, value) in eeg.iter().enumerate() {
+        assert!((value - i as f64 * 10.0).abs() < 0.02);
+    }
+    let digital = reader.read_digital_samples(0, 8).unwrap();
+    assert_eq!(digital, &[-4000, -3000, -2000, -1000, 0, 1000, 2000, 3000]);
+    assert_eq!(reader.read_digital_samples(0, 8).unwrap(), vec![32767; 8]);
+
+    let eeg = reader.read_physical_samples(0, 24).unwrap();
+    for (i, value) in eeg.iter().enumerate() {
+        assert!((value - i as f64).abs() < 0.02);
+    }
+    assert_eq!(reader.read_digital_samples(0, 16).unwrap(), (0..16).collect::<Vec<i32>>());
+
+    let resp = reader.read_digital_samples(1, 16).unwrap();
+    assert_eq!(&resp[2..4], &[7, -7]);
+    assert_eq!(&resp[12..], &[5, 6, 7, 8]);
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_read_into_buffers() {
+    let filename = "test_read_into_buffers.edf";
+
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        let mut signal = create_test_eeg_signal();
+        signal.digital_min = -1000;
+        signal.digital_max = 1000;
+        writer.add_signal(signal).unwrap();
+        for record in 0..5 {
+            let samples: Vec<f64> = (0..256)
+                .map(|i| 80.0 * ((record * 256 + i) as f64 * 0.05).sin())
+                .collect();
+            writer.write_samples(&[samples]).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = EdfReader::open(filename).unwrap();
+    let expected = reader.read_physical_samples(0, 256 * 5).unwrap();
+    let signal = reader.header().signals[0].clone();
+
+    // 跨记录边界的块读取与一次性读取结果一致
+    reader.rewind(0).unwrap();
+    let mut block = vec![0.0f64; 300];
+    let mut collected = Vec::new();
+    loop {
+        let n = reader.read_physical_into(0, &mut block).unwrap();
+        if n == 0 {
+            break;
+        }
+        collected.extend_from_slice(&block[..n]);
+    }
+    assert_eq!(collected.len(), expected.len());
+    for (a, b) in collected.iter().zip(&expected) {
+        assert!((a - b).abs() < 1e-9);
+    }
+
+    reader.rewind(0).unwrap();
+    let mut single = vec![0.0f32; 1000];
+    assert_eq!(reader.read_physical_f32_into(0, &mut single).unwrap(), 1000);
+    assert!((single[999] as f64 - expected[999]).abs() < 1e-3);
+    assert_eq!(reader.tell(0).unwrap(), 1000);
+
+    let mut digital = vec![0i32; 10];
+    reader.rewind(0).unwrap();
+    assert_eq!(reader.read_digital_into(0, &mut digital).unwrap(), 10);
+    for (d, p) in digital.iter().zip(&expected) {
+        assert!((signal.to_physical(*d) - p).abs() < 1e-9);
+    }
+    assert!(reader.read_digital_into(3, &mut digital).is_err());
+    drop(reader);
+
+    // 写入超出数字范围的原始值，检查限制开关
+    {
+        use std::io::{Seek, SeekFrom, Write};
+        let mut file = fs::OpenOptions::new().write(true).open(filename).unwrap();
+        file.seek(SeekFrom::Start(256 * 3)).unwrap();
+        file.write_all(&2000i16.to_le_bytes()).unwrap();
+    }
+    let mut reader = EdfReader::open(filename).unwrap();
+    assert_eq!(reader.read_digital_samples(0, 1).unwrap(), vec![1000]);
+    reader.set_digital_clamping(false);
+    reader.rewind(0).unwrap();
+    assert_eq!(reader.read_digital_samples(0, 1).unwrap(), vec![2000]);
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_sample_time_conversions() {
+    let filename = "test_sample_time_conversions.edf";
+
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.set_datarecord_duration(0.5).unwrap();
+        writer.set_subsecond_starttime(2_500_000).unwrap(); // 0.25秒
+        writer.add_signal(SignalParam::new_eeg("EEG Fz", 100)).unwrap(); // 200 Hz
+        writer.add_signal(SignalParam::new_respiration("Resp", 5)).unwrap(); // 10 Hz
+        for _ in 0..20 {
+            writer.write_samples(&[vec![0.0; 100], vec![0.0; 5]]).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let reader = EdfReader::open(filename).unwrap();
+    let header = reader.header();
+
+    assert_eq!(header.sampling_frequency(0).unwrap(), 200.0);
+    assert_eq!(header.sampling_frequency(1).unwrap(), 10.0);
+    assert!(header.sampling_frequency(2).is_err());
+
+    assert_eq!(header.sample_to_seconds(0, 300).unwrap(), 1.5);
+    assert_eq!(header.sample_to_seconds(1, 15).unwrap(), 1.5);
+    assert_eq!(header.seconds_to_sample(1, 1.56).unwrap(), 16);
+    assert_eq!(header.seconds_to_sample_floor(1, 1.56).unwrap(), 15);
+    assert_eq!(header.seconds_to_sample_floor(1, 1.6).unwrap(), 16);
+    assert_eq!(header.seconds_to_sample_floor(0, -0.001).unwrap(), -1);
+    assert!(header.seconds_to_sample(0, f64::NAN).is_err());
+
+    // 绝对时间包含亚秒起始时间
+    let start = header.start_datetime();
+    assert_eq!(start, header.start_date.and_time(header.start_time) + chrono::TimeDelta::milliseconds(250));
+    let at = header.sample_to_datetime(0, 201).unwrap();
+    assert_eq!(at - start, chrono::TimeDelta::microseconds(1_005_000));
+    assert_eq!(header.datetime_to_sample(0, &at).unwrap(), 201);
+    assert_eq!(header.datetime_to_sample(1, &at).unwrap(), 10);
+
+    // 超出日期范围的样本序号返回错误而不是溢出
+    assert!(matches!(header.sample_to_datetime(0, i64::MAX), Err(EdfError::InvalidFormat(_))));
+    assert!(matches!(header.sample_to_datetime(0, i64::MIN), Err(EdfError::InvalidFormat(_))));
+
+    let times = header.sample_times(1, 8, 4).unwrap();
+    assert_eq!(times, vec![0.8, 0.9, 1.0, 1.1]);
+    assert!(header.sample_times(0, 0, 0).unwrap().is_empty());
+
+    cleanup_test_file(filename);
+}
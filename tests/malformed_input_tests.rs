@@ -0,0 +1,187 @@
+use edfplus::{fuzzing, EdfError, EdfReader, EdfWriter, SignalParam, StatsOptions};
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+// 确定性的伪随机数（xorshift），保证失败可复现
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+fn valid_file_image(filename: &str) -> Vec<u8> {
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG Cz", 8)).unwrap();
+    writer.add_signal(SignalParam::new_ecg("ECG", 4)).unwrap();
+    writer.add_annotation(0.5, Some(1.25), "Arousal").unwrap();
+    writer.add_annotation(2.0, None, "Lights off").unwrap();
+    for record in 0..4 {
+        writer.write_samples(&[vec![record as f64; 8], vec![-1.0; 4]]).unwrap();
+    }
+    writer.finalize().unwrap();
+    fs::read(filename).unwrap()
+}
+
+fn mutate(original: &[u8], rng: &mut XorShift) -> Vec<u8> {
+    let mut bytes = original.to_vec();
+    // 优先破坏头部和TAL中有意义的字节
+    const INTERESTING: &[u8] = b"0123456789+-. \x14\x15\x00\xff";
+    for _ in 0..1 + rng.below(8) {
+        let position = if rng.below(2) == 0 { rng.below(bytes.len().min(1024)) } else { rng.below(bytes.len()) };
+        bytes[position] = match rng.below(3) {
+            0 => INTERESTING[rng.below(INTERESTING.len())],
+            1 => rng.next() as u8,
+            _ => bytes[position] ^ (1 << rng.below(8)),
+        };
+    }
+    if rng.below(8) == 0 {
+        bytes.truncate(rng.below(bytes.len()));
+    }
+    bytes
+}
+
+#[test]
+fn test_mutated_files_never_panic() {
+    let filename = "test_mutated_input.edf";
+    let original = valid_file_image(filename);
+    let mut rng = XorShift(0x5eed_edf0_1234_5678);
+
+    for _ in 0..400 {
+        let bytes = mutate(&original, &mut rng);
+        let _ = fuzzing::parse_header(&bytes);
+        let _ = fuzzing::parse_signals(&bytes[256.min(bytes.len())..]);
+
+        fs::write(filename, &bytes).unwrap();
+        if let Ok(mut reader) = EdfReader::open(filename) {
+            // 能打开的文件，所有读取路径都必须返回结果而不是 panic
+            for signal in 0..reader.header().signals.len() {
+                let _ = reader.read_physical_samples(signal, 64);
+                let _ = reader.read_digital_at(signal, 3, 16);
+                let _ = reader.seek(signal, 5);
+            }
+            for record in reader.records() {
+                if record.is_err() {
+                    break;
+                }
+            }
+            let _ = reader.signal_statistics(&StatsOptions::default());
+        }
+    }
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_record_count_must_match_file_size() {
+    let filename = "test_record_count_file_size.edf";
+    let original = valid_file_image(filename);
+
+    // 声明的记录数远大于文件中的数据、截断的文件、多出的字节
+    let mut claims_more = original.clone();
+    claims_more[236..244].copy_from_slice(b"99999999");
+    let mut trailing = original.clone();
+    trailing.extend_from_slice(&[0; 10]);
+    for bytes in [claims_more, original[..original.len() - 1].to_vec(), trailing] {
+        fs::write(filename, &bytes).unwrap();
+        match EdfReader::open(filename) {
+            Err(EdfError::InvalidHeader { field, bytes, .. }) => {
+                assert_eq!(field, "number of data records");
+                assert_eq!(bytes, 236..244);
+            }
+            other => panic!("unexpected result {:?}", other.map(|reader| reader.header().datarecords_in_file)),
+        }
+        assert!(fuzzing::parse_header(&bytes).is_err());
+    }
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_malformed_tals_are_errors() {
+    // 错误指向出错TAL在缓冲区中的起始位置
+    let cases: &[(&[u8], usize)] = &[
+        (b"+0\x14\x14\x00+1x\x14bad onset\x14\x00", 5),
+        (b"+0\x14\x14\x00+1\x15x\x14bad duration\x14\x00", 5),
+        (b"+0\x14\x14\x00+1\x15\x15\x14two durations\x14\x00", 5),
+        (b"+0\x14\x14\x00+1\x14stray\x15separator\x14\x00", 5),
+        (b"+0\x14\x14\x00+1\x14unterminated\x00", 5),
+        (b"+0\x14\x14\x00+1\x14no zero\x14", 5),
+        (b"+0\x14\x14\x00x1\x14no sign\x14\x00", 5),
+        (b"+0\x14\x14\x00+99999999999999999999\x14huge\x14\x00", 5),
+        (b"+0\x14\x14\x00\x00\x00+1\x14after padding\x14\x00", 7),
+    ];
+    for &(case, at) in cases {
+        match fuzzing::parse_tal_data(case) {
+            Err(EdfError::InvalidAnnotation { record, offset, .. }) => {
+                assert_eq!((record, offset), (0, at), "{:?}", String::from_utf8_lossy(case));
+            }
+            other => panic!("{:?} parsed as {:?}", String::from_utf8_lossy(case), other),
+        }
+    }
+
+    // 一个TAL中的多个描述、负的onset，以及全为 0 的注释信号都是有效的
+    let annotations = fuzzing::parse_tal_data(b"+0\x14\x14\x00-0.5\x150.25\x14first\x14second\x14\x00\x00").unwrap();
+    let annotations: Vec<(i64, i64, &str)> = annotations.iter()
+        .map(|a| (a.onset, a.duration, a.description.as_str()))
+        .collect();
+    assert_eq!(annotations, vec![(-5_000_000, 2_500_000, "first"), (-5_000_000, 2_500_000, "second")]);
+    assert!(fuzzing::parse_tal_data(&[0; 16]).unwrap().is_empty());
+
+    // 本库写入的相邻TAL之间没有 0x00
+    let annotations = fuzzing::parse_tal_data(b"+0\x14\x14\x00+1\x14a\x14+2\x150.5\x14b\x14\x00").unwrap();
+    let onsets: Vec<(i64, &str)> = annotations.iter().map(|a| (a.onset, a.description.as_str())).collect();
+    assert_eq!(onsets, vec![(10_000_000, "a"), (20_000_000, "b")]);
+}
+
+#[test]
+fn test_malformed_tals_never_panic() {
+    let cases: &[&[u8]] = &[
+        b"",
+        b"\x00",
+        b"\x14",
+        b"+\x14\x14\x00",
+        b"+\x15\x14\x00",
+        b"+.\x14\x14\x00",
+        b"+1..2\x14x\x14\x00",
+        b"+1\x15\x15\x14x\x14\x00",
+        b"+1\x14\x14\x00\x00\x00+2\x14y\x14\x00",
+        b"+99999999999999999999999999999999999999\x14huge\x14\x00",
+        b"+0.00000000000000000000000000000000000001\x14tiny\x14\x00",
+        b"+1\x1599999999999999999999999\x14long\x14\x00",
+        b"+1\x14\xff\xfe\x14\x00",
+        b"-1\x14\x14\x00",
+    ];
+    for case in cases {
+        let _ = fuzzing::parse_tal_data(case);
+    }
+
+    // 过长的onset不再被截断成错误的时间
+    let annotations = fuzzing::parse_tal_data(b"+0\x14\x14\x00+0000000000000000000000000000000012.5\x14late\x14\x00").unwrap();
+    assert_eq!(annotations.len(), 1);
+    assert_eq!(annotations[0].onset, 125_000_000);
+
+    let mut rng = XorShift(0x0ddb_a11c_afe0_0042);
+    for _ in 0..5000 {
+        let len = rng.below(64);
+        let data: Vec<u8> = (0..len)
+            .map(|_| b"+-.0123456789\x14\x15\x00ab"[rng.below(17)])
+            .collect();
+        let _ = fuzzing::parse_tal_data(&data);
+    }
+}
@@ -0,0 +1,236 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use edfplus::{concatenate, CropOptions, EdfError, EdfReader, EdfWriter, SignalParam};
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+fn at(seconds: f64) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 2, 29).unwrap().and_hms_opt(10, 0, 0).unwrap()
+        + chrono::TimeDelta::milliseconds((seconds * 1000.0) as i64)
+}
+
+// 5 个 1 秒记录，数字值从 first_value 开始递增
+fn create_part(filename: &str, start: NaiveDateTime, first_value: i32, annotation: &str) {
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.set_patient_info("MERGE01", "F", "X", "Merge_Test").unwrap();
+    writer.set_start_datetime(start).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG O1", 10)).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG O2", 10)).unwrap();
+    writer.add_annotation(1.0, Some(0.5), annotation).unwrap();
+    for record in 0..5 {
+        let o1: Vec<i32> = (0..10).map(|i| first_value + record * 10 + i).collect();
+        let o2: Vec<i32> = o1.iter().map(|v| -v).collect();
+        writer.write_digital_samples(&[o1, o2]).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_concatenate_contiguous_files() {
+    let parts = ["test_merge_c1.edf", "test_merge_c2.edf", "test_merge_c3.edf"];
+    let output = "test_merge_c_out.edf";
+    create_part(parts[0], at(0.5), 0, "First");
+    create_part(parts[1], at(5.5), 50, "Second");
+    create_part(parts[2], at(10.5), 100, "Third");
+
+    // 输入顺序无关，按开始时间合并
+    let summary = concatenate(&[parts[2], parts[0], parts[1]], output).unwrap();
+    assert_eq!(summary.files, parts.iter().map(Into::into).collect::<Vec<std::path::PathBuf>>());
+    assert_eq!(summary.start, at(0.5));
+    assert_eq!(summary.records, 15);
+    assert!(summary.gaps.is_empty());
+    assert_eq!(summary.annotations, 3);
+
+    let reader = EdfReader::open(output).unwrap();
+    let header = reader.header();
+    assert!(!header.discontinuous);
+    assert_eq!(header.datarecords_in_file, 15);
+    assert_eq!(header.start_datetime(), at(0.5));
+    assert_eq!(header.patient_code, "MERGE01");
+    assert_eq!(reader.read_digital_at(0, 0, 150).unwrap(), (0..150).collect::<Vec<i32>>());
+    assert_eq!(reader.read_digital_at(1, 149, 1).unwrap(), vec![-149]);
+
+    let annotations: Vec<(&str, i64, i64)> = reader.annotations().iter()
+        .map(|a| (a.description.as_str(), a.onset, a.duration))
+        .collect();
+    assert_eq!(annotations, vec![
+        ("First", 10_000_000, 5_000_000),
+        ("Second", 60_000_000, 5_000_000),
+        ("Third", 110_000_000, 5_000_000),
+    ]);
+
+    for part in parts {
+        cleanup_test_file(part);
+    }
+    cleanup_test_file(output);
+}
+
+#[test]
+fn test_concatenate_with_gap_writes_edf_plus_d() {
+    let parts = ["test_merge_d1.edf", "test_merge_d2.edf"];
+    let output = "test_merge_d_out.edf";
+    let cropped = "test_merge_d_crop.edf";
+    create_part(parts[0], at(0.0), 0, "First");
+    create_part(parts[1], at(8.0), 50, "Second");
+
+    let summary = concatenate(&parts, output).unwrap();
+    assert_eq!(summary.records, 10);
+    assert_eq!(summary.gaps.len(), 1);
+    assert_eq!(summary.gaps[0].start_seconds, 5.0);
+    assert_eq!(summary.gaps[0].duration_seconds, 3.0);
+
+    let mut reader = EdfReader::open(output).unwrap();
+    assert!(reader.header().discontinuous);
+    let onsets: Vec<i64> = reader.record_onsets().iter().map(|onset| onset / 10_000_000).collect();
+    assert_eq!(onsets, vec![0, 1, 2, 3, 4, 8, 9, 10, 11, 12]);
+    assert_eq!(reader.annotations()[1].description, "Second");
+    assert_eq!(reader.annotations()[1].onset, 90_000_000);
+
+    // 数据记录迭代器报告时间戳中的开始时间
+    let record = reader.records().nth(5).unwrap().unwrap();
+    assert_eq!(record.onset, 80_000_000);
+    assert_eq!(record.signal(0)[0], 50);
+
+    // 在 EDF+D 文件上裁剪保留窗口内的间隙
+    let reader = EdfReader::open(output).unwrap();
+    let summary = reader.crop(cropped, &CropOptions::seconds(4.5, 9.5)).unwrap();
+    assert_eq!(summary.records, 4..7);
+    let segment = EdfReader::open(cropped).unwrap();
+    assert!(segment.header().discontinuous);
+    assert_eq!(segment.record_onsets(), &[0, 40_000_000, 50_000_000]);
+    assert_eq!(segment.annotations().len(), 1);
+    assert_eq!(segment.annotations()[0].onset, 50_000_000);
+
+    for part in parts {
+        cleanup_test_file(part);
+    }
+    cleanup_test_file(output);
+    cleanup_test_file(cropped);
+}
+
+#[test]
+fn test_concatenate_rejects_incompatible_files() {
+    let first = "test_merge_bad1.edf";
+    let other = "test_merge_bad2.edf";
+    let output = "test_merge_bad_out.edf";
+    create_part(first, at(0.0), 0, "First");
+
+    // 信号标签不同
+    let mut writer = EdfWriter::create(other).unwrap();
+    writer.set_start_datetime(at(5.0)).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG O1", 10)).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG Oz", 10)).unwrap();
+    writer.write_digital_samples(&[vec![0; 10], vec![0; 10]]).unwrap();
+    writer.finalize().unwrap();
+    match concatenate(&[first, other], output) {
+        Err(EdfError::InvalidFormat(message)) => assert!(message.contains("label"), "{}", message),
+        other => panic!("expected a layout error, got {:?}", other.map(|s| s.records)),
+    }
+
+    // 记录时长不同
+    let mut writer = EdfWriter::create(other).unwrap();
+    writer.set_start_datetime(at(5.0)).unwrap();
+    writer.set_datarecord_duration(0.5).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG O1", 10)).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG O2", 10)).unwrap();
+    writer.write_digital_samples(&[vec![0; 10], vec![0; 10]]).unwrap();
+    writer.finalize().unwrap();
+    assert!(matches!(concatenate(&[first, other], output), Err(EdfError::InvalidFormat(_))));
+
+    // 时间重叠
+    create_part(other, at(3.0), 0, "Overlap");
+    match concatenate(&[first, other], output) {
+        Err(EdfError::InvalidFormat(message)) => assert!(message.contains("overlaps"), "{}", message),
+        other => panic!("expected an overlap error, got {:?}", other.map(|s| s.records)),
+    }
+
+    // 注释无法解析的输入不能合并，否则会丢失其间隙和注释
+    create_part(other, at(5.0), 50, "Second");
+    let mut bytes = fs::read(other).unwrap();
+    let stamp = bytes.windows(4).position(|w| w == b"+2\x14\x14").unwrap();
+    bytes[stamp + 1] = b'x';
+    fs::write(other, bytes).unwrap();
+    assert!(matches!(
+        concatenate(&[first, other], output),
+        Err(EdfError::InvalidAnnotation { record: 2, .. })
+    ));
+    assert!(!Path::new(output).exists());
+
+    let no_files: [&str; 0] = [];
+    assert!(matches!(concatenate(&no_files, output), Err(EdfError::InvalidFormat(_))));
+
+    cleanup_test_file(first);
+    cleanup_test_file(other);
+    cleanup_test_file(output);
+}
+
+#[test]
+fn test_writer_record_gaps() {
+    let filename = "test_writer_gaps.edf";
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG Cz", 10)).unwrap();
+    writer.add_annotation(2.5, None, "In gap").unwrap();
+    writer.write_digital_samples(&[vec![1; 10]]).unwrap();
+
+    // 不能与上一个记录重叠
+    assert!(writer.set_next_record_onset(0.5).is_err());
+    assert!(writer.set_next_record_onset(f64::NAN).is_err());
+    writer.set_next_record_onset(4.0).unwrap();
+    writer.write_digital_samples(&[vec![2; 10]]).unwrap();
+    // 紧接上一个记录不产生间隙
+    writer.set_next_record_onset(5.0).unwrap();
+    writer.write_digital_samples(&[vec![3; 10]]).unwrap();
+
+    writer.push_samples(0, &[0.0; 5]).unwrap();
+    assert!(writer.set_next_record_onset(10.0).is_err());
+    writer.finalize().unwrap();
+
+    let reader = EdfReader::open(filename).unwrap();
+    assert!(reader.header().discontinuous);
+    // 未填满的推送样本默认被丢弃
+    assert_eq!(reader.header().datarecords_in_file, 3);
+    assert_eq!(reader.record_onsets(), &[0, 40_000_000, 50_000_000]);
+    // 间隙中的注释保存在间隙之后的记录中
+    assert_eq!(reader.annotations().len(), 1);
+    assert_eq!(reader.annotations()[0].onset, 25_000_000);
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_concatenate_copies_out_of_range_samples() {
+    let parts = ["test_merge_range1.edf", "test_merge_range2.edf"];
+    let output = "test_merge_range_out.edf";
+    for (part, start) in parts.iter().zip([at(0.0), at(1.0)]) {
+        let mut signal = SignalParam::new_eeg("EEG O1", 10);
+        signal.digital_min = -100;
+        signal.digital_max = 100;
+        let mut writer = EdfWriter::create(part).unwrap();
+        writer.set_start_datetime(start).unwrap();
+        writer.add_signal(signal).unwrap();
+        writer.write_digital_samples(&[vec![0i32; 10]]).unwrap();
+        writer.finalize().unwrap();
+
+        // 第一个样本写入超出数字范围的原始值（头部为 3 x 256 字节）
+        let mut bytes = fs::read(part).unwrap();
+        bytes[768..770].copy_from_slice(&500i16.to_le_bytes());
+        fs::write(part, bytes).unwrap();
+    }
+
+    concatenate(&parts, output).unwrap();
+    let mut reader = EdfReader::open(output).unwrap();
+    reader.set_digital_clamping(false);
+    let samples = reader.read_digital_samples(0, 20).unwrap();
+    assert_eq!((samples[0], samples[1], samples[10]), (500, 0, 500));
+
+    for part in parts {
+        cleanup_test_file(part);
+    }
+    cleanup_test_file(output);
+}
@@ -0,0 +1,150 @@
+use edfplus::{EdfError, EdfReader, EdfWriter, Montage, SignalParam};
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+const LABELS: [&str; 4] = ["EEG Fp1", "EEG F7", "EEG A1", "EEG A2"];
+
+// 3 个 1 秒记录，每个 EEG 信号为不同频率的正弦波，另有一个 ECG 信号
+fn create_montage_test_file(filename: &str) {
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.set_patient_info("MONT01", "M", "X", "Montage_Test").unwrap();
+    for label in LABELS {
+        writer.add_signal(SignalParam::new_eeg(label, 100)).unwrap();
+    }
+    let mut ecg = SignalParam::new_eeg("ECG", 100);
+    ecg.physical_dimension = "mV".to_string();
+    writer.add_signal(ecg).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG Slow", 10)).unwrap();
+    writer.add_annotation(1.5, Some(0.5), "Spike").unwrap();
+    for record in 0..3 {
+        let mut samples: Vec<Vec<f64>> = (0..LABELS.len())
+            .map(|s| (0..100).map(|i| 100.0 * ((record * 100 + i) as f64 * 0.01 * (s + 1) as f64).sin()).collect())
+            .collect();
+        samples.push(vec![0.5; 100]);
+        samples.push(vec![1.0; 10]);
+        writer.write_samples(&samples).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+fn assert_close(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len());
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-9, "{} != {}", a, e);
+    }
+}
+
+#[test]
+fn test_bipolar_and_reference_derivations() {
+    let filename = "test_montage_read.edf";
+    create_montage_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    let source: Vec<Vec<f64>> = (0..LABELS.len()).map(|s| reader.read_physical_at(s, 0, 300).unwrap()).collect();
+    let montage = Montage::new()
+        .bipolar("Fp1-F7", "EEG Fp1", "EEG F7")
+        .linked_mastoids(&["EEG Fp1"], "EEG A1", "EEG A2")
+        .common_average(&["EEG Fp1", "EEG F7", "EEG A1"]);
+    assert_eq!(montage.derivations().len(), 5);
+
+    let mut derived = reader.montage(&montage).unwrap();
+    let labels: Vec<&str> = derived.signals().iter().map(|s| s.label.as_str()).collect();
+    assert_eq!(labels, vec!["Fp1-F7", "EEG Fp1-LM", "EEG Fp1-AVG", "EEG F7-AVG", "EEG A1-AVG"]);
+    let fp1_f7 = &derived.signals()[0];
+    assert_eq!(fp1_f7.samples_per_record, 100);
+    assert_eq!(fp1_f7.physical_dimension, "uV");
+    assert_eq!((fp1_f7.physical_min, fp1_f7.physical_max), (-1000.0, 1000.0));
+
+    let expected: Vec<f64> = (0..300).map(|i| source[0][i] - source[1][i]).collect();
+    assert_close(&derived.read_physical_at(0, 0, 1000).unwrap(), &expected);
+    let expected: Vec<f64> = (0..300).map(|i| source[0][i] - (source[2][i] + source[3][i]) / 2.0).collect();
+    assert_close(&derived.read_physical_at(1, 0, 300).unwrap(), &expected);
+    let expected: Vec<f64> = (0..300).map(|i| source[1][i] - (source[0][i] + source[1][i] + source[2][i]) / 3.0).collect();
+    assert_close(&derived.read_physical_at(3, 0, 300).unwrap(), &expected);
+
+    // 游标读取与位置读取一致，且每个派生信号有独立的位置
+    let fp1_f7: Vec<f64> = (0..300).map(|i| source[0][i] - source[1][i]).collect();
+    assert_close(&derived.read_physical_samples(0, 120).unwrap(), &fp1_f7[..120]);
+    assert_eq!(derived.tell(0).unwrap(), 120);
+    assert_eq!(derived.tell(1).unwrap(), 0);
+    let mut buf = vec![0.0; 500];
+    assert_eq!(derived.read_physical_into(0, &mut buf).unwrap(), 180);
+    assert_close(&buf[..180], &fp1_f7[120..]);
+    assert_eq!(derived.seek(0, 1000).unwrap(), 300);
+    derived.rewind(0).unwrap();
+    assert_eq!(derived.tell(0).unwrap(), 0);
+
+    assert!(matches!(derived.read_physical_at(5, 0, 10), Err(EdfError::InvalidSignalIndex(5))));
+    assert!(matches!(derived.read_physical_at(0, -1, 10), Err(EdfError::InvalidFormat(_))));
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_write_montage_to_file() {
+    let filename = "test_montage_write.edf";
+    let output = "test_montage_write_out.edf";
+    create_montage_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    let derived = reader.montage(&Montage::new()
+        .bipolar("Fp1-F7", "EEG Fp1", "EEG F7")
+        .bipolar("A1-A2", "EEG A1", "EEG A2"))
+        .unwrap();
+    derived.write(output).unwrap();
+
+    let written = EdfReader::open(output).unwrap();
+    let header = written.header();
+    assert_eq!(header.patient_code, "MONT01");
+    assert_eq!(header.start_datetime(), reader.header().start_datetime());
+    assert_eq!(header.datarecords_in_file, 3);
+    assert_eq!(header.signals.len(), 2);
+    assert_eq!(header.signals[1].label, "A1-A2");
+    assert_eq!(written.annotations().len(), 1);
+    assert_eq!(written.annotations()[0].description, "Spike");
+    assert_eq!(written.annotations()[0].onset, 15_000_000);
+
+    // 重新量化的误差不超过一个数字单位
+    for signal in 0..2 {
+        let expected = derived.read_physical_at(signal, 0, 300).unwrap();
+        let actual = written.read_physical_at(signal, 0, 300).unwrap();
+        let resolution = (header.signals[signal].physical_max - header.signals[signal].physical_min) / 65535.0;
+        for (a, e) in actual.iter().zip(&expected) {
+            assert!((a - e).abs() <= resolution, "{} != {}", a, e);
+        }
+    }
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output);
+}
+
+#[test]
+fn test_invalid_montages() {
+    let filename = "test_montage_invalid.edf";
+    create_montage_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    let invalid = [
+        Montage::new(),
+        Montage::new().derived("Empty", &[]),
+        Montage::new().bipolar("Fp1-Cz", "EEG Fp1", "EEG Cz"),
+        // 物理单位不同
+        Montage::new().bipolar("Fp1-ECG", "EEG Fp1", "ECG"),
+        // 采样率不同
+        Montage::new().bipolar("Fp1-Slow", "EEG Fp1", "EEG Slow"),
+        // 标签超过 16 个字符
+        Montage::new().bipolar("Frontopolar left-F7", "EEG Fp1", "EEG F7"),
+    ];
+    for montage in invalid {
+        assert!(matches!(reader.montage(&montage), Err(EdfError::InvalidFormat(_))), "{:?}", montage);
+    }
+
+    cleanup_test_file(filename);
+}
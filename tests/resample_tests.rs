@@ -0,0 +1,165 @@
+use edfplus::{EdfError, EdfReader, EdfWriter, ResampleMethod, ResampleOptions, SignalParam};
+use std::f64::consts::PI;
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+fn eeg(t: f64) -> f64 {
+    50.0 * (2.0 * PI * 5.0 * t).sin()
+}
+
+fn emg(t: f64) -> f64 {
+    40.0 * (2.0 * PI * 10.0 * t).sin()
+}
+
+// 20 个 1 秒记录：EEG 256 Hz（5 Hz 信号叠加 60 Hz 干扰）、SpO2 1 Hz（斜坡）、EMG 512 Hz
+fn create_resample_test_file(filename: &str) {
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG C3", 256)).unwrap();
+    writer.add_signal(SignalParam::new_eeg("SpO2", 1)).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EMG", 512)).unwrap();
+    for record in 0..20 {
+        let eeg: Vec<f64> = (0..256)
+            .map(|i| (record * 256 + i) as f64 / 256.0)
+            .map(|t| eeg(t) + 30.0 * (2.0 * PI * 60.0 * t).sin())
+            .collect();
+        let spo2 = vec![90.0 + record as f64];
+        let emg: Vec<f64> = (0..512).map(|i| emg((record * 512 + i) as f64 / 512.0)).collect();
+        writer.write_samples(&[eeg, spo2, emg]).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_shared_time_base() {
+    let filename = "test_resample_time_base.edf";
+    create_resample_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    let resampler = reader.resample(&ResampleOptions::new(32.0)).unwrap();
+    assert_eq!(resampler.rate(), 32.0);
+    assert_eq!(resampler.signals(), &[0, 1, 2]);
+    assert_eq!(resampler.samples_in_file(), 640);
+
+    let all = resampler.read_at(0, 10_000).unwrap();
+    assert_eq!(all.len(), 3);
+    assert!(all.iter().all(|signal| signal.len() == 640));
+    assert!(resampler.read_at(640, 10).unwrap().iter().all(|signal| signal.is_empty()));
+
+    // 非整数比例的目标频率
+    let resampler = reader.resample(&ResampleOptions::new(100.5)).unwrap();
+    assert_eq!(resampler.samples_in_file(), 2010);
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_polyphase_is_anti_aliased() {
+    let filename = "test_resample_alias.edf";
+    create_resample_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    // 60 Hz 干扰高于 25 Hz 的目标奈奎斯特频率，必须被滤除
+    let options = ResampleOptions::new(50.0).signals(&[0]);
+    let filtered = &reader.resample(&options).unwrap().read_at(0, 1000).unwrap()[0];
+    let nearest = &reader.resample(&options.clone().method(ResampleMethod::Nearest)).unwrap().read_at(0, 1000).unwrap()[0];
+
+    let max_error = |samples: &[f64]| (50..950)
+        .map(|k| (samples[k] - eeg(k as f64 / 50.0)).abs())
+        .fold(0.0, f64::max);
+    assert!(max_error(filtered) < 1.0, "filtered error {}", max_error(filtered));
+    // 不做抗混叠时 60 Hz 混叠为 10 Hz
+    assert!(max_error(nearest) > 10.0, "nearest error {}", max_error(nearest));
+
+    // 升采样保持信号不变
+    let emg_samples = &reader.resample(&ResampleOptions::new(1000.0).signals(&[2])).unwrap().read_at(0, 20_000).unwrap()[0];
+    for (k, value) in emg_samples.iter().enumerate().take(19_000).skip(1000) {
+        assert!((value - emg(k as f64 / 1000.0)).abs() < 0.2, "sample {}", k);
+    }
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_linear_interpolation_across_records() {
+    let filename = "test_resample_linear.edf";
+    create_resample_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    let options = ResampleOptions::new(4.0).signals(&[1]).method(ResampleMethod::Linear);
+    let spo2 = &reader.resample(&options).unwrap().read_at(0, 100).unwrap()[0];
+    assert_eq!(spo2.len(), 80);
+    // 每个记录只有一个样本，插值跨越记录边界；最后一个样本之后保持不变
+    for (k, value) in spo2.iter().enumerate() {
+        let expected = (90.0 + k as f64 / 4.0).min(109.0);
+        assert!((value - expected).abs() < 0.05, "sample {}: {} != {}", k, value, expected);
+    }
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_streaming_matches_positional_reads() {
+    let filename = "test_resample_stream.edf";
+    create_resample_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    for method in [ResampleMethod::Nearest, ResampleMethod::Linear, ResampleMethod::default()] {
+        let mut resampler = reader.resample(&ResampleOptions::new(77.0).method(method)).unwrap();
+        let whole = resampler.read_at(0, usize::MAX).unwrap();
+
+        // 任意块大小的流式读取与一次性读取结果相同
+        let mut streamed = vec![Vec::new(); 3];
+        loop {
+            let chunk = resampler.read(37).unwrap();
+            if chunk[0].is_empty() {
+                break;
+            }
+            for (all, part) in streamed.iter_mut().zip(chunk) {
+                all.extend(part);
+            }
+        }
+        assert_eq!(streamed, whole, "{:?}", method);
+        assert_eq!(resampler.tell(), resampler.samples_in_file());
+
+        assert_eq!(resampler.seek(-5), 0);
+        assert_eq!(resampler.seek(100), 100);
+        assert_eq!(resampler.read(10).unwrap()[2], resampler.read_signal_at(2, 100, 10).unwrap());
+    }
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_invalid_resample_options() {
+    let filename = "test_resample_invalid.edf";
+    create_resample_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(matches!(reader.resample(&ResampleOptions::new(rate)), Err(EdfError::InvalidFormat(_))));
+    }
+    let no_taps = ResampleOptions::new(10.0).method(ResampleMethod::Polyphase { zero_crossings: 0 });
+    assert!(matches!(reader.resample(&no_taps), Err(EdfError::InvalidFormat(_))));
+    assert!(matches!(reader.resample(&ResampleOptions::new(10.0).signals(&[3])), Err(EdfError::InvalidSignalIndex(3))));
+
+    // 多相滤波器每侧最多 4096 个抽头：EMG 512 Hz 最多降到 2 Hz
+    assert!(reader.resample(&ResampleOptions::new(2.0).signals(&[2])).is_ok());
+    assert!(matches!(reader.resample(&ResampleOptions::new(1.0)), Err(EdfError::InvalidFormat(_))));
+    let huge = ResampleOptions::new(1000.0).method(ResampleMethod::Polyphase { zero_crossings: usize::MAX });
+    assert!(matches!(reader.resample(&huge), Err(EdfError::InvalidFormat(_))));
+    let nearest = ResampleOptions::new(0.001).method(ResampleMethod::Nearest);
+    assert!(reader.resample(&nearest).is_ok());
+
+    let resampler = reader.resample(&ResampleOptions::new(10.0).signals(&[1])).unwrap();
+    assert!(matches!(resampler.read_signal_at(1, 0, 10), Err(EdfError::InvalidSignalIndex(1))));
+    assert!(matches!(resampler.read_at(-1, 10), Err(EdfError::InvalidFormat(_))));
+
+    cleanup_test_file(filename);
+}
@@ -0,0 +1,133 @@
+use edfplus::{EdfError, EdfReader, EdfWriter, SegmentBoundary, SegmentOptions, SignalParam};
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+// 样本值等于其序号（模 1000）的斜坡信号，便于检查对齐
+fn ramp_value(sample: i64) -> f64 {
+    (sample % 1000) as f64 - 500.0
+}
+
+#[test]
+fn test_event_segments_alignment_and_boundaries() {
+    let filename = "test_event_segments.edf";
+
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.add_signal(SignalParam::new_eeg("EEG Cz", 100)).unwrap();
+        writer.add_signal(SignalParam::new_eog("EOG", 50)).unwrap();
+        writer.set_subsecond_starttime(2_500_000).unwrap(); // 0.25 s
+        writer.add_annotation(0.1, None, "Stimulus A").unwrap();
+        writer.add_annotation(1.234, None, "Stimulus B").unwrap();
+        writer.add_annotation(2.0, None, "Response").unwrap();
+        writer.add_annotation(5.5, None, "Stimulus A").unwrap();
+        writer.add_annotation(9.5, None, "Stimulus C").unwrap();
+
+        for record in 0..10i64 {
+            let eeg: Vec<f64> = (0..100).map(|i| ramp_value(record * 100 + i)).collect();
+            let eog: Vec<f64> = (0..50).map(|i| ramp_value(record * 50 + i)).collect();
+            writer.write_samples(&[eeg, eog]).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = EdfReader::open(filename).unwrap();
+    assert_eq!(reader.header().starttime_subsecond, 2_500_000);
+    assert_eq!(reader.annotations().len(), 5);
+
+    let result = reader
+        .event_segments(|a| a.matches("Stimulus*"), &SegmentOptions::new(-0.2, 0.8))
+        .unwrap();
+
+    assert_eq!(result.segments.len(), 2);
+    assert_eq!(result.skipped.len(), 2);
+    assert_eq!(result.skipped[0].event.description, "Stimulus A");
+    assert_eq!(result.skipped[0].boundary, SegmentBoundary::BeforeStart);
+    assert_eq!(result.skipped[1].event.description, "Stimulus C");
+    assert_eq!(result.skipped[1].boundary, SegmentBoundary::AfterEnd);
+
+    let first = &result.segments[0];
+    assert_eq!(first.event.description, "Stimulus B");
+    assert_eq!(first.event_samples, vec![123, 62]);
+    assert_eq!(first.signals[0].len(), 100);
+    assert_eq!(first.signals[1].len(), 50);
+    // 事件前 20 个样本（EEG）和 10 个样本（EOG）
+    assert!((first.signals[0][20] - ramp_value(123)).abs() < 0.02);
+    assert!((first.signals[0][0] - ramp_value(103)).abs() < 0.02);
+    assert!((first.signals[1][10] - ramp_value(62)).abs() < 0.02);
+
+    let second = &result.segments[1];
+    assert_eq!(second.event_samples, vec![550, 275]);
+    assert!((second.signals[0][20] - ramp_value(550)).abs() < 0.02);
+
+    // 信号选择与无效参数
+    let only_eog = reader
+        .event_segments(|a| a.description == "Response", &SegmentOptions::new(0.0, 0.5).signals(&[1]))
+        .unwrap();
+    assert_eq!(only_eog.segments.len(), 1);
+    assert_eq!(only_eog.segments[0].signals.len(), 1);
+    assert!((only_eog.segments[0].signals[0][0] - ramp_value(100)).abs() < 0.02);
+
+    assert!(reader.event_segments(|_| true, &SegmentOptions::new(0.5, 0.5)).is_err());
+    assert!(reader.event_segments(|_| true, &SegmentOptions::new(0.0, 1.0).signals(&[5])).is_err());
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_event_segments_discontinuous() {
+    let filename = "test_event_segments_discontinuous.edf";
+
+    // EDF+D：第二个记录开始于 100 秒
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.add_signal(SignalParam::new_eeg("EEG Cz", 100)).unwrap();
+        writer.add_annotation(0.5, None, "Stimulus").unwrap();
+        writer.add_annotation(0.9, None, "Stimulus").unwrap();
+        writer.add_annotation(50.0, None, "Stimulus").unwrap();
+        writer.add_annotation(100.5, None, "Stimulus").unwrap();
+        writer.add_annotation(100.9, None, "Stimulus").unwrap();
+        writer.write_samples(&[(0..100).map(ramp_value).collect::<Vec<_>>()]).unwrap();
+        writer.set_next_record_onset(100.0).unwrap();
+        writer.write_samples(&[(100..200).map(ramp_value).collect::<Vec<_>>()]).unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = EdfReader::open(filename).unwrap();
+    assert!(reader.header().discontinuous);
+    assert_eq!(reader.record_onsets(), &[0, 1_000_000_000]);
+
+    // 按记录开始时间换算样本与时间；头部的换算不适用于 EDF+D
+    assert_eq!(reader.seconds_to_sample(0, 100.5).unwrap(), 150);
+    assert_eq!(reader.seconds_to_sample(0, 50.0).unwrap(), 100);
+    assert_eq!(reader.sample_times(0, 99, 2).unwrap(), vec![0.99, 100.0]);
+    assert!(matches!(reader.header().seconds_to_sample(0, 100.5), Err(EdfError::DiscontinuousFile)));
+
+    let result = reader
+        .event_segments(|a| a.matches("Stimulus"), &SegmentOptions::new(-0.2, 0.3))
+        .unwrap();
+
+    let event_samples: Vec<i64> = result.segments.iter().map(|s| s.event_samples[0]).collect();
+    assert_eq!(event_samples, vec![50, 150]);
+    let second = &result.segments[1];
+    assert_eq!(second.event.onset, 1_005_000_000);
+    assert!((second.signals[0][0] - ramp_value(130)).abs() < 0.01);
+    assert!((second.signals[0][49] - ramp_value(179)).abs() < 0.01);
+
+    let skipped: Vec<(i64, SegmentBoundary)> = result.skipped.iter()
+        .map(|s| (s.event.onset, s.boundary))
+        .collect();
+    assert_eq!(skipped, vec![
+        (9_000_000, SegmentBoundary::Gap),
+        (500_000_000, SegmentBoundary::Gap),
+        (1_009_000_000, SegmentBoundary::AfterEnd),
+    ]);
+
+    cleanup_test_file(filename);
+}
@@ -0,0 +1,146 @@
+use edfplus::{CropOptions, EdfError, EdfReader, EdfWriter, SignalParam, SignalRef, SignalSelection};
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+// 4 个 1 秒记录，三个信号的数字值互不相同
+fn create_select_test_file(filename: &str) {
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.set_patient_info("SEL01", "F", "02-FEB-1990", "Select_Test").unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG Fp1", 20)).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG Fp2", 20)).unwrap();
+    let mut ecg = SignalParam::new_eeg("ECG", 5);
+    ecg.physical_dimension = "mV".to_string();
+    writer.add_signal(ecg).unwrap();
+    writer.add_annotation(0.5, None, "Lights off").unwrap();
+    writer.add_annotation(2.0, Some(1.5), "Arousal").unwrap();
+    for record in 0..4 {
+        let fp1: Vec<i32> = (0..20).map(|i| record * 20 + i).collect();
+        let fp2: Vec<i32> = fp1.iter().map(|v| 1000 + v).collect();
+        let ecg: Vec<i32> = (0..5).map(|i| -(record * 5 + i)).collect();
+        writer.write_digital_samples(&[fp1, fp2, ecg]).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_extract_reorders_and_renames_signals() {
+    let filename = "test_select_source.edf";
+    let output = "test_select_out.edf";
+    create_select_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    let selection = SignalSelection::new()
+        .signal("ECG")
+        .renamed(1, "Fp2-Ref");
+    let summary = reader.extract_signals(output, &selection).unwrap();
+    assert_eq!(summary.records, 0..4);
+    assert_eq!(summary.annotations_copied, 2);
+
+    let subset = EdfReader::open(output).unwrap();
+    let header = subset.header();
+    assert_eq!(header.patient_code, "SEL01");
+    assert_eq!(header.start_datetime(), reader.header().start_datetime());
+    assert_eq!(header.datarecords_in_file, 4);
+    let labels: Vec<&str> = header.signals.iter().map(|s| s.label.as_str()).collect();
+    assert_eq!(labels, vec!["ECG", "Fp2-Ref"]);
+    assert_eq!(header.signals[0].physical_dimension, "mV");
+    assert_eq!(header.signals[0].samples_per_record, 5);
+
+    // 数字值原样复制
+    assert_eq!(subset.read_digital_at(0, 0, 20).unwrap(), reader.read_digital_at(2, 0, 20).unwrap());
+    assert_eq!(subset.read_digital_at(1, 0, 80).unwrap(), reader.read_digital_at(1, 0, 80).unwrap());
+    assert_eq!(subset.read_physical_at(1, 0, 80).unwrap(), reader.read_physical_at(1, 0, 80).unwrap());
+
+    let annotations: Vec<(&str, i64, i64)> = subset.annotations().iter()
+        .map(|a| (a.description.as_str(), a.onset, a.duration))
+        .collect();
+    assert_eq!(annotations, vec![("Lights off", 5_000_000, -1), ("Arousal", 20_000_000, 15_000_000)]);
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output);
+}
+
+#[test]
+fn test_selection_with_crop_window() {
+    let filename = "test_select_crop.edf";
+    let output = "test_select_crop_out.edf";
+    create_select_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    // 同一信号可以选择多次
+    let selection: SignalSelection = [SignalRef::Index(0), SignalRef::Label("EEG Fp1".to_string())].into_iter().collect();
+    reader.crop(output, &CropOptions::records(2..4).signals(selection)).unwrap();
+
+    let cropped = EdfReader::open(output).unwrap();
+    assert_eq!(cropped.header().signals.len(), 2);
+    assert_eq!(cropped.header().datarecords_in_file, 2);
+    assert_eq!(cropped.read_digital_at(0, 0, 40).unwrap(), (40..80).collect::<Vec<i32>>());
+    assert_eq!(cropped.read_digital_at(1, 0, 40).unwrap(), (40..80).collect::<Vec<i32>>());
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output);
+}
+
+#[test]
+fn test_invalid_selections() {
+    let filename = "test_select_invalid.edf";
+    let output = "test_select_invalid_out.edf";
+    create_select_test_file(filename);
+
+    let reader = EdfReader::open(filename).unwrap();
+    assert!(matches!(
+        reader.extract_signals(output, &SignalSelection::new()),
+        Err(EdfError::InvalidFormat(_))
+    ));
+    assert!(matches!(
+        reader.extract_signals(output, &SignalSelection::new().signal(3)),
+        Err(EdfError::InvalidSignalIndex(3))
+    ));
+    match reader.extract_signals(output, &SignalSelection::new().signal("EEG Cz")) {
+        Err(EdfError::InvalidFormat(message)) => assert!(message.contains("EEG Cz"), "{}", message),
+        other => panic!("expected an unknown label error, got {:?}", other.map(|s| s.records)),
+    }
+    // 新标签超过 16 个字符
+    assert!(reader.extract_signals(output, &SignalSelection::new().renamed(0, "EEG Fp1 referenced to A2")).is_err());
+    // 选择无效时不创建输出文件
+    assert!(!Path::new(output).exists());
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_extract_copies_out_of_range_samples() {
+    let filename = "test_select_out_of_range.edf";
+    let output = "test_select_out_of_range_out.edf";
+    let mut narrow = SignalParam::new_eeg("EEG Fp1", 10);
+    narrow.digital_min = -100;
+    narrow.digital_max = 100;
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.add_signal(narrow).unwrap();
+    writer.add_signal(SignalParam::new_eeg("EEG Fp2", 10)).unwrap();
+    writer.write_digital_samples(&[vec![0i32; 10], vec![7i32; 10]]).unwrap();
+    writer.finalize().unwrap();
+
+    // 第一个信号的前两个样本写入超出数字范围的原始值（头部为 4 x 256 字节）
+    let mut bytes = fs::read(filename).unwrap();
+    bytes[1024..1026].copy_from_slice(&500i16.to_le_bytes());
+    bytes[1026..1028].copy_from_slice(&(-32768i16).to_le_bytes());
+    fs::write(filename, bytes).unwrap();
+
+    let reader = EdfReader::open(filename).unwrap();
+    reader.extract_signals(output, &SignalSelection::new().signal(1).signal(0)).unwrap();
+    let mut subset = EdfReader::open(output).unwrap();
+    subset.set_digital_clamping(false);
+    assert_eq!(subset.read_digital_samples(1, 3).unwrap(), vec![500, -32768, 0]);
+    assert_eq!(subset.read_digital_samples(0, 3).unwrap(), vec![7, 7, 7]);
+
+    cleanup_test_file(filename);
+    cleanup_test_file(output);
+}
@@ -0,0 +1,232 @@
+use edfplus::{
+    ClipPolicy, EdfError, EdfReader, EdfWriter, InvalidSamplePolicy, SamplePolicy, SampleStats,
+    SignalParam, SignalParamBuilder,
+};
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+#[test]
+fn test_presets_write_read_cycle() {
+    let filename = "test_signal_presets.edf";
+
+    let presets = vec![
+        SignalParam::new_eeg("EEG Fp1", 256),
+        SignalParam::new_ecg("ECG", 256),
+        SignalParam::new_emg("EMG chin", 512),
+        SignalParam::new_eog("EOG left", 128),
+        SignalParam::new_respiration("Resp thorax", 25),
+        SignalParam::new_spo2("SpO2", 1),
+    ];
+
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        for signal in &presets {
+            assert!(signal.validate().is_ok(), "preset {} should be valid", signal.label);
+            assert_eq!(signal.samples_in_file, 0);
+            assert_eq!(signal.digital_min, -32768);
+            assert_eq!(signal.digital_max, 32767);
+            writer.add_signal(signal.clone()).unwrap();
+        }
+
+        let record: Vec<Vec<f64>> = presets
+            .iter()
+            .map(|s| vec![(s.physical_max + s.physical_min) / 2.0; s.samples_per_record as usize])
+            .collect();
+        writer.write_samples(&record).unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let reader = EdfReader::open(filename).unwrap();
+    let header = reader.header();
+    assert_eq!(header.signals.len(), presets.len());
+    for (read, written) in header.signals.iter().zip(&presets) {
+        assert_eq!(read.label, written.label);
+        assert_eq!(read.physical_dimension, written.physical_dimension);
+        assert_eq!(read.transducer, written.transducer);
+        assert_eq!(read.samples_per_record, written.samples_per_record);
+        assert_eq!(read.physical_min, written.physical_min);
+        assert_eq!(read.physical_max, written.physical_max);
+    }
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_builder_overrides_and_validation() {
+    let signal = SignalParamBuilder::eeg("EEG C3-A2", 200)
+        .physical_range(-250.0, 250.0)
+        .digital_range(-2048, 2047)
+        .prefilter("HP:0.3Hz LP:35Hz")
+        .build()
+        .unwrap();
+    assert_eq!(signal.samples_per_record, 200);
+    assert_eq!(signal.physical_min, -250.0);
+    assert_eq!(signal.digital_max, 2047);
+    assert_eq!(signal.prefilter, "HP:0.3Hz LP:35Hz");
+    assert_eq!(signal.physical_dimension, "uV");
+
+    // 16字符标签限制
+    assert!(SignalParam::builder("0123456789abcdef").build().is_ok());
+    assert!(SignalParam::builder("0123456789abcdefg").build().is_err());
+
+    // 8字符单位限制
+    assert!(SignalParam::builder("Temp").physical_dimension("degC/min").build().is_ok());
+    assert!(SignalParam::builder("Temp").physical_dimension("arbitrary").build().is_err());
+
+    // 80字符传感器/预滤波限制
+    assert!(SignalParam::builder("X").transducer(&"t".repeat(80)).build().is_ok());
+    assert!(SignalParam::builder("X").transducer(&"t".repeat(81)).build().is_err());
+    assert!(SignalParam::builder("X").prefilter(&"p".repeat(81)).build().is_err());
+
+    // 仅允许可打印ASCII
+    assert!(SignalParam::builder("Temp").physical_dimension("°C").build().is_err());
+    assert!(SignalParam::builder("EEG\tFp1").build().is_err());
+
+    // 保留标签
+    assert!(SignalParam::builder("EDF Annotations").build().is_err());
+
+    // 无效的每记录样本数
+    assert!(SignalParam::builder("X").samples_per_record(0).build().is_err());
+}
+
+#[test]
+fn test_add_signal_rejects_out_of_range_digital_values() {
+    let filename = "test_signal_digital_range.edf";
+    let mut writer = EdfWriter::create(filename).unwrap();
+
+    let mut too_wide = SignalParam::new_eeg("EEG Fp1", 256);
+    too_wide.digital_max = 40000;
+    assert!(matches!(writer.add_signal(too_wide), Err(EdfError::InvalidFormat(_))));
+
+    let mut too_low = SignalParam::new_eeg("EEG Fp1", 256);
+    too_low.digital_min = -40000;
+    assert!(matches!(writer.add_signal(too_low), Err(EdfError::InvalidFormat(_))));
+
+    let mut inverted = SignalParam::new_eeg("EEG Fp1", 256);
+    inverted.digital_min = 100;
+    inverted.digital_max = -100;
+    assert!(writer.add_signal(inverted).is_err());
+
+    let mut flat = SignalParam::new_eeg("EEG Fp1", 256);
+    flat.digital_min = 0;
+    flat.digital_max = 0;
+    assert!(matches!(writer.add_signal(flat), Err(EdfError::DigitalMinEqualsMax)));
+
+    let mut too_long = SignalParam::new_eeg("EEG Fp1", 256);
+    too_long.label = "EEG Fp1-A1 referential".to_string();
+    assert!(matches!(writer.add_signal(too_long), Err(EdfError::InvalidFormat(_))));
+
+    // 有效信号仍然可以添加
+    assert!(writer.add_signal(SignalParam::new_eeg("EEG Fp1", 256)).is_ok());
+
+    drop(writer);
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_automatic_physical_range() {
+    let filename = "test_signal_auto_range.edf";
+
+    // 不对称、非整数的真实数据范围
+    let samples: Vec<f64> = (0..256)
+        .map(|i| 143.6789 * (i as f64 * 0.1).sin() - 20.12345)
+        .collect();
+    let mut fitted = SignalParam::new_eeg("EEG Fp1", 256);
+    let q = fitted.fit_physical_range(&samples).unwrap();
+    let min = samples.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = samples.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    assert!(fitted.physical_min <= min && fitted.physical_max >= max);
+    assert_eq!((fitted.digital_min, fitted.digital_max), (-32768, 32767));
+    assert_eq!(q.max_error, q.step / 2.0);
+
+    // 目标分辨率
+    let mut resolved = SignalParam::new_eeg("EEG Fp2", 256);
+    let q_res = resolved.set_resolution(0.1).unwrap();
+    assert_eq!((resolved.physical_min, resolved.physical_max), (-3276.8, 3276.7));
+    assert!((q_res.step - 0.1).abs() < 1e-12);
+
+    // 构建器
+    let built = SignalParamBuilder::eeg("EEG C3", 256)
+        .physical_range_from_samples(&samples)
+        .build()
+        .unwrap();
+    assert_eq!(built.physical_min, fitted.physical_min);
+    assert_eq!(built.physical_max, fitted.physical_max);
+    assert!(SignalParamBuilder::eeg("EEG C4", 256).resolution(0.0).build().is_err());
+    assert!(SignalParamBuilder::eeg("EEG C4", 256)
+        .physical_range_from_samples(&[f64::NAN])
+        .build()
+        .is_err());
+
+    // 常数信号
+    let mut flat = SignalParam::new_eeg("EEG O1", 256);
+    flat.fit_physical_range(&[5.0; 10]).unwrap();
+    assert_eq!((flat.physical_min, flat.physical_max), (4.0, 6.0));
+
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.add_signal(fitted.clone()).unwrap();
+        writer.add_signal(resolved.clone()).unwrap();
+        assert!(writer.header_rounding().is_empty());
+        assert_eq!(writer.quantization(), vec![q, q_res]);
+        writer.write_samples(&[samples.clone(), samples.clone()]).unwrap();
+        writer.finalize().unwrap();
+    }
+
+    // 拟合的范围不会截断，误差不超过半个步长
+    let mut reader = EdfReader::open(filename).unwrap();
+    let read = reader.read_physical_samples(0, 256).unwrap();
+    for (original, restored) in samples.iter().zip(&read) {
+        assert!((original - restored).abs() <= q.max_error + 1e-9);
+    }
+
+    cleanup_test_file(filename);
+}
+
+#[test]
+fn test_sample_policy_and_stats() {
+    let filename = "test_signal_sample_policy.edf";
+
+    let mut writer = EdfWriter::create(filename).unwrap();
+    writer.add_signal(SignalParam::new_ecg("ECG", 4)).unwrap();
+    writer.add_signal(SignalParam::new_spo2("SpO2", 4)).unwrap();
+
+    // 默认策略：截断超出范围的值，拒绝 NaN
+    writer.write_samples(&[vec![0.0, 6.0, -7.0, 1.0], vec![97.0; 4]]).unwrap();
+    let result = writer.write_samples(&[vec![0.0, f64::NAN, 0.0, 0.0], vec![97.0; 4]]);
+    assert!(matches!(result, Err(EdfError::InvalidSample(_))));
+
+    // SpO2 的 NaN 用标记值替换，ECG 截断时报错
+    writer.set_signal_sample_policy(1, Some(SamplePolicy {
+        invalid: InvalidSamplePolicy::Marker(-32768),
+        ..Default::default()
+    })).unwrap();
+    writer.set_sample_policy(SamplePolicy { clip: ClipPolicy::Error, ..Default::default() });
+    writer.write_samples(&[vec![0.0; 4], vec![98.0, f64::NAN, f64::INFINITY, 98.0]]).unwrap();
+    let result = writer.write_samples(&[vec![0.0, 0.0, 5.5, 0.0], vec![98.0; 4]]);
+    assert!(matches!(result, Err(EdfError::InvalidSample(_))));
+    assert!(writer.set_signal_sample_policy(2, None).is_err());
+
+    let stats = writer.finalize().unwrap();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0], SampleStats { label: "ECG".to_string(), samples: 8, clipped: 2, invalid: 0 });
+    assert_eq!(stats[1], SampleStats { label: "SpO2".to_string(), samples: 8, clipped: 0, invalid: 2 });
+    assert!(!stats[0].is_clean());
+
+    // 被拒绝的记录没有写入文件
+    let mut reader = EdfReader::open(filename).unwrap();
+    assert_eq!(reader.header().datarecords_in_file, 2);
+    let ecg = reader.read_physical_samples(0, 4).unwrap();
+    assert!((ecg[1] - 5.0).abs() < 1e-3 && (ecg[2] + 5.0).abs() < 1e-3);
+    let digital = reader.read_digital_samples(1, 8).unwrap();
+    assert_eq!(&digital[5..7], &[-32768, -32768]);
+
+    cleanup_test_file(filename);
+}
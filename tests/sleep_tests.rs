@@ -0,0 +1,121 @@
+use edfplus::sleep::{Hypnogram, SleepStage};
+use edfplus::{Annotation, EdfReader, EdfWriter, SignalParam};
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+const EPOCH: i64 = 300_000_000; // 30 s
+
+fn stage(onset_epoch: i64, epochs: i64, description: &str) -> Annotation {
+    Annotation {
+        onset: onset_epoch * EPOCH,
+        duration: epochs * EPOCH,
+        description: description.to_string(),
+    }
+}
+
+#[test]
+fn test_stage_label_variants() {
+    let cases = [
+        ("Sleep stage W", SleepStage::Wake),
+        ("Sleep stage 1", SleepStage::N1),
+        ("Sleep stage N1", SleepStage::N1),
+        ("Sleep stage 2", SleepStage::N2),
+        ("Sleep stage 3", SleepStage::N3),
+        ("Sleep stage 4", SleepStage::N3),
+        ("Sleep stage N3", SleepStage::N3),
+        ("Sleep stage R", SleepStage::Rem),
+        ("Sleep stage REM", SleepStage::Rem),
+        ("Sleep stage ?", SleepStage::Unknown),
+        ("Movement time", SleepStage::Movement),
+        ("SLEEP_STAGE_N2", SleepStage::N2),
+        ("N3", SleepStage::N3),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(SleepStage::from_annotation(text), Some(expected), "{}", text);
+    }
+    assert_eq!(SleepStage::from_annotation("Lights off"), None);
+    assert_eq!(SleepStage::from_annotation("Sleep stage 5"), None);
+}
+
+#[test]
+fn test_hypnogram_statistics() {
+    // W W | N1 | N2 N2 | W | N2 | N3 N3 | R R | W  (12 epochs), R&K 和 AASM 混合标签
+    let annotations = vec![
+        stage(0, 2, "Sleep stage W"),
+        stage(2, 1, "Sleep stage 1"),
+        stage(3, 2, "Sleep stage N2"),
+        stage(5, 1, "Sleep stage W"),
+        Annotation { onset: 6 * EPOCH, duration: -1, description: "Sleep stage 2".to_string() },
+        stage(7, 1, "Sleep stage 3"),
+        stage(8, 1, "Sleep stage 4"),
+        stage(9, 2, "Sleep stage R"),
+        stage(11, 1, "Sleep stage W"),
+        stage(4, 0, "Arousal"),
+    ];
+    let hypnogram = Hypnogram::from_annotations(&annotations, 30.0, 12 * EPOCH + EPOCH / 2).unwrap();
+    assert_eq!(hypnogram.stages.len(), 13);
+    assert_eq!(hypnogram.stages[12], SleepStage::Unknown);
+    assert_eq!(hypnogram.stages[8], SleepStage::N3);
+
+    let stats = hypnogram.statistics();
+    assert_eq!(stats.time_in_bed, 13 * EPOCH);
+    assert_eq!(stats.total_sleep_time, 8 * EPOCH);
+    assert_eq!(stats.sleep_period, 9 * EPOCH);
+    assert_eq!(stats.sleep_latency, Some(2 * EPOCH));
+    assert_eq!(stats.rem_latency, Some(7 * EPOCH));
+    assert_eq!(stats.wake_after_sleep_onset, EPOCH);
+    assert_eq!((stats.wake, stats.n1, stats.n2, stats.n3, stats.rem), (4 * EPOCH, EPOCH, 3 * EPOCH, 2 * EPOCH, 2 * EPOCH));
+    assert_eq!(stats.unknown, EPOCH);
+    assert!((stats.sleep_efficiency - 8.0 / 13.0).abs() < 1e-12);
+
+    // 没有睡眠
+    let awake = Hypnogram::from_annotations(&[stage(0, 4, "Sleep stage W")], 30.0, 4 * EPOCH).unwrap();
+    let stats = awake.statistics();
+    assert_eq!(stats.total_sleep_time, 0);
+    assert_eq!(stats.sleep_latency, None);
+    assert_eq!(stats.rem_latency, None);
+
+    assert!(Hypnogram::from_annotations(&annotations, 0.0, EPOCH).is_err());
+}
+
+#[test]
+fn test_hypnogram_write_read_cycle() {
+    let filename = "test_hypnogram.edf";
+    let original = Hypnogram {
+        epoch_length: EPOCH,
+        stages: vec![
+            SleepStage::Wake, SleepStage::N1, SleepStage::N2, SleepStage::N2,
+            SleepStage::Unknown, SleepStage::N3, SleepStage::Rem, SleepStage::Movement,
+        ],
+    };
+
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.add_signal(SignalParam::new_eeg("EEG C3-M2", 10)).unwrap();
+        original.write_annotations(&mut writer).unwrap();
+        assert_eq!(writer.annotation_count(), 6);
+        writer.set_datarecord_duration(30.0).unwrap();
+        for _ in 0..8 {
+            writer.write_samples(&[vec![0.0; 10]]).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let reader = EdfReader::open(filename).unwrap();
+    let texts: Vec<_> = reader.annotations().iter().map(|a| a.description.as_str()).collect();
+    assert_eq!(texts, vec![
+        "Sleep stage W", "Sleep stage N1", "Sleep stage N2",
+        "Sleep stage N3", "Sleep stage R", "Movement time",
+    ]);
+    let restored = Hypnogram::from_reader(&reader, 30.0).unwrap();
+    assert_eq!(restored, original);
+
+    cleanup_test_file(filename);
+}
@@ -0,0 +1,85 @@
+use edfplus::{EdfError, EdfReader, EdfWriter, FlatLine, SignalParam, StatsOptions};
+use std::fs;
+use std::path::Path;
+
+// 清理测试文件的辅助函数
+fn cleanup_test_file(filename: &str) {
+    if Path::new(filename).exists() {
+        fs::remove_file(filename).ok();
+    }
+}
+
+#[test]
+fn test_signal_statistics_and_quality() {
+    let filename = "test_signal_statistics.edf";
+
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        let mut eeg = SignalParam::new_eeg("EEG Fp1", 100);
+        eeg.digital_min = -1000;
+        eeg.digital_max = 1000;
+        eeg.physical_min = -100.0;
+        eeg.physical_max = 100.0;
+        writer.add_signal(eeg).unwrap();
+        writer.add_signal(SignalParam::new_spo2("SpO2", 1)).unwrap();
+
+        // 10 秒：第 2-8 秒为平直段（6 秒），第 9 秒饱和
+        for second in 0..10 {
+            let eeg: Vec<f64> = match second {
+                2..=7 => vec![12.0; 100],
+                9 => (0..100).map(|i| if i % 2 == 0 { 150.0 } else { -150.0 }).collect(),
+                _ => (0..100).map(|i| 50.0 * (i as f64 * 0.3).sin()).collect(),
+            };
+            writer.write_samples(&[eeg, vec![95.0 + second as f64 * 0.5]]).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    // 在第一个样本写入超出声明范围的原始值（读取时会被限制）
+    {
+        use std::io::{Seek, SeekFrom, Write};
+        let mut file = fs::OpenOptions::new().write(true).open(filename).unwrap();
+        file.seek(SeekFrom::Start(256 * 4)).unwrap();
+        file.write_all(&1500i16.to_le_bytes()).unwrap();
+    }
+
+    let mut reader = EdfReader::open(filename).unwrap();
+    let stats = reader.signal_statistics(&StatsOptions::default()).unwrap();
+    assert_eq!(stats.len(), 2);
+
+    let eeg = &stats[0];
+    assert_eq!(eeg.label, "EEG Fp1");
+    assert_eq!(eeg.samples, 1000);
+    assert_eq!(eeg.digital_max, 1500);
+    assert_eq!(eeg.digital_min, -1000);
+    assert_eq!(eeg.above_range, 1);
+    assert_eq!(eeg.below_range, 0);
+    assert_eq!(eeg.at_digital_max, 50);
+    assert_eq!(eeg.at_digital_min, 50);
+    assert!((eeg.physical_max - 100.0).abs() < 1e-9);
+    assert!((eeg.physical_min + 100.0).abs() < 1e-9);
+    assert!((eeg.saturation_ratio() - 0.101).abs() < 1e-12);
+    assert_eq!(eeg.flat_lines, vec![FlatLine { start: 200, length: 600 }]);
+    assert_eq!(eeg.flat_line_samples(), 600);
+
+    let spo2 = &stats[1];
+    assert_eq!(spo2.samples, 10);
+    assert!((spo2.mean - 97.25).abs() < 0.01);
+    assert!((spo2.physical_min - 95.0).abs() < 0.01);
+    assert!(spo2.flat_lines.is_empty());
+    assert!(spo2.rms >= spo2.mean);
+
+    // 读取器的限制设置保持不变
+    assert_eq!(reader.read_digital_samples(0, 1).unwrap(), vec![1000]);
+
+    // 更短的平直段阈值和容差
+    let options = StatsOptions { flat_line_seconds: 0.5, flat_line_tolerance: 0 };
+    let stats = reader.signal_statistics(&options).unwrap();
+    assert_eq!(stats[0].flat_lines.len(), 1);
+    for invalid in [-1.0, 0.0, f64::NAN, f64::INFINITY] {
+        let options = StatsOptions { flat_line_seconds: invalid, ..Default::default() };
+        assert!(matches!(reader.signal_statistics(&options), Err(EdfError::InvalidFormat(_))));
+    }
+
+    cleanup_test_file(filename);
+}
@@ -1,4 +1,4 @@
-use edfplus::{EdfReader, EdfWriter, SignalParam};
+use edfplus::{EdfReader, EdfWriter, PaddingPolicy, SignalParam};
 use std::fs;
 use std::path::Path;
 use std::thread;
@@ -171,15 +171,15 @@ fn test_random_access_streaming() {
             let actual_second = (current_pos - 256) / 256; // 减去刚读取的256样本
             
             // 计算主要频率成分（简单FFT替代）
-            let mut freq_powers = vec![0.0; 20]; // 检测0-20Hz
-            for freq_idx in 0..20 {
+            let mut freq_powers = [0.0; 20]; // 检测0-20Hz
+            for (freq_idx, freq_power) in freq_powers.iter_mut().enumerate() {
                 let freq = freq_idx as f64;
                 let mut power = 0.0;
                 for (i, &sample) in samples.iter().enumerate() {
                     let t = i as f64 / 256.0;
                     power += sample * (2.0 * std::f64::consts::PI * freq * t).cos();
                 }
-                freq_powers[freq_idx] = power.abs();
+                *freq_power = power.abs();
             }
             
             // 找到最强的频率
@@ -398,6 +398,63 @@ fn test_concurrent_read_access() {
     cleanup_test_file(filename);
 }
 
+#[test]
+fn test_shared_reader_positional_reads() {
+    let filename = "test_shared_reader.edf";
+    
+    fn assert_sync<T: Sync + Send>() {}
+    assert_sync::<EdfReader>();
+    
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        for label in ["EEG A", "EEG B", "EEG C"] {
+            let mut signal = create_streaming_signal();
+            signal.label = label.to_string();
+            writer.add_signal(signal).unwrap();
+        }
+        for second in 0..10 {
+            let records: Vec<Vec<f64>> = (0..3)
+                .map(|s| (0..256).map(|i| (s * 50) as f64 + second as f64 + i as f64 / 256.0).collect())
+                .collect();
+            writer.write_samples(&records).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+    
+    let mut reader = EdfReader::open(filename).unwrap();
+    let expected: Vec<Vec<f64>> = (0..3)
+        .map(|s| {
+            reader.seek(s, 0).unwrap();
+            reader.read_physical_samples(s, 2560).unwrap()
+        })
+        .collect();
+    reader.seek(1, 300).unwrap();
+    
+    // 多线程共享同一个 reader，按位置读取不同信号
+    let shared = &reader;
+    let results: Vec<Vec<f64>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..3)
+            .map(|s| scope.spawn(move || shared.read_physical_at(s, 0, 4096).unwrap()))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(results, expected);
+    
+    // 跨记录读取，结尾截断，游标不变
+    assert_eq!(reader.read_physical_at(2, 250, 20).unwrap(), expected[2][250..270]);
+    assert_eq!(reader.read_digital_at(0, 2550, 100).unwrap().len(), 10);
+    assert!(reader.read_digital_at(0, 2560, 1).unwrap().is_empty());
+    let mut buf = [0.0; 8];
+    assert_eq!(reader.read_physical_at_into(1, 1000, &mut buf).unwrap(), 8);
+    assert_eq!(buf[..], expected[1][1000..1008]);
+    assert_eq!(reader.tell(1).unwrap(), 300);
+    
+    assert!(reader.read_physical_at(3, 0, 1).is_err());
+    assert!(reader.read_physical_at(0, -1, 1).is_err());
+    
+    cleanup_test_file(filename);
+}
+
 #[test]
 fn test_streaming_with_annotations() {
     let filename = "test_streaming_annotations.edf";
@@ -492,3 +549,65 @@ fn test_streaming_with_annotations() {
     
     cleanup_test_file(filename);
 }
+
+#[test]
+fn test_push_samples_variable_packets() {
+    let filename = "test_push_samples.edf";
+    let eeg_total = 256 * 3 + 100;
+    let resp_total = 25 * 3 + 10;
+
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.add_signal(create_streaming_signal()).unwrap();
+        writer.add_signal(SignalParam::new_respiration("Resp", 25)).unwrap();
+        writer.set_padding_policy(PaddingPolicy::RepeatLast);
+
+        // 两个通道以不同的包大小独立到达
+        let eeg: Vec<f64> = (0..eeg_total).map(|i| (i % 200) as f64 - 100.0).collect();
+        let resp: Vec<f64> = (0..resp_total).map(|i| (i % 50) as f64).collect();
+        let mut records = 0;
+        for chunk in eeg.chunks(37) {
+            records += writer.push_samples(0, chunk).unwrap();
+        }
+        assert_eq!(records, 0, "no record is complete without respiration data");
+        for chunk in resp.chunks(7) {
+            records += writer.push_samples(1, chunk).unwrap();
+        }
+        assert_eq!(records, 3);
+        assert_eq!(writer.buffered_samples(), vec![100, 10]);
+
+        // 缓冲中有数据时不能混用 write_samples
+        assert!(writer.write_samples(&[vec![0.0; 256], vec![0.0; 25]]).is_err());
+        assert!(writer.push_samples(2, &[0.0]).is_err());
+
+        let stats = writer.finalize().unwrap();
+        assert_eq!(stats[0].samples, 256 * 4);
+        assert_eq!(stats[1].samples, 25 * 4);
+    }
+
+    let mut reader = EdfReader::open(filename).unwrap();
+    assert_eq!(reader.header().datarecords_in_file, 4);
+    let eeg = reader.read_physical_samples(0, 256 * 4).unwrap();
+    for (i, &value) in eeg.iter().enumerate() {
+        // 最后一个记录用最后的样本填充
+        let expected = (i.min(eeg_total - 1) % 200) as f64 - 100.0;
+        assert!((value - expected).abs() < 0.01, "sample {}: {} != {}", i, value, expected);
+    }
+    let resp = reader.read_physical_samples(1, 25 * 4).unwrap();
+    assert!((resp[resp_total - 1] - 34.0).abs() < 0.01);
+    assert!((resp[99] - 34.0).abs() < 0.01);
+
+    cleanup_test_file(filename);
+
+    // 丢弃策略只保留完整的记录
+    {
+        let mut writer = EdfWriter::create(filename).unwrap();
+        writer.add_signal(create_streaming_signal()).unwrap();
+        writer.push_samples(0, &[1.0; 300]).unwrap();
+        writer.finalize().unwrap();
+    }
+    let reader = EdfReader::open(filename).unwrap();
+    assert_eq!(reader.header().datarecords_in_file, 1);
+
+    cleanup_test_file(filename);
+}